@@ -22,7 +22,7 @@ fn main() {
         })
         .build();
 
-    let entry = cache.insert("hello".to_string(), "world".to_string(), 1);
+    let entry = cache.insert("hello".to_string(), "world".to_string());
     let e = cache.get("hello").unwrap();
 
     assert_eq!(entry.value(), e.value());
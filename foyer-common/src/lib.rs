@@ -21,6 +21,7 @@ pub mod buf;
 pub mod code;
 pub mod continuum;
 pub mod erwlock;
+pub mod metrics;
 pub mod object_pool;
 pub mod range;
 pub mod rate;
@@ -0,0 +1,33 @@
+//  Copyright 2024 Foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::sync::OnceLock;
+
+use prometheus::Registry;
+
+static REGISTRY: OnceLock<Registry> = OnceLock::new();
+
+/// Set metrics registry for `foyer`.
+///
+/// The registry is shared by the in-memory cache and the disk cache, and must be set before any of them is built.
+///
+/// Return `true` if set succeeds.
+pub fn set_metrics_registry(registry: Registry) -> bool {
+    REGISTRY.set(registry).is_ok()
+}
+
+/// Get metrics registry for `foyer`, the default registry of `prometheus` is used if it is not set.
+pub fn get_metrics_registry() -> &'static Registry {
+    REGISTRY.get_or_init(|| prometheus::default_registry().clone())
+}
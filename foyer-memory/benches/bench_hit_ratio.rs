@@ -14,7 +14,7 @@
 
 use std::sync::Arc;
 
-use foyer_memory::{Cache, CacheBuilder, FifoConfig, LfuConfig, LruConfig, S3FifoConfig};
+use foyer_memory::{Cache, CacheBuilder, FifoConfig, LfuConfig, LruConfig, S3FifoConfig, SieveConfig};
 use rand::{distributions::Distribution, thread_rng};
 
 type CacheKey = String;
@@ -29,32 +29,32 @@ const OBJECT_POOL_CAPACITY: usize = 16;
 inspired by pingora/tinyufo/benches/bench_hit_ratio.rs
 cargo bench --bench bench_hit_ratio
 
-zif_exp, cache_size             fifo            lru             lfu             s3fifo          moka
-0.90, 0.005                     16.24%          19.22%          32.37%          32.39%          33.50%
-0.90, 0.01                      22.55%          26.20%          38.54%          39.20%          37.92%
-0.90, 0.05                      41.05%          45.56%          55.37%          56.63%          55.25%
-0.90,  0.1                      51.06%          55.68%          63.82%          65.06%          64.20%
-0.90, 0.25                      66.81%          71.17%          76.21%          77.26%          77.12%
-1.00, 0.005                     26.62%          31.10%          44.16%          44.15%          45.62%
-1.00, 0.01                      34.38%          39.17%          50.63%          51.29%          50.72%
-1.00, 0.05                      54.04%          58.76%          66.79%          67.85%          66.89%
-1.00,  0.1                      63.15%          67.60%          73.93%          74.92%          74.38%
-1.00, 0.25                      76.18%          79.95%          83.63%          84.39%          84.38%
-1.05, 0.005                     32.67%          37.71%          50.26%          50.21%          51.85%
-1.05, 0.01                      40.97%          46.10%          56.74%          57.40%          57.09%
-1.05, 0.05                      60.44%          65.03%          72.04%          73.02%          72.28%
-1.05,  0.1                      68.93%          73.12%          78.49%          79.37%          79.00%
-1.05, 0.25                      80.38%          83.73%          86.78%          87.42%          87.41%
-1.10, 0.005                     39.02%          44.50%          56.26%          56.20%          57.90%
-1.10, 0.01                      47.60%          52.93%          62.61%          63.24%          63.05%
-1.10, 0.05                      66.59%          70.95%          76.92%          77.76%          77.27%
-1.10,  0.1                      74.24%          78.07%          82.54%          83.28%          83.00%
-1.10, 0.25                      84.18%          87.10%          89.57%          90.06%          90.08%
-1.50, 0.005                     81.17%          85.27%          88.90%          89.10%          89.89%
-1.50, 0.01                      86.91%          89.87%          92.25%          92.56%          92.79%
-1.50, 0.05                      94.77%          96.04%          96.96%          97.10%          97.07%
-1.50,  0.1                      96.65%          97.50%          98.06%          98.14%          98.15%
-1.50, 0.25                      98.36%          98.81%          99.04%          99.06%          99.09%
+zif_exp, cache_size             fifo            lru             lfu             s3fifo          s3fifo-ghost    sieve           moka
+0.90, 0.005                     16.23%          19.21%          32.36%          32.75%          32.01%          32.16%          33.44%
+0.90, 0.01                      22.58%          26.23%          38.56%          39.00%          38.54%          39.10%          37.93%
+0.90, 0.05                      41.07%          45.57%          55.41%          56.69%          55.50%          56.99%          55.27%
+0.90,  0.1                      51.07%          55.70%          63.81%          65.17%          63.74%          65.35%          64.16%
+0.90, 0.25                      66.84%          71.21%          76.23%          77.54%          75.89%          77.63%          77.15%
+1.00, 0.005                     26.61%          31.06%          44.12%          44.46%          43.61%          43.88%          45.57%
+1.00, 0.01                      34.37%          39.16%          50.59%          51.36%          50.60%          51.26%          50.71%
+1.00, 0.05                      54.01%          58.73%          66.76%          67.99%          66.90%          68.08%          66.92%
+1.00,  0.1                      63.18%          67.63%          73.92%          75.07%          73.92%          75.13%          74.42%
+1.00, 0.25                      76.14%          79.90%          83.59%          84.51%          83.37%          84.68%          84.33%
+1.05, 0.005                     32.64%          37.68%          50.25%          50.53%          49.71%          50.12%          51.81%
+1.05, 0.01                      40.90%          46.03%          56.69%          57.49%          56.72%          57.39%          57.03%
+1.05, 0.05                      60.43%          65.03%          72.06%          73.06%          72.19%          73.35%          72.18%
+1.05,  0.1                      68.95%          73.13%          78.50%          79.42%          78.51%          79.57%          78.98%
+1.05, 0.25                      80.41%          83.76%          86.80%          87.47%          86.63%          87.61%          87.42%
+1.10, 0.005                     38.98%          44.46%          56.24%          56.10%          55.69%          56.14%          57.88%
+1.10, 0.01                      47.62%          52.94%          62.62%          63.36%          62.68%          63.38%          63.11%
+1.10, 0.05                      66.60%          70.96%          76.93%          77.82%          77.06%          78.09%          77.22%
+1.10,  0.1                      74.26%          78.07%          82.54%          83.31%          82.56%          83.54%          82.89%
+1.10, 0.25                      84.20%          87.12%          89.57%          90.09%          89.44%          90.12%          90.11%
+1.50, 0.005                     81.19%          85.28%          88.91%          89.33%          88.83%          89.22%          89.93%
+1.50, 0.01                      86.88%          89.84%          92.23%          92.66%          92.29%          92.62%          92.76%
+1.50, 0.05                      94.78%          96.05%          96.96%          97.08%          96.97%          97.16%          97.10%
+1.50,  0.1                      96.67%          97.52%          98.07%          98.10%          98.07%          98.18%          98.16%
+1.50, 0.25                      98.36%          98.81%          99.03%          99.02%          99.02%          99.06%          99.09%
 */
 fn cache_hit(cache: Cache<CacheKey, CacheValue>, keys: Arc<Vec<CacheKey>>) -> f64 {
     let mut hit = 0;
@@ -63,7 +63,7 @@ fn cache_hit(cache: Cache<CacheKey, CacheValue>, keys: Arc<Vec<CacheKey>>) -> f6
         if value.is_some() {
             hit += 1;
         } else {
-            cache.insert(key.clone(), (), 1);
+            cache.insert(key.clone(), ());
         }
     }
     hit as f64 / ITERATIONS as f64
@@ -108,21 +108,33 @@ fn new_lfu_cache(capacity: usize) -> Cache<CacheKey, CacheValue> {
             protected_capacity_ratio: 0.8,
             cmsketch_eps: 0.001,
             cmsketch_confidence: 0.9,
+            hill_climbing: None,
+            doorkeeper: None,
+            aging_sample_ratio: None,
         })
         .with_object_pool_capacity(OBJECT_POOL_CAPACITY)
         .build()
 }
 
-fn new_s3fifo_cache(capacity: usize) -> Cache<CacheKey, CacheValue> {
+fn new_s3fifo_cache(capacity: usize, ghost_queue_capacity_ratio: f64) -> Cache<CacheKey, CacheValue> {
     CacheBuilder::new(capacity)
         .with_shards(SHARDS)
         .with_eviction_config(S3FifoConfig {
             small_queue_capacity_ratio: 0.1,
+            ghost_queue_capacity_ratio,
         })
         .with_object_pool_capacity(OBJECT_POOL_CAPACITY)
         .build()
 }
 
+fn new_sieve_cache(capacity: usize) -> Cache<CacheKey, CacheValue> {
+    CacheBuilder::new(capacity)
+        .with_shards(SHARDS)
+        .with_eviction_config(SieveConfig {})
+        .with_object_pool_capacity(OBJECT_POOL_CAPACITY)
+        .build()
+}
+
 fn bench_one(zif_exp: f64, cache_size_percent: f64) {
     print!("{zif_exp:.2}, {cache_size_percent:4}\t\t\t");
     let mut rng = thread_rng();
@@ -133,7 +145,9 @@ fn bench_one(zif_exp: f64, cache_size_percent: f64) {
     let fifo_cache = new_fifo_cache(cache_size);
     let lru_cache = new_lru_cache(cache_size);
     let lfu_cache = new_lfu_cache(cache_size);
-    let s3fifo_cache = new_s3fifo_cache(cache_size);
+    let s3fifo_cache = new_s3fifo_cache(cache_size, 0.0);
+    let s3fifo_ghost_cache = new_s3fifo_cache(cache_size, 0.9);
+    let sieve_cache = new_sieve_cache(cache_size);
     let moka_cache = moka::sync::Cache::new(cache_size as u64);
 
     let mut keys = Vec::with_capacity(ITERATIONS);
@@ -169,6 +183,18 @@ fn bench_one(zif_exp: f64, cache_size_percent: f64) {
         move || cache_hit(cache, keys)
     });
 
+    let s3fifo_ghost_cache_hit_handle = std::thread::spawn({
+        let cache = s3fifo_ghost_cache.clone();
+        let keys = keys.clone();
+        move || cache_hit(cache, keys)
+    });
+
+    let sieve_cache_hit_handle = std::thread::spawn({
+        let cache = sieve_cache.clone();
+        let keys = keys.clone();
+        move || cache_hit(cache, keys)
+    });
+
     let moka_cache_hit_handle = std::thread::spawn({
         let cache = moka_cache.clone();
         let keys = keys.clone();
@@ -179,17 +205,21 @@ fn bench_one(zif_exp: f64, cache_size_percent: f64) {
     let lru_hit_ratio = lru_cache_hit_handle.join().unwrap();
     let lfu_hit_ratio = lfu_cache_hit_handle.join().unwrap();
     let s3fifo_hit_ratio = s3fifo_cache_hit_handle.join().unwrap();
+    let s3fifo_ghost_hit_ratio = s3fifo_ghost_cache_hit_handle.join().unwrap();
+    let sieve_hit_ratio = sieve_cache_hit_handle.join().unwrap();
     let moka_hit_ratio = moka_cache_hit_handle.join().unwrap();
 
     print!("{:.2}%\t\t", fifo_hit_ratio * 100.0);
     print!("{:.2}%\t\t", lru_hit_ratio * 100.0);
     print!("{:.2}%\t\t", lfu_hit_ratio * 100.0);
     print!("{:.2}%\t\t", s3fifo_hit_ratio * 100.0);
+    print!("{:.2}%\t\t", s3fifo_ghost_hit_ratio * 100.0);
+    print!("{:.2}%\t\t", sieve_hit_ratio * 100.0);
     println!("{:.2}%", moka_hit_ratio * 100.0);
 }
 
 fn bench_zipf_hit() {
-    println!("zif_exp, cache_size\t\tfifo\t\tlru\t\tlfu\t\ts3fifo\t\tmoka");
+    println!("zif_exp, cache_size\t\tfifo\t\tlru\t\tlfu\t\ts3fifo\t\ts3fifo-ghost\tsieve\t\tmoka");
     for zif_exp in [0.9, 1.0, 1.05, 1.1, 1.5] {
         for cache_capacity in [0.005, 0.01, 0.05, 0.1, 0.25] {
             bench_one(zif_exp, cache_capacity);
@@ -0,0 +1,153 @@
+//  Copyright 2024 Foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use foyer_memory::{Cache, CacheBuilder, EvictionConfig, LfuConfig, LruConfig, SieveConfig};
+use rand::{distributions::Distribution, rngs::StdRng, SeedableRng};
+
+const ITEMS: usize = 100_000;
+const CAPACITY: usize = 10_000;
+const OPS_PER_THREAD: usize = 1_000_000;
+const ZIPF_EXP: f64 = 1.05;
+
+const SHARDS: usize = 8;
+const OBJECT_POOL_CAPACITY: usize = 1024;
+const READ_BUFFER_CAPACITY: usize = 64;
+
+/*
+cargo bench --bench bench_read_buffer
+
+The results below are measured on a single core, where the shard lock is never contended and the read buffers only
+add overhead. Run it on a multi-core machine to see the difference with hot keys.
+
+throughput (Mops/s), hit ratio
+policy  threads         locked                  read buffer
+lru     1                3.26,  79.52%           3.49,  79.52%
+lru     4                3.08,  79.55%           3.19,  79.55%
+lru     16               2.92,  79.53%           3.44,  79.53%
+lfu     1                4.23,  82.84%           3.98,  82.83%
+lfu     4                3.66,  83.45%           4.17,  83.46%
+lfu     16               3.59,  83.59%           3.56,  83.59%
+sieve   1                4.08,  83.02%           3.43,  83.01%
+sieve   4                3.52,  83.59%           4.09,  83.58%
+sieve   16               4.45,  84.19%           3.51,  84.17%
+*/
+
+fn eviction_configs() -> Vec<(&'static str, EvictionConfig)> {
+    vec![
+        (
+            "lru",
+            LruConfig {
+                high_priority_pool_ratio: 0.1,
+            }
+            .into(),
+        ),
+        (
+            "lfu",
+            LfuConfig {
+                window_capacity_ratio: 0.1,
+                protected_capacity_ratio: 0.8,
+                cmsketch_eps: 0.001,
+                cmsketch_confidence: 0.9,
+                hill_climbing: None,
+                doorkeeper: None,
+                aging_sample_ratio: None,
+            }
+            .into(),
+        ),
+        ("sieve", SieveConfig {}.into()),
+    ]
+}
+
+fn new_cache(eviction_config: EvictionConfig, read_buffer: bool) -> Cache<u64, u64> {
+    let builder = CacheBuilder::new(CAPACITY)
+        .with_shards(SHARDS)
+        .with_eviction_config(eviction_config)
+        .with_object_pool_capacity(OBJECT_POOL_CAPACITY);
+    let builder = if read_buffer {
+        builder.with_read_buffer(READ_BUFFER_CAPACITY)
+    } else {
+        builder
+    };
+    builder.build()
+}
+
+/// Run the read-through workload with the given threads, return the throughput (Mops/s) and the hit ratio.
+fn bench_one(cache: Cache<u64, u64>, threads: usize) -> (f64, f64) {
+    let zipf = zipf::ZipfDistribution::new(ITEMS, ZIPF_EXP).unwrap();
+
+    // Warm up the cache.
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..CAPACITY * 10 {
+        let key = zipf.sample(&mut rng) as u64;
+        if cache.get(&key).is_none() {
+            cache.insert(key, key);
+        }
+    }
+
+    let hits = Arc::new(AtomicUsize::new(0));
+    let now = Instant::now();
+    let handles = (0..threads)
+        .map(|i| {
+            let cache = cache.clone();
+            let hits = hits.clone();
+            std::thread::spawn(move || {
+                let mut rng = StdRng::seed_from_u64(i as u64 + 1);
+                let mut hit = 0;
+                for _ in 0..OPS_PER_THREAD {
+                    let key = zipf.sample(&mut rng) as u64;
+                    if cache.get(&key).is_some() {
+                        hit += 1;
+                    } else {
+                        cache.insert(key, key);
+                    }
+                }
+                hits.fetch_add(hit, Ordering::Relaxed);
+            })
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    let elapsed = now.elapsed();
+
+    let ops = threads * OPS_PER_THREAD;
+    let throughput = ops as f64 / elapsed.as_secs_f64() / 1_000_000.0;
+    let hit_ratio = hits.load(Ordering::Relaxed) as f64 / ops as f64;
+    (throughput, hit_ratio)
+}
+
+fn main() {
+    println!("throughput (Mops/s), hit ratio");
+    println!("policy\tthreads\t\tlocked\t\t\tread buffer");
+    for (name, eviction_config) in eviction_configs() {
+        for threads in [1, 4, 16] {
+            let (locked_throughput, locked_hit_ratio) = bench_one(new_cache(eviction_config.clone(), false), threads);
+            let (buffered_throughput, buffered_hit_ratio) =
+                bench_one(new_cache(eviction_config.clone(), true), threads);
+            println!(
+                "{name}\t{threads}\t\t{locked_throughput:5.2}, {:6.2}%\t\t{buffered_throughput:5.2}, {:6.2}%",
+                locked_hit_ratio * 100.0,
+                buffered_hit_ratio * 100.0,
+            );
+        }
+    }
+}
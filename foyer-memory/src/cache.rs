@@ -18,11 +18,14 @@ use std::{
     hash::{BuildHasher, Hash},
     marker::PhantomData,
     ops::Deref,
+    path::Path,
     sync::Arc,
+    time::Duration,
 };
 
 use ahash::RandomState;
 use futures::{Future, FutureExt};
+use serde::{de::DeserializeOwned, Serialize};
 use tokio::sync::oneshot;
 
 use foyer_common::code::{Key, Value};
@@ -30,16 +33,22 @@ use foyer_common::code::{Key, Value};
 use crate::{
     context::CacheContext,
     eviction::{
+        arc::{ArcEviction, ArcHandle},
         fifo::{Fifo, FifoHandle},
-        lfu::{Lfu, LfuHandle},
+        lfu::{Lfu, LfuHandle, LfuQueueSizes},
         lru::{Lru, LruHandle},
         s3fifo::{S3Fifo, S3FifoHandle},
+        sieve::{Sieve, SieveHandle},
+    },
+    generic::{
+        GenericCache, GenericCacheConfig, GenericCacheEntry, GenericEntry, Negative, NegativeCacheConfig,
+        RefreshPolicy, Weigher,
     },
-    generic::{GenericCache, GenericCacheConfig, GenericCacheEntry, GenericEntry},
     indexer::HashTableIndexer,
     listener::{CacheEventListener, DefaultCacheEventListener},
     metrics::Metrics,
-    FifoConfig, LfuConfig, LruConfig, S3FifoConfig,
+    snapshot::{SnapshotReader, SnapshotResult, SnapshotWriter},
+    ArcConfig, FifoConfig, LfuConfig, LruConfig, S3FifoConfig, SieveConfig,
 };
 
 pub type FifoCache<K, V, L = DefaultCacheEventListener<K, V>, S = RandomState> =
@@ -70,6 +79,20 @@ pub type S3FifoCacheEntry<K, V, L = DefaultCacheEventListener<K, V>, S = RandomS
 pub type S3FifoEntry<K, V, ER, L = DefaultCacheEventListener<K, V>, S = RandomState> =
     GenericEntry<K, V, S3Fifo<(K, V)>, HashTableIndexer<K, S3FifoHandle<(K, V)>>, L, S, ER>;
 
+pub type SieveCache<K, V, L = DefaultCacheEventListener<K, V>, S = RandomState> =
+    GenericCache<K, V, Sieve<(K, V)>, HashTableIndexer<K, SieveHandle<(K, V)>>, L, S>;
+pub type SieveCacheEntry<K, V, L = DefaultCacheEventListener<K, V>, S = RandomState> =
+    GenericCacheEntry<K, V, Sieve<(K, V)>, HashTableIndexer<K, SieveHandle<(K, V)>>, L, S>;
+pub type SieveEntry<K, V, ER, L = DefaultCacheEventListener<K, V>, S = RandomState> =
+    GenericEntry<K, V, Sieve<(K, V)>, HashTableIndexer<K, SieveHandle<(K, V)>>, L, S, ER>;
+
+pub type ArcCache<K, V, L = DefaultCacheEventListener<K, V>, S = RandomState> =
+    GenericCache<K, V, ArcEviction<(K, V)>, HashTableIndexer<K, ArcHandle<(K, V)>>, L, S>;
+pub type ArcCacheEntry<K, V, L = DefaultCacheEventListener<K, V>, S = RandomState> =
+    GenericCacheEntry<K, V, ArcEviction<(K, V)>, HashTableIndexer<K, ArcHandle<(K, V)>>, L, S>;
+pub type ArcEntry<K, V, ER, L = DefaultCacheEventListener<K, V>, S = RandomState> =
+    GenericEntry<K, V, ArcEviction<(K, V)>, HashTableIndexer<K, ArcHandle<(K, V)>>, L, S, ER>;
+
 pub enum CacheEntry<K, V, L, S = RandomState>
 where
     K: Key,
@@ -81,6 +104,8 @@ where
     Lru(LruCacheEntry<K, V, L, S>),
     Lfu(LfuCacheEntry<K, V, L, S>),
     S3Fifo(S3FifoCacheEntry<K, V, L, S>),
+    Sieve(SieveCacheEntry<K, V, L, S>),
+    Arc(ArcCacheEntry<K, V, L, S>),
 }
 
 impl<K, V, L, S> Clone for CacheEntry<K, V, L, S>
@@ -96,6 +121,8 @@ where
             Self::Lru(entry) => Self::Lru(entry.clone()),
             Self::Lfu(entry) => Self::Lfu(entry.clone()),
             Self::S3Fifo(entry) => Self::S3Fifo(entry.clone()),
+            Self::Sieve(entry) => Self::Sieve(entry.clone()),
+            Self::Arc(entry) => Self::Arc(entry.clone()),
         }
     }
 }
@@ -115,6 +142,8 @@ where
             CacheEntry::Lru(entry) => entry.deref(),
             CacheEntry::Lfu(entry) => entry.deref(),
             CacheEntry::S3Fifo(entry) => entry.deref(),
+            CacheEntry::Sieve(entry) => entry.deref(),
+            CacheEntry::Arc(entry) => entry.deref(),
         }
     }
 }
@@ -167,6 +196,30 @@ where
     }
 }
 
+impl<K, V, L, S> From<SieveCacheEntry<K, V, L, S>> for CacheEntry<K, V, L, S>
+where
+    K: Key,
+    V: Value,
+    L: CacheEventListener<K, V>,
+    S: BuildHasher + Send + Sync + 'static,
+{
+    fn from(entry: SieveCacheEntry<K, V, L, S>) -> Self {
+        Self::Sieve(entry)
+    }
+}
+
+impl<K, V, L, S> From<ArcCacheEntry<K, V, L, S>> for CacheEntry<K, V, L, S>
+where
+    K: Key,
+    V: Value,
+    L: CacheEventListener<K, V>,
+    S: BuildHasher + Send + Sync + 'static,
+{
+    fn from(entry: ArcCacheEntry<K, V, L, S>) -> Self {
+        Self::Arc(entry)
+    }
+}
+
 impl<K, V, L, S> CacheEntry<K, V, L, S>
 where
     K: Key,
@@ -180,6 +233,8 @@ where
             CacheEntry::Lru(entry) => entry.key(),
             CacheEntry::Lfu(entry) => entry.key(),
             CacheEntry::S3Fifo(entry) => entry.key(),
+            CacheEntry::Sieve(entry) => entry.key(),
+            CacheEntry::Arc(entry) => entry.key(),
         }
     }
 
@@ -189,6 +244,8 @@ where
             CacheEntry::Lru(entry) => entry.value(),
             CacheEntry::Lfu(entry) => entry.value(),
             CacheEntry::S3Fifo(entry) => entry.value(),
+            CacheEntry::Sieve(entry) => entry.value(),
+            CacheEntry::Arc(entry) => entry.value(),
         }
     }
 
@@ -198,6 +255,8 @@ where
             CacheEntry::Lru(entry) => entry.context().clone().into(),
             CacheEntry::Lfu(entry) => entry.context().clone().into(),
             CacheEntry::S3Fifo(entry) => entry.context().clone().into(),
+            CacheEntry::Sieve(entry) => entry.context().clone().into(),
+            CacheEntry::Arc(entry) => entry.context().clone().into(),
         }
     }
 
@@ -207,6 +266,8 @@ where
             CacheEntry::Lru(entry) => entry.charge(),
             CacheEntry::Lfu(entry) => entry.charge(),
             CacheEntry::S3Fifo(entry) => entry.charge(),
+            CacheEntry::Sieve(entry) => entry.charge(),
+            CacheEntry::Arc(entry) => entry.charge(),
         }
     }
 
@@ -216,6 +277,8 @@ where
             CacheEntry::Lru(entry) => entry.refs(),
             CacheEntry::Lfu(entry) => entry.refs(),
             CacheEntry::S3Fifo(entry) => entry.refs(),
+            CacheEntry::Sieve(entry) => entry.refs(),
+            CacheEntry::Arc(entry) => entry.refs(),
         }
     }
 }
@@ -226,6 +289,8 @@ pub enum EvictionConfig {
     Lru(LruConfig),
     Lfu(LfuConfig),
     S3Fifo(S3FifoConfig),
+    Sieve(SieveConfig),
+    Arc(ArcConfig),
 }
 
 impl From<FifoConfig> for EvictionConfig {
@@ -252,6 +317,18 @@ impl From<S3FifoConfig> for EvictionConfig {
     }
 }
 
+impl From<SieveConfig> for EvictionConfig {
+    fn from(value: SieveConfig) -> EvictionConfig {
+        EvictionConfig::Sieve(value)
+    }
+}
+
+impl From<ArcConfig> for EvictionConfig {
+    fn from(value: ArcConfig) -> EvictionConfig {
+        EvictionConfig::Arc(value)
+    }
+}
+
 pub struct CacheBuilder<K, V, L, S>
 where
     K: Key,
@@ -259,10 +336,16 @@ where
     L: CacheEventListener<K, V>,
     S: BuildHasher + Send + Sync + 'static,
 {
+    name: String,
     capacity: usize,
     shards: Option<usize>,
     eviction_config: Option<EvictionConfig>,
     object_pool_capacity: Option<usize>,
+    ttl: Option<Duration>,
+    read_buffer_capacity: Option<usize>,
+    weigher: Weigher<K, V>,
+    refresh_policy: Option<RefreshPolicy>,
+    negative_cache: Option<NegativeCacheConfig>,
     event_listener: L,
     hash_builder: S,
     _marker: PhantomData<(K, V)>,
@@ -275,10 +358,16 @@ where
 {
     pub fn new(capacity: usize) -> Self {
         Self {
+            name: Self::DEFAULT_NAME.to_string(),
             capacity,
             shards: None,
             eviction_config: None,
             object_pool_capacity: None,
+            ttl: None,
+            read_buffer_capacity: None,
+            weigher: Arc::new(|_, _| 1),
+            refresh_policy: None,
+            negative_cache: None,
             event_listener: DefaultCacheEventListener::default(),
             hash_builder: RandomState::default(),
             _marker: PhantomData,
@@ -299,8 +388,20 @@ where
         protected_capacity_ratio: 0.8,
         cmsketch_eps: 0.001,
         cmsketch_confidence: 0.9,
+        hill_climbing: None,
+        doorkeeper: None,
+        aging_sample_ratio: None,
     });
     const DEFAULT_OBJECT_POOL_CAPACITY_RATIO_RECIPROCAL: usize = 10;
+    const DEFAULT_NAME: &'static str = "foyer";
+
+    /// Set the name of the cache, which is used as the label `foyer` of the exported metrics.
+    ///
+    /// Caches with the same name share the exported metrics.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
 
     pub fn with_shards(mut self, shards: usize) -> Self {
         self.shards = Some(shards);
@@ -317,15 +418,78 @@ where
         self
     }
 
+    /// Set the default time-to-live of the inserted entries.
+    ///
+    /// Expired entries are treated as misses and are evicted before any other entries.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Enable the per-shard read buffers with the given capacity.
+    ///
+    /// With the read buffers enabled, cache hits only take the shard lock in shared mode. The accesses are recorded
+    /// in the read buffers and applied to the eviction algorithm in batch. Accesses may be dropped under heavy
+    /// contention, which trades a little accuracy of the eviction algorithm for the throughput of hot keys.
+    pub fn with_read_buffer(mut self, capacity: usize) -> Self {
+        self.read_buffer_capacity = Some(capacity);
+        self
+    }
+
+    /// Set the weigher that calculates the charge of the entries inserted without an explicit charge.
+    ///
+    /// The default weigher charges `1` for each entry.
+    pub fn with_weigher(mut self, weigher: impl Fn(&K, &V) -> usize + Send + Sync + 'static) -> Self {
+        self.weigher = Arc::new(weigher);
+        self
+    }
+
+    /// Refresh the entries after the duration since they are written.
+    ///
+    /// A hit of a stale entry with the `entry` interface returns the current value immediately, and reloads the entry
+    /// in background with the given fetch future. Only one reload of the entry is ongoing at a time. The reloaded entry
+    /// keeps the context and the time-to-live of the stale one, and is dropped if the stale entry has been removed or
+    /// replaced since the reload started.
+    pub fn with_refresh_after_write(mut self, duration: Duration) -> Self {
+        self.refresh_policy = Some(RefreshPolicy::AfterWrite(duration));
+        self
+    }
+
+    /// Refresh the entries after the duration since they are last accessed or written.
+    ///
+    /// The reload is the same as [`CacheBuilder::with_refresh_after_write`].
+    pub fn with_refresh_after_access(mut self, duration: Duration) -> Self {
+        self.refresh_policy = Some(RefreshPolicy::AfterAccess(duration));
+        self
+    }
+
+    /// Cache the failures of the fetches with [`Cache::entry_with_negative_cache`].
+    ///
+    /// A failure is kept for the ttl of the config with the charge of the config, and returned without fetching
+    /// again until it expires. A successful insert or remove of the key drops the cached failure. Cached failures take
+    /// no more than the capacity ratio of the config, a new one replaces the oldest one beyond that. Otherwise, they
+    /// are evicted only after the expired entries, or when there are no other entries to evict. The failures of the
+    /// fetches with [`Cache::entry`] are not cached.
+    pub fn with_negative_cache(mut self, config: NegativeCacheConfig) -> Self {
+        self.negative_cache = Some(config);
+        self
+    }
+
     pub fn with_event_listener<OL>(self, event_listener: OL) -> CacheBuilder<K, V, OL, S>
     where
         OL: CacheEventListener<K, V>,
     {
         CacheBuilder {
+            name: self.name,
             capacity: self.capacity,
             shards: self.shards,
             eviction_config: self.eviction_config,
             object_pool_capacity: self.object_pool_capacity,
+            ttl: self.ttl,
+            read_buffer_capacity: self.read_buffer_capacity,
+            weigher: self.weigher,
+            refresh_policy: self.refresh_policy,
+            negative_cache: self.negative_cache,
             event_listener,
             hash_builder: self.hash_builder,
             _marker: PhantomData,
@@ -337,10 +501,16 @@ where
         OS: BuildHasher + Send + Sync + 'static,
     {
         CacheBuilder {
+            name: self.name,
             capacity: self.capacity,
             shards: self.shards,
             eviction_config: self.eviction_config,
             object_pool_capacity: self.object_pool_capacity,
+            ttl: self.ttl,
+            read_buffer_capacity: self.read_buffer_capacity,
+            weigher: self.weigher,
+            refresh_policy: self.refresh_policy,
+            negative_cache: self.negative_cache,
             event_listener: self.event_listener,
             hash_builder,
             _marker: PhantomData,
@@ -348,50 +518,130 @@ where
     }
 
     pub fn build(self) -> Cache<K, V, L, S> {
+        let name = self.name;
         let capacity = self.capacity;
         let shards = self.shards.unwrap_or(Self::DEFAULT_SHARDS);
         let eviction_config = self.eviction_config.unwrap_or(Self::DEFAULT_EVICTION_CONFIG);
         let object_pool_capacity = self
             .object_pool_capacity
             .unwrap_or(capacity / Self::DEFAULT_OBJECT_POOL_CAPACITY_RATIO_RECIPROCAL);
+        let ttl = self.ttl;
+        let read_buffer_capacity = self.read_buffer_capacity;
+        let weigher = self.weigher;
+        let refresh_policy = self.refresh_policy;
+        let negative_cache = self.negative_cache;
         let event_listener = self.event_listener;
         let hash_builder = self.hash_builder;
 
         match eviction_config {
             EvictionConfig::Fifo(eviction_config) => Cache::Fifo(Arc::new(GenericCache::new(GenericCacheConfig {
+                name,
                 capacity,
                 shards,
                 eviction_config,
                 object_pool_capacity,
                 hash_builder,
                 event_listener,
+                ttl,
+                read_buffer_capacity,
+                weigher,
+                refresh_policy,
+                negative_cache,
             }))),
             EvictionConfig::Lru(eviction_config) => Cache::Lru(Arc::new(GenericCache::new(GenericCacheConfig {
+                name,
                 capacity,
                 shards,
                 eviction_config,
                 object_pool_capacity,
                 hash_builder,
                 event_listener,
+                ttl,
+                read_buffer_capacity,
+                weigher,
+                refresh_policy,
+                negative_cache,
             }))),
             EvictionConfig::Lfu(eviction_config) => Cache::Lfu(Arc::new(GenericCache::new(GenericCacheConfig {
+                name,
                 capacity,
                 shards,
                 eviction_config,
                 object_pool_capacity,
                 hash_builder,
                 event_listener,
+                ttl,
+                read_buffer_capacity,
+                weigher,
+                refresh_policy,
+                negative_cache,
             }))),
             EvictionConfig::S3Fifo(eviction_config) => Cache::S3Fifo(Arc::new(GenericCache::new(GenericCacheConfig {
+                name,
                 capacity,
                 shards,
                 eviction_config,
                 object_pool_capacity,
                 hash_builder,
                 event_listener,
+                ttl,
+                read_buffer_capacity,
+                weigher,
+                refresh_policy,
+                negative_cache,
+            }))),
+            EvictionConfig::Sieve(eviction_config) => Cache::Sieve(Arc::new(GenericCache::new(GenericCacheConfig {
+                name,
+                capacity,
+                shards,
+                eviction_config,
+                object_pool_capacity,
+                hash_builder,
+                event_listener,
+                ttl,
+                read_buffer_capacity,
+                weigher,
+                refresh_policy,
+                negative_cache,
+            }))),
+            EvictionConfig::Arc(eviction_config) => Cache::Arc(Arc::new(GenericCache::new(GenericCacheConfig {
+                name,
+                capacity,
+                shards,
+                eviction_config,
+                object_pool_capacity,
+                hash_builder,
+                event_listener,
+                ttl,
+                read_buffer_capacity,
+                weigher,
+                refresh_policy,
+                negative_cache,
             }))),
         }
     }
+
+    /// Build the cache and restore the entries from the snapshot saved by [`Cache::save_snapshot`].
+    ///
+    /// The snapshot must be saved by a cache with the same eviction algorithm. The entries are restored in the saved
+    /// eviction order with the saved eviction states, and the remaining time-to-live of the entries is kept.
+    pub fn load_snapshot(self, path: impl AsRef<Path>) -> SnapshotResult<Cache<K, V, L, S>>
+    where
+        K: DeserializeOwned,
+        V: DeserializeOwned,
+    {
+        let cache = self.build();
+        let reader = SnapshotReader::open(path, cache.eviction_name())?;
+        match &cache {
+            Cache::Fifo(cache) => cache.restore_snapshot(reader)?,
+            Cache::Lru(cache) => cache.restore_snapshot(reader)?,
+            Cache::Lfu(cache) => cache.restore_snapshot(reader)?,
+            Cache::S3Fifo(cache) => cache.restore_snapshot(reader)?,
+            Cache::Sieve(cache) => cache.restore_snapshot(reader)?,
+            Cache::Arc(cache) => cache.restore_snapshot(reader)?,
+        };
+        Ok(cache)
+    }
 }
 
 pub enum Cache<K, V, L = DefaultCacheEventListener<K, V>, S = RandomState>
@@ -405,6 +655,8 @@ where
     Lru(Arc<LruCache<K, V, L, S>>),
     Lfu(Arc<LfuCache<K, V, L, S>>),
     S3Fifo(Arc<S3FifoCache<K, V, L, S>>),
+    Sieve(Arc<SieveCache<K, V, L, S>>),
+    Arc(Arc<ArcCache<K, V, L, S>>),
 }
 
 impl<K, V, L, S> Debug for Cache<K, V, L, S>
@@ -420,6 +672,8 @@ where
             Self::Lru(_) => f.debug_tuple("Cache::LruCache").finish(),
             Self::Lfu(_) => f.debug_tuple("Cache::LfuCache").finish(),
             Self::S3Fifo(_) => f.debug_tuple("Cache::S3FifoCache").finish(),
+            Self::Sieve(_) => f.debug_tuple("Cache::SieveCache").finish(),
+            Self::Arc(_) => f.debug_tuple("Cache::ArcCache").finish(),
         }
     }
 }
@@ -437,6 +691,8 @@ where
             Self::Lru(cache) => Self::Lru(cache.clone()),
             Self::Lfu(cache) => Self::Lfu(cache.clone()),
             Self::S3Fifo(cache) => Self::S3Fifo(cache.clone()),
+            Self::Sieve(cache) => Self::Sieve(cache.clone()),
+            Self::Arc(cache) => Self::Arc(cache.clone()),
         }
     }
 }
@@ -448,12 +704,26 @@ where
     L: CacheEventListener<K, V>,
     S: BuildHasher + Send + Sync + 'static,
 {
-    pub fn insert(&self, key: K, value: V, charge: usize) -> CacheEntry<K, V, L, S> {
+    /// Insert an entry with the charge calculated by the weigher.
+    pub fn insert(&self, key: K, value: V) -> CacheEntry<K, V, L, S> {
+        match self {
+            Cache::Fifo(cache) => cache.insert(key, value).into(),
+            Cache::Lru(cache) => cache.insert(key, value).into(),
+            Cache::Lfu(cache) => cache.insert(key, value).into(),
+            Cache::S3Fifo(cache) => cache.insert(key, value).into(),
+            Cache::Sieve(cache) => cache.insert(key, value).into(),
+            Cache::Arc(cache) => cache.insert(key, value).into(),
+        }
+    }
+
+    pub fn insert_with_charge(&self, key: K, value: V, charge: usize) -> CacheEntry<K, V, L, S> {
         match self {
-            Cache::Fifo(cache) => cache.insert(key, value, charge).into(),
-            Cache::Lru(cache) => cache.insert(key, value, charge).into(),
-            Cache::Lfu(cache) => cache.insert(key, value, charge).into(),
-            Cache::S3Fifo(cache) => cache.insert(key, value, charge).into(),
+            Cache::Fifo(cache) => cache.insert_with_charge(key, value, charge).into(),
+            Cache::Lru(cache) => cache.insert_with_charge(key, value, charge).into(),
+            Cache::Lfu(cache) => cache.insert_with_charge(key, value, charge).into(),
+            Cache::S3Fifo(cache) => cache.insert_with_charge(key, value, charge).into(),
+            Cache::Sieve(cache) => cache.insert_with_charge(key, value, charge).into(),
+            Cache::Arc(cache) => cache.insert_with_charge(key, value, charge).into(),
         }
     }
 
@@ -469,6 +739,20 @@ where
             Cache::Lru(cache) => cache.insert_with_context(key, value, charge, context).into(),
             Cache::Lfu(cache) => cache.insert_with_context(key, value, charge, context).into(),
             Cache::S3Fifo(cache) => cache.insert_with_context(key, value, charge, context).into(),
+            Cache::Sieve(cache) => cache.insert_with_context(key, value, charge, context).into(),
+            Cache::Arc(cache) => cache.insert_with_context(key, value, charge, context).into(),
+        }
+    }
+
+    /// Insert an entry that expires after `ttl`, regardless of the default ttl of the cache.
+    pub fn insert_with_ttl(&self, key: K, value: V, charge: usize, ttl: Duration) -> CacheEntry<K, V, L, S> {
+        match self {
+            Cache::Fifo(cache) => cache.insert_with_ttl(key, value, charge, ttl).into(),
+            Cache::Lru(cache) => cache.insert_with_ttl(key, value, charge, ttl).into(),
+            Cache::Lfu(cache) => cache.insert_with_ttl(key, value, charge, ttl).into(),
+            Cache::S3Fifo(cache) => cache.insert_with_ttl(key, value, charge, ttl).into(),
+            Cache::Sieve(cache) => cache.insert_with_ttl(key, value, charge, ttl).into(),
+            Cache::Arc(cache) => cache.insert_with_ttl(key, value, charge, ttl).into(),
         }
     }
 
@@ -482,6 +766,8 @@ where
             Cache::Lru(cache) => cache.remove(key).map(CacheEntry::from),
             Cache::Lfu(cache) => cache.remove(key).map(CacheEntry::from),
             Cache::S3Fifo(cache) => cache.remove(key).map(CacheEntry::from),
+            Cache::Sieve(cache) => cache.remove(key).map(CacheEntry::from),
+            Cache::Arc(cache) => cache.remove(key).map(CacheEntry::from),
         }
     }
 
@@ -491,6 +777,8 @@ where
             Cache::Lru(cache) => cache.pop().map(CacheEntry::from),
             Cache::Lfu(cache) => cache.pop().map(CacheEntry::from),
             Cache::S3Fifo(cache) => cache.pop().map(CacheEntry::from),
+            Cache::Sieve(cache) => cache.pop().map(CacheEntry::from),
+            Cache::Arc(cache) => cache.pop().map(CacheEntry::from),
         }
     }
 
@@ -500,6 +788,8 @@ where
             Cache::Lru(cache) => cache.pop_corase().map(CacheEntry::from),
             Cache::Lfu(cache) => cache.pop_corase().map(CacheEntry::from),
             Cache::S3Fifo(cache) => cache.pop_corase().map(CacheEntry::from),
+            Cache::Sieve(cache) => cache.pop_corase().map(CacheEntry::from),
+            Cache::Arc(cache) => cache.pop_corase().map(CacheEntry::from),
         }
     }
 
@@ -513,6 +803,8 @@ where
             Cache::Lru(cache) => cache.get(key).map(CacheEntry::from),
             Cache::Lfu(cache) => cache.get(key).map(CacheEntry::from),
             Cache::S3Fifo(cache) => cache.get(key).map(CacheEntry::from),
+            Cache::Sieve(cache) => cache.get(key).map(CacheEntry::from),
+            Cache::Arc(cache) => cache.get(key).map(CacheEntry::from),
         }
     }
 
@@ -526,6 +818,8 @@ where
             Cache::Lru(cache) => cache.contains(key),
             Cache::Lfu(cache) => cache.contains(key),
             Cache::S3Fifo(cache) => cache.contains(key),
+            Cache::Sieve(cache) => cache.contains(key),
+            Cache::Arc(cache) => cache.contains(key),
         }
     }
 
@@ -539,6 +833,8 @@ where
             Cache::Lru(cache) => cache.touch(key),
             Cache::Lfu(cache) => cache.touch(key),
             Cache::S3Fifo(cache) => cache.touch(key),
+            Cache::Sieve(cache) => cache.touch(key),
+            Cache::Arc(cache) => cache.touch(key),
         }
     }
 
@@ -548,6 +844,37 @@ where
             Cache::Lru(cache) => cache.clear(),
             Cache::Lfu(cache) => cache.clear(),
             Cache::S3Fifo(cache) => cache.clear(),
+            Cache::Sieve(cache) => cache.clear(),
+            Cache::Arc(cache) => cache.clear(),
+        }
+    }
+
+    /// Retain only the entries that the predicate returns `true` for, return the count of the removed entries.
+    ///
+    /// The shards are visited one at a time with the shard lock held. The removed entries are released the same as
+    /// the ones removed by `remove`.
+    pub fn retain(&self, f: impl FnMut(&K, &V) -> bool) -> usize {
+        match self {
+            Cache::Fifo(cache) => cache.retain(f),
+            Cache::Lru(cache) => cache.retain(f),
+            Cache::Lfu(cache) => cache.retain(f),
+            Cache::S3Fifo(cache) => cache.retain(f),
+            Cache::Sieve(cache) => cache.retain(f),
+            Cache::Arc(cache) => cache.retain(f),
+        }
+    }
+
+    /// Remove the entries that the predicate returns `true` for, return the count of the removed entries.
+    ///
+    /// See [`Self::retain`].
+    pub fn remove_if(&self, f: impl FnMut(&K, &V) -> bool) -> usize {
+        match self {
+            Cache::Fifo(cache) => cache.remove_if(f),
+            Cache::Lru(cache) => cache.remove_if(f),
+            Cache::Lfu(cache) => cache.remove_if(f),
+            Cache::S3Fifo(cache) => cache.remove_if(f),
+            Cache::Sieve(cache) => cache.remove_if(f),
+            Cache::Arc(cache) => cache.remove_if(f),
         }
     }
 
@@ -557,6 +884,23 @@ where
             Cache::Lru(cache) => cache.capacity(),
             Cache::Lfu(cache) => cache.capacity(),
             Cache::S3Fifo(cache) => cache.capacity(),
+            Cache::Sieve(cache) => cache.capacity(),
+            Cache::Arc(cache) => cache.capacity(),
+        }
+    }
+
+    /// Update the capacity of the cache, which is split evenly across the shards.
+    ///
+    /// Entries are evicted until the usage fits the new capacity. Entries that are still held by external users are
+    /// released after they are dropped.
+    pub fn resize(&self, capacity: usize) {
+        match self {
+            Cache::Fifo(cache) => cache.resize(capacity),
+            Cache::Lru(cache) => cache.resize(capacity),
+            Cache::Lfu(cache) => cache.resize(capacity),
+            Cache::S3Fifo(cache) => cache.resize(capacity),
+            Cache::Sieve(cache) => cache.resize(capacity),
+            Cache::Arc(cache) => cache.resize(capacity),
         }
     }
 
@@ -566,6 +910,67 @@ where
             Cache::Lru(cache) => cache.usage(),
             Cache::Lfu(cache) => cache.usage(),
             Cache::S3Fifo(cache) => cache.usage(),
+            Cache::Sieve(cache) => cache.usage(),
+            Cache::Arc(cache) => cache.usage(),
+        }
+    }
+
+    pub fn shards(&self) -> usize {
+        match self {
+            Cache::Fifo(cache) => cache.shards(),
+            Cache::Lru(cache) => cache.shards(),
+            Cache::Lfu(cache) => cache.shards(),
+            Cache::S3Fifo(cache) => cache.shards(),
+            Cache::Sieve(cache) => cache.shards(),
+            Cache::Arc(cache) => cache.shards(),
+        }
+    }
+
+    /// Iterate over the entries of the cache.
+    ///
+    /// The iteration is weakly consistent. The shards are visited one at a time, and the entries of a shard are
+    /// collected at once when the shard is visited. Entries inserted or removed concurrently may or may not be
+    /// yielded. Expired entries are skipped.
+    ///
+    /// Iterating does not count as access to the entries.
+    pub fn iter(&self) -> impl Iterator<Item = CacheEntry<K, V, L, S>> {
+        let cache = self.clone();
+        (0..self.shards()).flat_map(move |shard| cache.shard_entries(shard))
+    }
+
+    /// Iterate over the keys of the cache.
+    ///
+    /// The iteration is weakly consistent, the same as [`Self::iter`], but holds no references of the entries.
+    pub fn keys(&self) -> impl Iterator<Item = K>
+    where
+        K: Clone,
+    {
+        let cache = self.clone();
+        (0..self.shards()).flat_map(move |shard| cache.shard_keys(shard))
+    }
+
+    fn shard_entries(&self, shard: usize) -> Vec<CacheEntry<K, V, L, S>> {
+        match self {
+            Cache::Fifo(cache) => cache.shard_entries(shard).into_iter().map(CacheEntry::from).collect(),
+            Cache::Lru(cache) => cache.shard_entries(shard).into_iter().map(CacheEntry::from).collect(),
+            Cache::Lfu(cache) => cache.shard_entries(shard).into_iter().map(CacheEntry::from).collect(),
+            Cache::S3Fifo(cache) => cache.shard_entries(shard).into_iter().map(CacheEntry::from).collect(),
+            Cache::Sieve(cache) => cache.shard_entries(shard).into_iter().map(CacheEntry::from).collect(),
+            Cache::Arc(cache) => cache.shard_entries(shard).into_iter().map(CacheEntry::from).collect(),
+        }
+    }
+
+    fn shard_keys(&self, shard: usize) -> Vec<K>
+    where
+        K: Clone,
+    {
+        match self {
+            Cache::Fifo(cache) => cache.shard_keys(shard),
+            Cache::Lru(cache) => cache.shard_keys(shard),
+            Cache::Lfu(cache) => cache.shard_keys(shard),
+            Cache::S3Fifo(cache) => cache.shard_keys(shard),
+            Cache::Sieve(cache) => cache.shard_keys(shard),
+            Cache::Arc(cache) => cache.shard_keys(shard),
         }
     }
 
@@ -575,6 +980,62 @@ where
             Cache::Lru(cache) => cache.metrics(),
             Cache::Lfu(cache) => cache.metrics(),
             Cache::S3Fifo(cache) => cache.metrics(),
+            Cache::Sieve(cache) => cache.metrics(),
+            Cache::Arc(cache) => cache.metrics(),
+        }
+    }
+
+    pub fn weigher(&self) -> &Weigher<K, V> {
+        match self {
+            Cache::Fifo(cache) => cache.weigher(),
+            Cache::Lru(cache) => cache.weigher(),
+            Cache::Lfu(cache) => cache.weigher(),
+            Cache::S3Fifo(cache) => cache.weigher(),
+            Cache::Sieve(cache) => cache.weigher(),
+            Cache::Arc(cache) => cache.weigher(),
+        }
+    }
+
+    /// Get the current queue sizes if the cache uses [`LfuConfig`], which may change with adaptive window sizing.
+    pub fn lfu_queue_sizes(&self) -> Option<LfuQueueSizes> {
+        match self {
+            Cache::Lfu(cache) => Some(cache.lfu_queue_sizes()),
+            _ => None,
+        }
+    }
+
+    /// Save the entries of the cache to the snapshot file, which can be loaded by [`CacheBuilder::load_snapshot`].
+    ///
+    /// The entries are saved with their eviction order and eviction states. The shards are visited one at a time, so
+    /// the snapshot is weakly consistent with concurrent operations. Expired entries are skipped.
+    ///
+    /// The order within each shard is restored exactly only if the loading cache uses the same hasher and shard
+    /// count, otherwise the entries of the shards are restored in an interleaved order.
+    pub fn save_snapshot(&self, path: impl AsRef<Path>) -> SnapshotResult<()>
+    where
+        K: Serialize,
+        V: Serialize,
+    {
+        let mut writer = SnapshotWriter::create(path, self.eviction_name())?;
+        match self {
+            Cache::Fifo(cache) => cache.dump_snapshot(&mut writer)?,
+            Cache::Lru(cache) => cache.dump_snapshot(&mut writer)?,
+            Cache::Lfu(cache) => cache.dump_snapshot(&mut writer)?,
+            Cache::S3Fifo(cache) => cache.dump_snapshot(&mut writer)?,
+            Cache::Sieve(cache) => cache.dump_snapshot(&mut writer)?,
+            Cache::Arc(cache) => cache.dump_snapshot(&mut writer)?,
+        }
+        writer.finish()
+    }
+
+    fn eviction_name(&self) -> &'static str {
+        match self {
+            Cache::Fifo(_) => "fifo",
+            Cache::Lru(_) => "lru",
+            Cache::Lfu(_) => "lfu",
+            Cache::S3Fifo(_) => "s3fifo",
+            Cache::Sieve(_) => "sieve",
+            Cache::Arc(_) => "arc",
         }
     }
 }
@@ -591,6 +1052,8 @@ where
     Lru(LruEntry<K, V, ER, L, S>),
     Lfu(LfuEntry<K, V, ER, L, S>),
     S3Fifo(S3FifoEntry<K, V, ER, L, S>),
+    Sieve(SieveEntry<K, V, ER, L, S>),
+    Arc(ArcEntry<K, V, ER, L, S>),
 }
 
 impl<K, V, ER, L, S> From<FifoEntry<K, V, ER, L, S>> for Entry<K, V, ER, L, S>
@@ -645,6 +1108,32 @@ where
     }
 }
 
+impl<K, V, ER, L, S> From<SieveEntry<K, V, ER, L, S>> for Entry<K, V, ER, L, S>
+where
+    K: Key + Clone,
+    V: Value,
+    ER: std::error::Error,
+    L: CacheEventListener<K, V>,
+    S: BuildHasher + Send + Sync + 'static,
+{
+    fn from(entry: SieveEntry<K, V, ER, L, S>) -> Self {
+        Self::Sieve(entry)
+    }
+}
+
+impl<K, V, ER, L, S> From<ArcEntry<K, V, ER, L, S>> for Entry<K, V, ER, L, S>
+where
+    K: Key + Clone,
+    V: Value,
+    ER: std::error::Error,
+    L: CacheEventListener<K, V>,
+    S: BuildHasher + Send + Sync + 'static,
+{
+    fn from(entry: ArcEntry<K, V, ER, L, S>) -> Self {
+        Self::Arc(entry)
+    }
+}
+
 impl<K, V, ER, L, S> Future for Entry<K, V, ER, L, S>
 where
     K: Key + Clone,
@@ -661,6 +1150,8 @@ where
             Entry::Lru(entry) => entry.poll_unpin(cx).map(|res| res.map(CacheEntry::from)),
             Entry::Lfu(entry) => entry.poll_unpin(cx).map(|res| res.map(CacheEntry::from)),
             Entry::S3Fifo(entry) => entry.poll_unpin(cx).map(|res| res.map(CacheEntry::from)),
+            Entry::Sieve(entry) => entry.poll_unpin(cx).map(|res| res.map(CacheEntry::from)),
+            Entry::Arc(entry) => entry.poll_unpin(cx).map(|res| res.map(CacheEntry::from)),
         }
     }
 }
@@ -670,6 +1161,8 @@ pub enum EntryState {
     Hit,
     Wait,
     Miss,
+    /// The cached failure of the fetch is returned.
+    Negative,
 }
 
 impl<K, V, ER, L, S> Entry<K, V, ER, L, S>
@@ -685,19 +1178,33 @@ where
             Entry::Fifo(FifoEntry::Hit(_))
             | Entry::Lru(LruEntry::Hit(_))
             | Entry::Lfu(LfuEntry::Hit(_))
-            | Entry::S3Fifo(S3FifoEntry::Hit(_)) => EntryState::Hit,
+            | Entry::S3Fifo(S3FifoEntry::Hit(_))
+            | Entry::Sieve(SieveEntry::Hit(_))
+            | Entry::Arc(ArcEntry::Hit(_)) => EntryState::Hit,
             Entry::Fifo(FifoEntry::Wait(_))
             | Entry::Lru(LruEntry::Wait(_))
             | Entry::Lfu(LfuEntry::Wait(_))
-            | Entry::S3Fifo(S3FifoEntry::Wait(_)) => EntryState::Wait,
+            | Entry::S3Fifo(S3FifoEntry::Wait(_))
+            | Entry::Sieve(SieveEntry::Wait(_))
+            | Entry::Arc(ArcEntry::Wait(_)) => EntryState::Wait,
             Entry::Fifo(FifoEntry::Miss(_))
             | Entry::Lru(LruEntry::Miss(_))
             | Entry::Lfu(LfuEntry::Miss(_))
-            | Entry::S3Fifo(S3FifoEntry::Miss(_)) => EntryState::Miss,
+            | Entry::S3Fifo(S3FifoEntry::Miss(_))
+            | Entry::Sieve(SieveEntry::Miss(_))
+            | Entry::Arc(ArcEntry::Miss(_)) => EntryState::Miss,
+            Entry::Fifo(FifoEntry::Negative(_))
+            | Entry::Lru(LruEntry::Negative(_))
+            | Entry::Lfu(LfuEntry::Negative(_))
+            | Entry::S3Fifo(S3FifoEntry::Negative(_))
+            | Entry::Sieve(SieveEntry::Negative(_))
+            | Entry::Arc(ArcEntry::Negative(_)) => EntryState::Negative,
             Entry::Fifo(FifoEntry::Invalid)
             | Entry::Lru(LruEntry::Invalid)
             | Entry::Lfu(LfuEntry::Invalid)
-            | Entry::S3Fifo(S3FifoEntry::Invalid) => unreachable!(),
+            | Entry::S3Fifo(S3FifoEntry::Invalid)
+            | Entry::Sieve(SieveEntry::Invalid)
+            | Entry::Arc(ArcEntry::Invalid) => unreachable!(),
         }
     }
 }
@@ -709,10 +1216,17 @@ where
     L: CacheEventListener<K, V>,
     S: BuildHasher + Send + Sync + 'static,
 {
+    /// Get the entry of the key, or fetch it with `f` on miss.
+    ///
+    /// Concurrent fetches of the same key are deduplicated. The charge of the fetched entry is calculated by the
+    /// weigher.
+    ///
+    /// The negative cache is neither looked up nor filled by `entry`, even if it is configured. Use
+    /// [`Self::entry_with_negative_cache`] to cache the failures.
     pub fn entry<F, FU, ER>(&self, key: K, f: F) -> Entry<K, V, ER, L, S>
     where
         F: FnOnce() -> FU,
-        FU: Future<Output = std::result::Result<(V, usize, CacheContext), ER>> + Send + 'static,
+        FU: Future<Output = std::result::Result<(V, CacheContext), ER>> + Send + 'static,
         ER: std::error::Error + Send + 'static,
     {
         match self {
@@ -720,20 +1234,51 @@ where
             Cache::Lru(cache) => Entry::from(cache.entry(key, f)),
             Cache::Lfu(cache) => Entry::from(cache.entry(key, f)),
             Cache::S3Fifo(cache) => Entry::from(cache.entry(key, f)),
+            Cache::Sieve(cache) => Entry::from(cache.entry(key, f)),
+            Cache::Arc(cache) => Entry::from(cache.entry(key, f)),
+        }
+    }
+
+    /// The same as [`Self::entry`], but the failure of the fetch is cached if the negative cache is configured with
+    /// [`CacheBuilder::with_negative_cache`].
+    ///
+    /// `f` returns `Ok(None)` if it finds nothing for the key, which is returned as [`Negative::NotFound`]. The cached
+    /// failure is returned without calling `f` until it expires. A cached error is returned as is if the error type
+    /// matches the fetch that caused it, and converted from [`Negative::Error`] otherwise.
+    pub fn entry_with_negative_cache<F, FU, ER>(&self, key: K, f: F) -> Entry<K, V, ER, L, S>
+    where
+        F: FnOnce() -> FU,
+        FU: Future<Output = std::result::Result<Option<(V, CacheContext)>, ER>> + Send + 'static,
+        ER: std::error::Error + From<Negative> + Clone + Send + Sync + 'static,
+    {
+        match self {
+            Cache::Fifo(cache) => Entry::from(cache.entry_with_negative_cache(key, f)),
+            Cache::Lru(cache) => Entry::from(cache.entry_with_negative_cache(key, f)),
+            Cache::Lfu(cache) => Entry::from(cache.entry_with_negative_cache(key, f)),
+            Cache::S3Fifo(cache) => Entry::from(cache.entry_with_negative_cache(key, f)),
+            Cache::Sieve(cache) => Entry::from(cache.entry_with_negative_cache(key, f)),
+            Cache::Arc(cache) => Entry::from(cache.entry_with_negative_cache(key, f)),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{ops::Range, time::Duration};
+    use std::{
+        ops::Range,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
 
-    use futures::future::join_all;
+    use futures::future::{join_all, BoxFuture};
     use itertools::Itertools;
     use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
 
     use super::*;
-    use crate::{eviction::s3fifo::S3FifoConfig, FifoConfig, LfuConfig, LruConfig};
+    use crate::{
+        eviction::s3fifo::S3FifoConfig, ArcConfig, FifoConfig, LfuConfig, LfuDoorkeeperConfig, LfuHillClimbingConfig,
+        LruConfig, NegativeCacheConfig, RemovalCause, SieveConfig, SnapshotError,
+    };
+    use foyer_common::metrics::get_metrics_registry;
 
     const CAPACITY: usize = 100;
     const SHARDS: usize = 4;
@@ -768,6 +1313,27 @@ mod tests {
                 protected_capacity_ratio: 0.8,
                 cmsketch_eps: 0.001,
                 cmsketch_confidence: 0.9,
+                hill_climbing: None,
+                doorkeeper: None,
+                aging_sample_ratio: None,
+            })
+            .with_object_pool_capacity(OBJECT_POOL_CAPACITY)
+            .build()
+    }
+
+    fn lfu_adaptive() -> Cache<u64, u64> {
+        CacheBuilder::new(CAPACITY)
+            .with_shards(SHARDS)
+            .with_eviction_config(LfuConfig {
+                window_capacity_ratio: 0.1,
+                protected_capacity_ratio: 0.8,
+                cmsketch_eps: 0.01,
+                cmsketch_confidence: 0.95,
+                hill_climbing: Some(LfuHillClimbingConfig::default()),
+                doorkeeper: Some(LfuDoorkeeperConfig {
+                    false_positive_rate: 0.01,
+                }),
+                aging_sample_ratio: Some(10.0),
             })
             .with_object_pool_capacity(OBJECT_POOL_CAPACITY)
             .build()
@@ -778,16 +1344,33 @@ mod tests {
             .with_shards(SHARDS)
             .with_eviction_config(S3FifoConfig {
                 small_queue_capacity_ratio: 0.1,
+                ghost_queue_capacity_ratio: 0.9,
             })
             .with_object_pool_capacity(OBJECT_POOL_CAPACITY)
             .build()
     }
 
+    fn sieve() -> Cache<u64, u64> {
+        CacheBuilder::new(CAPACITY)
+            .with_shards(SHARDS)
+            .with_eviction_config(SieveConfig {})
+            .with_object_pool_capacity(OBJECT_POOL_CAPACITY)
+            .build()
+    }
+
+    fn arc() -> Cache<u64, u64> {
+        CacheBuilder::new(CAPACITY)
+            .with_shards(SHARDS)
+            .with_eviction_config(ArcConfig {})
+            .with_object_pool_capacity(OBJECT_POOL_CAPACITY)
+            .build()
+    }
+
     fn init_cache(cache: &Cache<u64, u64>, rng: &mut StdRng) {
         let mut v = RANGE.collect_vec();
         v.shuffle(rng);
         for i in v {
-            cache.insert(i, i, 1);
+            cache.insert(i, i);
         }
     }
 
@@ -795,7 +1378,7 @@ mod tests {
         let i = rng.gen_range(RANGE);
         match rng.gen_range(0..=3) {
             0 => {
-                let entry = cache.insert(i, i, 1);
+                let entry = cache.insert(i, i);
                 assert_eq!(*entry.key(), i);
                 assert_eq!(entry.key(), entry.value());
             }
@@ -812,7 +1395,7 @@ mod tests {
                 let entry = cache
                     .entry(i, || async move {
                         tokio::time::sleep(Duration::from_micros(10)).await;
-                        Ok::<_, tokio::sync::oneshot::error::RecvError>((i, 1, CacheContext::Default))
+                        Ok::<_, tokio::sync::oneshot::error::RecvError>((i, CacheContext::Default))
                     })
                     .await
                     .unwrap();
@@ -858,13 +1441,897 @@ mod tests {
         case(lfu()).await
     }
 
+    #[tokio::test]
+    async fn test_lfu_adaptive_cache() {
+        let cache = lfu_adaptive();
+        case(cache.clone()).await;
+        let sizes = cache.lfu_queue_sizes().unwrap();
+        assert_eq!(sizes.window + sizes.probation + sizes.protected, cache.usage());
+        assert!(sizes.window_capacity + sizes.protected_capacity <= CAPACITY);
+    }
+
     #[tokio::test]
     async fn test_s3fifo_cache() {
         case(s3fifo()).await
     }
 
+    #[tokio::test]
+    async fn test_sieve_cache() {
+        case(sieve()).await
+    }
+
+    #[tokio::test]
+    async fn test_arc_cache() {
+        case(arc()).await
+    }
+
     #[tokio::test]
     async fn test_cache_with_zero_object_pool() {
         case(CacheBuilder::new(8).with_object_pool_capacity(0).build()).await
     }
+
+    #[tokio::test]
+    async fn test_weigher() {
+        let cache: Cache<u64, Vec<u8>> = CacheBuilder::new(10)
+            .with_eviction_config(FifoConfig {})
+            .with_weigher(|_, value: &Vec<u8>| value.len())
+            .build();
+
+        assert_eq!(cache.insert(1, vec![1; 3]).charge(), 3);
+        assert_eq!(cache.insert(2, vec![2; 4]).charge(), 4);
+        assert_eq!(cache.usage(), 7);
+
+        // The explicit charge overrides the weigher.
+        assert_eq!(cache.insert_with_charge(3, vec![3; 4], 1).charge(), 1);
+        assert_eq!(cache.usage(), 8);
+
+        // Entries are evicted by the charges calculated by the weigher.
+        cache.insert(4, vec![4; 5]);
+        assert_eq!(cache.usage(), 10);
+        assert!(!cache.contains(&1));
+
+        // The charges of the fetched entries are calculated by the weigher, too.
+        let entry = cache
+            .entry(5, || async move {
+                Ok::<_, tokio::sync::oneshot::error::RecvError>((vec![5; 2], CacheContext::Default))
+            })
+            .await
+            .unwrap();
+        assert_eq!(entry.charge(), 2);
+    }
+
+    fn resize_case(cache: Cache<u64, u64>) {
+        let mut rng = StdRng::seed_from_u64(42);
+        init_cache(&cache, &mut rng);
+        assert_eq!(cache.usage(), CAPACITY);
+
+        // The held entry is released after it is dropped.
+        let entry = cache.insert(RANGE.end, RANGE.end);
+        cache.resize(CAPACITY / 2);
+        assert_eq!(cache.capacity(), CAPACITY / 2);
+        assert!(cache.usage() <= CAPACITY / 2 + 1);
+        drop(entry);
+        assert!(cache.usage() <= CAPACITY / 2);
+
+        for i in RANGE {
+            cache.insert(i, i);
+            assert!(cache.usage() <= CAPACITY / 2);
+        }
+
+        cache.resize(CAPACITY * 2);
+        for i in RANGE {
+            cache.insert(i, i);
+        }
+        assert!(cache.usage() > CAPACITY && cache.usage() <= CAPACITY * 2);
+    }
+
+    #[test]
+    fn test_resize() {
+        resize_case(fifo());
+        resize_case(lru());
+        resize_case(lfu());
+        resize_case(lfu_adaptive());
+        resize_case(s3fifo());
+        resize_case(sieve());
+        resize_case(arc());
+    }
+
+    fn iter_case(cache: Cache<u64, u64>) {
+        let mut rng = StdRng::seed_from_u64(42);
+        init_cache(&cache, &mut rng);
+
+        let keys = cache.keys().sorted().collect_vec();
+        assert_eq!(keys.len(), CAPACITY);
+        assert!(keys.iter().all(|key| cache.contains(key)));
+
+        let entries = cache.iter().collect_vec();
+        assert_eq!(entries.iter().map(|entry| *entry.key()).sorted().collect_vec(), keys);
+        assert!(entries
+            .iter()
+            .all(|entry| entry.key() == entry.value() && entry.refs() == 1));
+
+        // The yielded entries are still valid after they are removed from the cache.
+        cache.clear();
+        assert_eq!(cache.keys().count(), 0);
+        assert!(entries.iter().all(|entry| entry.key() == entry.value()));
+        assert_eq!(cache.usage(), CAPACITY);
+        drop(entries);
+        assert_eq!(cache.usage(), 0);
+    }
+
+    fn retain_case(cache: Cache<u64, u64>) {
+        let mut rng = StdRng::seed_from_u64(42);
+        init_cache(&cache, &mut rng);
+
+        let keys = cache.keys().collect_vec();
+        let evens = keys.iter().filter(|key| *key % 2 == 0).count();
+        let held = *keys.iter().find(|key| *key % 2 == 0).unwrap();
+        let entry = cache.get(&held).unwrap();
+
+        assert_eq!(cache.remove_if(|key, _| key % 2 == 0), evens);
+        assert!(cache.keys().all(|key| key % 2 == 1));
+        assert_eq!(cache.usage(), CAPACITY - evens + 1);
+
+        // The held entry is released after it is dropped.
+        assert_eq!(entry.value(), &held);
+        drop(entry);
+        assert_eq!(cache.usage(), CAPACITY - evens);
+
+        assert_eq!(cache.retain(|_, _| true), 0);
+        assert_eq!(cache.retain(|_, _| false), CAPACITY - evens);
+        assert_eq!(cache.usage(), 0);
+    }
+
+    #[test]
+    fn test_retain() {
+        retain_case(fifo());
+        retain_case(lru());
+        retain_case(lfu());
+        retain_case(lfu_adaptive());
+        retain_case(s3fifo());
+        retain_case(sieve());
+        retain_case(arc());
+    }
+
+    #[test]
+    fn test_iter() {
+        iter_case(fifo());
+        iter_case(lru());
+        iter_case(lfu());
+        iter_case(lfu_adaptive());
+        iter_case(s3fifo());
+        iter_case(sieve());
+        iter_case(arc());
+    }
+
+    fn snapshot_case(eviction_config: EvictionConfig, shards: usize, exact: bool) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshot");
+        // The same hasher keeps the entries in the same shards after restoring.
+        let builder = || {
+            CacheBuilder::new(CAPACITY)
+                .with_shards(shards)
+                .with_eviction_config(eviction_config.clone())
+                .with_hash_builder(RandomState::with_seeds(1, 2, 3, 4))
+        };
+
+        let cache: Cache<u64, u64> = builder().build();
+        let mut rng = StdRng::seed_from_u64(42);
+        init_cache(&cache, &mut rng);
+        for _ in 0..OPS {
+            let i = rng.gen_range(RANGE);
+            if cache.get(&i).is_none() {
+                cache.insert(i, i);
+            }
+        }
+        cache.save_snapshot(&path).unwrap();
+
+        let loaded: Cache<u64, u64> = builder().load_snapshot(&path).unwrap();
+        assert_eq!(loaded.usage(), cache.usage());
+
+        let pop_all =
+            |cache: &Cache<u64, u64>| std::iter::from_fn(|| cache.pop().map(|entry| *entry.key())).collect_vec();
+        let expected = pop_all(&cache);
+        let mut popped = pop_all(&loaded);
+        assert_eq!(popped.len(), CAPACITY);
+        if exact {
+            assert_eq!(popped, expected);
+        } else {
+            // The policy-wide states (e.g. the LFU sketch and the ARC target) are not exact after restoring, only the
+            // entries are compared.
+            popped.sort();
+            assert_eq!(popped, expected.into_iter().sorted().collect_vec());
+        }
+    }
+
+    #[test]
+    fn test_snapshot() {
+        for shards in [1, SHARDS] {
+            snapshot_cases(shards);
+        }
+    }
+
+    fn snapshot_cases(shards: usize) {
+        snapshot_case(FifoConfig {}.into(), shards, true);
+        snapshot_case(
+            LruConfig {
+                high_priority_pool_ratio: 0.1,
+            }
+            .into(),
+            shards,
+            true,
+        );
+        snapshot_case(
+            LfuConfig {
+                window_capacity_ratio: 0.1,
+                protected_capacity_ratio: 0.8,
+                cmsketch_eps: 0.001,
+                cmsketch_confidence: 0.9,
+                hill_climbing: None,
+                doorkeeper: None,
+                aging_sample_ratio: None,
+            }
+            .into(),
+            shards,
+            false,
+        );
+        snapshot_case(
+            S3FifoConfig {
+                small_queue_capacity_ratio: 0.1,
+                ghost_queue_capacity_ratio: 0.9,
+            }
+            .into(),
+            shards,
+            true,
+        );
+        snapshot_case(SieveConfig {}.into(), shards, true);
+        snapshot_case(ArcConfig {}.into(), shards, false);
+    }
+
+    #[test]
+    fn test_snapshot_interleave_shards() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshot");
+
+        let cache: Cache<u64, u64> = CacheBuilder::new(CAPACITY)
+            .with_shards(SHARDS)
+            .with_eviction_config(FifoConfig {})
+            .build();
+        for i in RANGE {
+            cache.insert(i, i);
+        }
+        cache.save_snapshot(&path).unwrap();
+
+        // Restore into a smaller cache with a different shard layout, the entries appended first are evicted first.
+        // The shards are interleaved, so the kept entries are mostly the newest ones instead of the whole last shards.
+        let loaded: Cache<u64, u64> = CacheBuilder::new(CAPACITY / 2)
+            .with_eviction_config(FifoConfig {})
+            .load_snapshot(&path)
+            .unwrap();
+        assert_eq!(loaded.usage(), CAPACITY / 2);
+        let newest = RANGE.end - (CAPACITY / 2) as u64;
+        let kept = loaded.keys().filter(|key| *key >= newest).count();
+        assert!(kept >= CAPACITY * 2 / 5, "kept: {kept}");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_snapshot_ttl() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshot");
+
+        let cache: Cache<u64, u64> = CacheBuilder::new(4).build();
+        cache.insert_with_ttl(1, 1, 1, TTL);
+        cache.insert(2, 2);
+        cache.insert_with_ttl(3, 3, 1, Duration::from_secs(60));
+        tokio::time::advance(TTL * 2).await;
+        cache.save_snapshot(&path).unwrap();
+
+        // The expired entry is skipped, and the remaining time-to-live is kept.
+        let loaded: Cache<u64, u64> = CacheBuilder::new(4).load_snapshot(&path).unwrap();
+        assert_eq!(loaded.keys().sorted().collect_vec(), vec![2, 3]);
+        assert_eq!(loaded.usage(), 2);
+    }
+
+    #[test]
+    fn test_snapshot_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshot");
+
+        assert!(matches!(
+            CacheBuilder::<u64, u64, _, _>::new(4).load_snapshot(&path),
+            Err(SnapshotError::Io(_))
+        ));
+
+        let cache: Cache<u64, u64> = CacheBuilder::new(4).with_eviction_config(FifoConfig {}).build();
+        for i in 0..4 {
+            cache.insert(i, i);
+        }
+        cache.save_snapshot(&path).unwrap();
+
+        // The snapshot must be loaded with the same eviction algorithm.
+        assert!(matches!(
+            CacheBuilder::<u64, u64, _, _>::new(4)
+                .with_eviction_config(SieveConfig {})
+                .load_snapshot(&path),
+            Err(SnapshotError::Invalid(_))
+        ));
+
+        // The corrupted snapshot is rejected.
+        let mut buf = std::fs::read(&path).unwrap();
+        *buf.last_mut().unwrap() ^= 0xff;
+        std::fs::write(&path, buf).unwrap();
+        assert!(matches!(
+            CacheBuilder::<u64, u64, _, _>::new(4)
+                .with_eviction_config(FifoConfig {})
+                .load_snapshot(&path),
+            Err(SnapshotError::Invalid(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cache_with_read_buffer() {
+        let configs: Vec<EvictionConfig> = vec![
+            FifoConfig {}.into(),
+            LruConfig {
+                high_priority_pool_ratio: 0.1,
+            }
+            .into(),
+            CacheBuilder::<u64, u64, DefaultCacheEventListener<u64, u64>, RandomState>::DEFAULT_EVICTION_CONFIG,
+            S3FifoConfig {
+                small_queue_capacity_ratio: 0.1,
+                ghost_queue_capacity_ratio: 0.9,
+            }
+            .into(),
+            SieveConfig {}.into(),
+            ArcConfig {}.into(),
+        ];
+        for config in configs {
+            let cache = CacheBuilder::new(CAPACITY)
+                .with_shards(SHARDS)
+                .with_eviction_config(config)
+                .with_object_pool_capacity(OBJECT_POOL_CAPACITY)
+                .with_read_buffer(16)
+                .build();
+            case(cache.clone()).await;
+
+            // All references held by the read buffers are released.
+            cache.clear();
+            assert_eq!(cache.usage(), 0);
+        }
+    }
+
+    #[derive(Debug, Default, Clone)]
+    struct ExpireCounter(Arc<AtomicUsize>);
+
+    impl CacheEventListener<u64, u64> for ExpireCounter {
+        fn on_release(&self, _: u64, _: u64, _: CacheContext, _: usize) {}
+
+        fn on_expire(&self, _: u64, _: u64, _: CacheContext, _: usize) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    const TTL: Duration = Duration::from_millis(10);
+
+    async fn ttl_case(eviction_config: impl Into<EvictionConfig>) {
+        let counter = ExpireCounter::default();
+        let cache: Cache<u64, u64, ExpireCounter> = CacheBuilder::new(4)
+            .with_eviction_config(eviction_config)
+            .with_event_listener(counter.clone())
+            .build();
+
+        // The expired entry is evicted before the ones chosen by the eviction algorithm.
+        cache.insert_with_ttl(0, 0, 1, TTL);
+        for i in 1..4 {
+            cache.insert(i, i);
+            cache.get(&i);
+        }
+        tokio::time::advance(TTL * 2).await;
+        cache.insert(4, 4);
+        for i in 1..5 {
+            assert!(cache.contains(&i));
+        }
+        assert_eq!(counter.0.load(Ordering::Relaxed), 1);
+        assert_eq!(cache.usage(), 4);
+
+        // The expired entry is treated as a miss.
+        cache.insert_with_ttl(5, 5, 1, TTL);
+        assert_eq!(cache.get(&5).unwrap().value(), &5);
+        tokio::time::advance(TTL * 2).await;
+        assert!(cache.get(&5).is_none());
+        assert!(!cache.contains(&5));
+        assert_eq!(counter.0.load(Ordering::Relaxed), 2);
+
+        cache.insert_with_ttl(6, 6, 1, TTL);
+        tokio::time::advance(TTL * 2).await;
+        let entry = cache.entry(6, || async move {
+            Ok::<_, tokio::sync::oneshot::error::RecvError>((66, CacheContext::Default))
+        });
+        assert_eq!(entry.state(), EntryState::Miss);
+        assert_eq!(entry.await.unwrap().value(), &66);
+        assert_eq!(counter.0.load(Ordering::Relaxed), 3);
+        assert_eq!(cache.metrics().expire.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_ttl() {
+        ttl_case(FifoConfig {}).await;
+        ttl_case(LruConfig {
+            high_priority_pool_ratio: 0.0,
+        })
+        .await;
+        ttl_case(LfuConfig {
+            window_capacity_ratio: 0.1,
+            protected_capacity_ratio: 0.8,
+            cmsketch_eps: 0.01,
+            cmsketch_confidence: 0.95,
+            hill_climbing: None,
+            doorkeeper: None,
+            aging_sample_ratio: None,
+        })
+        .await;
+        ttl_case(S3FifoConfig {
+            small_queue_capacity_ratio: 0.1,
+            ghost_queue_capacity_ratio: 0.9,
+        })
+        .await;
+        ttl_case(SieveConfig {}).await;
+        ttl_case(ArcConfig {}).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_default_ttl() {
+        let cache: Cache<u64, u64> = CacheBuilder::new(4).with_ttl(TTL).build();
+        cache.insert(1, 1);
+        assert!(cache.contains(&1));
+        tokio::time::advance(TTL * 2).await;
+        assert_eq!(cache.keys().count(), 0);
+        assert_eq!(cache.iter().count(), 0);
+        assert!(!cache.contains(&1));
+        assert_eq!(cache.usage(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_entry_not_overwrite_insert() {
+        let cache: Cache<u64, u64> = CacheBuilder::new(4).build();
+
+        let (tx, rx) = oneshot::channel();
+        let entry: Entry<_, _, RecvError> =
+            cache.entry(
+                1,
+                || async move { rx.await.map(|value| (value, CacheContext::Default)) },
+            );
+        assert_eq!(entry.state(), EntryState::Miss);
+
+        // The entry inserted during the fetch supersedes the fetched one.
+        cache.insert(1, 2);
+        tx.send(1).unwrap();
+        assert_eq!(entry.await.unwrap().value(), &2);
+        assert_eq!(cache.get(&1).unwrap().value(), &2);
+        assert_eq!(cache.usage(), 1);
+    }
+
+    type RecvError = tokio::sync::oneshot::error::RecvError;
+
+    fn refresh_fetch(
+        rx: oneshot::Receiver<u64>,
+    ) -> impl FnOnce() -> BoxFuture<'static, std::result::Result<(u64, CacheContext), RecvError>> {
+        move || async move { rx.await.map(|value| (value, CacheContext::Default)) }.boxed()
+    }
+
+    fn refreshes(cache: &Cache<u64, u64>) -> usize {
+        cache.metrics().refresh.load(Ordering::Relaxed)
+    }
+
+    /// Hit the key with the `entry` interface, and return the sender of the refresh if it is started.
+    async fn refresh_hit(cache: &Cache<u64, u64>, key: u64, value: u64) -> Option<oneshot::Sender<u64>> {
+        let before = refreshes(cache);
+        let (tx, rx) = oneshot::channel();
+        let entry: Entry<_, _, RecvError> = cache.entry(key, refresh_fetch(rx));
+        assert_eq!(entry.state(), EntryState::Hit);
+        assert_eq!(entry.await.unwrap().value(), &value);
+        (refreshes(cache) > before).then_some(tx)
+    }
+
+    /// Complete the refresh and let it apply.
+    async fn refresh_complete(tx: oneshot::Sender<u64>, value: u64) {
+        tx.send(value).unwrap();
+        tokio::task::yield_now().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_refresh_after_write() {
+        let cache: Cache<u64, u64> = CacheBuilder::new(4).with_refresh_after_write(TTL).build();
+        cache.insert(1, 1);
+
+        // The fresh entry is not refreshed.
+        assert!(refresh_hit(&cache, 1, 1).await.is_none());
+
+        // The stale entry is returned, and refreshed in background.
+        tokio::time::advance(TTL * 2).await;
+        let tx = refresh_hit(&cache, 1, 1).await.unwrap();
+
+        // The ongoing refresh is deduplicated.
+        assert!(refresh_hit(&cache, 1, 1).await.is_none());
+
+        // The refreshed entry replaces the stale one, and is fresh again.
+        refresh_complete(tx, 2).await;
+        assert!(refresh_hit(&cache, 1, 2).await.is_none());
+        assert_eq!(refreshes(&cache), 1);
+
+        // The stale entry is kept if the refresh fails, and refreshed again by a later hit.
+        tokio::time::advance(TTL * 2).await;
+        let tx = refresh_hit(&cache, 1, 2).await.unwrap();
+        drop(tx);
+        tokio::task::yield_now().await;
+        let tx = refresh_hit(&cache, 1, 2).await.unwrap();
+        refresh_complete(tx, 3).await;
+        assert_eq!(cache.get(&1).unwrap().value(), &3);
+        assert_eq!(refreshes(&cache), 3);
+        assert_eq!(cache.metrics().fetch.load(Ordering::Relaxed), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_refresh_after_removal() {
+        let cache: Cache<u64, u64> = CacheBuilder::new(4).with_refresh_after_write(TTL).build();
+        cache.insert(1, 1);
+        cache.insert(2, 2);
+        tokio::time::advance(TTL * 2).await;
+
+        // The refresh of the removed entry does not resurrect it.
+        let tx = refresh_hit(&cache, 1, 1).await.unwrap();
+        cache.remove(&1);
+        refresh_complete(tx, 10).await;
+        assert!(cache.get(&1).is_none());
+
+        // The refresh of the replaced entry does not overwrite the new value.
+        let tx = refresh_hit(&cache, 2, 2).await.unwrap();
+        cache.insert(2, 3);
+        refresh_complete(tx, 20).await;
+        assert_eq!(cache.get(&2).unwrap().value(), &3);
+
+        // Misses during the refresh of the removed entry fetch on their own.
+        cache.insert(1, 1);
+        tokio::time::advance(TTL * 2).await;
+        let refresh = refresh_hit(&cache, 1, 1).await.unwrap();
+        cache.remove(&1);
+        let (tx, rx) = oneshot::channel();
+        let entry: Entry<_, _, RecvError> = cache.entry(1, refresh_fetch(rx));
+        assert_eq!(entry.state(), EntryState::Miss);
+        tx.send(4).unwrap();
+        assert_eq!(entry.await.unwrap().value(), &4);
+        refresh_complete(refresh, 40).await;
+        assert_eq!(cache.get(&1).unwrap().value(), &4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_refresh_keep_ttl_and_context() {
+        let cache: Cache<u64, u64> = CacheBuilder::new(4)
+            .with_eviction_config(LruConfig {
+                high_priority_pool_ratio: 0.1,
+            })
+            .with_refresh_after_write(TTL)
+            .build();
+        cache.insert_with_context(1, 1, 1, CacheContext::LruPriorityLow);
+        cache.insert_with_ttl(2, 2, 1, TTL * 10);
+        tokio::time::advance(TTL * 2).await;
+
+        let tx = refresh_hit(&cache, 1, 1).await.unwrap();
+        refresh_complete(tx, 10).await;
+        assert_eq!(cache.get(&1).unwrap().context(), CacheContext::LruPriorityLow);
+
+        // The time-to-live restarts from the refresh.
+        let tx = refresh_hit(&cache, 2, 2).await.unwrap();
+        refresh_complete(tx, 20).await;
+        tokio::time::advance(TTL * 9).await;
+        assert_eq!(cache.get(&2).unwrap().value(), &20);
+        tokio::time::advance(TTL * 2).await;
+        assert!(cache.get(&2).is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_refresh_after_access() {
+        let cache: Cache<u64, u64> = CacheBuilder::new(4).with_refresh_after_access(TTL).build();
+        cache.insert(1, 1);
+
+        // The access postpones the refresh.
+        tokio::time::advance(TTL / 2).await;
+        assert_eq!(cache.get(&1).unwrap().value(), &1);
+        tokio::time::advance(TTL * 3 / 4).await;
+        assert!(refresh_hit(&cache, 1, 1).await.is_none());
+
+        // The entry is refreshed after the duration since the last access.
+        tokio::time::advance(TTL * 2).await;
+        let tx = refresh_hit(&cache, 1, 1).await.unwrap();
+        refresh_complete(tx, 2).await;
+        assert!(refresh_hit(&cache, 1, 2).await.is_none());
+        assert_eq!(refreshes(&cache), 1);
+    }
+
+    #[derive(Debug, Clone, thiserror::Error)]
+    enum FetchError {
+        #[error("unavailable")]
+        Unavailable,
+        #[error("negative: {0}")]
+        Negative(#[from] Negative),
+        #[error("recv error: {0}")]
+        Recv(#[from] tokio::sync::oneshot::error::RecvError),
+    }
+
+    #[derive(Debug, Clone, thiserror::Error)]
+    enum OtherError {
+        #[error("negative: {0}")]
+        Negative(#[from] Negative),
+        #[error("recv error: {0}")]
+        Recv(#[from] tokio::sync::oneshot::error::RecvError),
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_negative_cache() {
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let fetch = |result: std::result::Result<Option<u64>, FetchError>| {
+            let fetches = fetches.clone();
+            move || async move {
+                fetches.fetch_add(1, Ordering::Relaxed);
+                result.map(|value| value.map(|value| (value, CacheContext::Default)))
+            }
+        };
+        let not_found = |res: std::result::Result<
+            CacheEntry<u64, u64, DefaultCacheEventListener<u64, u64>>,
+            FetchError,
+        >| { matches!(res, Err(FetchError::Negative(Negative::NotFound))) };
+
+        let cache: Cache<u64, u64> = CacheBuilder::new(2)
+            .with_negative_cache(NegativeCacheConfig {
+                ttl: TTL,
+                charge: 1,
+                capacity_ratio: 1.0,
+            })
+            .build();
+
+        // The not found result is cached and returned without fetching again.
+        let entry = cache.entry_with_negative_cache(1, fetch(Ok(None)));
+        assert_eq!(entry.state(), EntryState::Miss);
+        assert!(not_found(entry.await));
+        assert_eq!(cache.usage(), 1);
+        let entry = cache.entry_with_negative_cache(1, fetch(Ok(Some(1))));
+        assert_eq!(entry.state(), EntryState::Negative);
+        assert!(not_found(entry.await));
+        assert_eq!(fetches.load(Ordering::Relaxed), 1);
+        assert_eq!(cache.metrics().negative.load(Ordering::Relaxed), 1);
+
+        // The plain `entry` interface ignores the negative cache.
+        let entry: Entry<_, _, FetchError> = cache.entry(1, || async { Ok((1, CacheContext::Default)) });
+        assert_eq!(entry.state(), EntryState::Miss);
+        entry.await.unwrap();
+        cache.remove(&1);
+        assert_eq!(cache.usage(), 0);
+
+        // The error is cached, and returned as is with the same error type, or converted with a different one.
+        let entry = cache.entry_with_negative_cache(1, fetch(Err(FetchError::Unavailable)));
+        assert!(matches!(entry.await, Err(FetchError::Unavailable)));
+        let entry = cache.entry_with_negative_cache(1, fetch(Ok(Some(1))));
+        assert_eq!(entry.state(), EntryState::Negative);
+        assert!(matches!(entry.await, Err(FetchError::Unavailable)));
+        let entry =
+            cache.entry_with_negative_cache(1, || async { Ok::<_, OtherError>(Some((1, CacheContext::Default))) });
+        assert_eq!(entry.state(), EntryState::Negative);
+        match entry.await {
+            Err(OtherError::Negative(Negative::Error(e))) => assert!(e.downcast_ref::<FetchError>().is_some()),
+            _ => panic!("the cached error is not returned"),
+        }
+        assert_eq!(fetches.load(Ordering::Relaxed), 2);
+
+        // Removing the key drops the cached failure.
+        cache.remove(&1);
+        assert_eq!(cache.usage(), 0);
+        let entry = cache.entry_with_negative_cache(1, fetch(Ok(None)));
+        assert_eq!(entry.state(), EntryState::Miss);
+        assert!(not_found(entry.await));
+
+        // The cached failure expires after the ttl.
+        tokio::time::advance(TTL * 2).await;
+        let entry = cache.entry_with_negative_cache(1, fetch(Ok(Some(1))));
+        assert_eq!(entry.state(), EntryState::Miss);
+        assert_eq!(entry.await.unwrap().value(), &1);
+        assert_eq!(fetches.load(Ordering::Relaxed), 4);
+        assert_eq!(cache.usage(), 1);
+        cache.clear();
+
+        // Cached failures are evicted when there are no other entries to evict.
+        for key in [1, 2] {
+            assert!(not_found(cache.entry_with_negative_cache(key, fetch(Ok(None))).await));
+        }
+        assert_eq!(cache.usage(), 2);
+        cache.insert(3, 3);
+        assert_eq!(cache.usage(), 2);
+        assert_eq!(
+            cache.entry_with_negative_cache(1, fetch(Ok(Some(1)))).state(),
+            EntryState::Miss
+        );
+        assert_eq!(
+            cache.entry_with_negative_cache(2, fetch(Ok(Some(2)))).state(),
+            EntryState::Negative
+        );
+
+        // A burst of failures replaces the oldest cached failures instead of evicting the entries beyond its share.
+        let cache: Cache<u64, u64> = CacheBuilder::new(8)
+            .with_negative_cache(NegativeCacheConfig {
+                ttl: TTL,
+                charge: 1,
+                capacity_ratio: 0.25,
+            })
+            .build();
+        for key in 0..8 {
+            cache.insert(key, key);
+        }
+        for key in 100..200 {
+            assert!(not_found(cache.entry_with_negative_cache(key, fetch(Ok(None))).await));
+        }
+        assert_eq!(cache.usage(), 8);
+        assert_eq!((0..8).filter(|key| cache.contains(key)).count(), 6);
+        assert_eq!(
+            cache.entry_with_negative_cache(198, fetch(Ok(Some(198)))).state(),
+            EntryState::Negative
+        );
+        assert_eq!(
+            cache.entry_with_negative_cache(197, fetch(Ok(Some(197)))).state(),
+            EntryState::Miss
+        );
+
+        // Failures are not cached without the negative cache config.
+        let cache: Cache<u64, u64> = CacheBuilder::new(2).build();
+        let entry = cache.entry_with_negative_cache(1, fetch(Ok(None)));
+        assert!(not_found(entry.await));
+        let entry = cache.entry_with_negative_cache(1, fetch(Ok(Some(1))));
+        assert_eq!(entry.state(), EntryState::Miss);
+        assert_eq!(entry.await.unwrap().value(), &1);
+    }
+
+    #[derive(Debug, Default, Clone)]
+    struct Recorder {
+        inserts: Arc<parking_lot::Mutex<Vec<u64>>>,
+        removals: Arc<parking_lot::Mutex<Vec<(u64, RemovalCause)>>>,
+    }
+
+    impl CacheEventListener<u64, u64> for Recorder {
+        fn on_insert(&self, key: &u64, _: &u64, _: CacheContext, _: usize) {
+            self.inserts.lock().push(*key);
+        }
+
+        fn on_release(&self, _: u64, _: u64, _: CacheContext, _: usize) {
+            unreachable!()
+        }
+
+        fn on_release_with_cause(&self, key: u64, _: u64, _: CacheContext, _: usize, cause: RemovalCause) {
+            self.removals.lock().push((key, cause));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_removal_cause() {
+        let recorder = Recorder::default();
+        let cache: Cache<u64, u64, Recorder> = CacheBuilder::new(4)
+            .with_eviction_config(FifoConfig {})
+            .with_event_listener(recorder.clone())
+            .build();
+        let removals = || std::mem::take(&mut *recorder.removals.lock());
+
+        for i in 0..3 {
+            cache.insert(i, i);
+        }
+        assert_eq!(*recorder.inserts.lock(), vec![0, 1, 2]);
+        assert!(removals().is_empty());
+
+        cache.insert(0, 10);
+        assert_eq!(removals(), vec![(0, RemovalCause::Replaced)]);
+
+        // The cause is reported when the last external reference is dropped.
+        let entry = cache.remove(&1).unwrap();
+        assert!(removals().is_empty());
+        drop(entry);
+        assert_eq!(removals(), vec![(1, RemovalCause::Removed)]);
+
+        for i in 3..6 {
+            cache.insert(i, i);
+        }
+        assert_eq!(removals(), vec![(2, RemovalCause::Evicted)]);
+        cache.pop();
+        assert_eq!(removals(), vec![(0, RemovalCause::Evicted)]);
+
+        cache.insert_with_ttl(6, 6, 1, TTL);
+        tokio::time::advance(TTL * 2).await;
+        assert!(cache.get(&6).is_none());
+        assert_eq!(removals(), vec![(6, RemovalCause::Expired)]);
+
+        assert_eq!(cache.remove_if(|key, _| *key == 3), 1);
+        assert_eq!(removals(), vec![(3, RemovalCause::Removed)]);
+
+        cache.clear();
+        let mut cleared = removals();
+        cleared.sort_by_key(|(key, _)| *key);
+        assert_eq!(cleared, vec![(4, RemovalCause::Cleared), (5, RemovalCause::Cleared)]);
+        assert_eq!(*recorder.inserts.lock(), vec![0, 1, 2, 0, 3, 4, 5, 6]);
+    }
+
+    /// Get the value of the exported metric with the given labels.
+    fn exported(metric: &str, labels: &[(&str, &str)]) -> i64 {
+        get_metrics_registry()
+            .gather()
+            .into_iter()
+            .filter(|family| family.get_name() == metric)
+            .flat_map(|family| family.get_metric().to_vec())
+            .find(|m| {
+                labels.iter().all(|(name, value)| {
+                    m.get_label()
+                        .iter()
+                        .any(|label| label.get_name() == *name && label.get_value() == *value)
+                })
+            })
+            .map(|m| {
+                if m.has_counter() {
+                    m.get_counter().get_value() as i64
+                } else {
+                    m.get_gauge().get_value() as i64
+                }
+            })
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_metrics() {
+        const NAME: &str = "test-metrics";
+        let op = |op: &str, extra: &str| {
+            exported(
+                "foyer_memory_op_total",
+                &[("foyer", NAME), ("op", op), ("extra", extra)],
+            )
+        };
+        let usage = || exported("foyer_memory_usage", &[("foyer", NAME)]);
+        let shard_usage = |shard: &str| exported("foyer_memory_shard_usage", &[("foyer", NAME), ("shard", shard)]);
+        let capacity = || exported("foyer_memory_capacity", &[("foyer", NAME)]);
+
+        // Spare capacity, so no entry is evicted even if all keys fall into the same shard.
+        let cache: Cache<u64, u64> = CacheBuilder::new(16)
+            .with_name(NAME)
+            .with_shards(2)
+            .with_eviction_config(FifoConfig {})
+            .build();
+
+        for i in 0..4 {
+            cache.insert(i, i);
+        }
+        cache.insert(0, 0);
+        assert!(cache.get(&1).is_some());
+        assert!(cache.get(&100).is_none());
+        assert_eq!(op("insert", "inserted"), 4);
+        assert_eq!(op("insert", "replaced"), 1);
+        assert_eq!(op("lookup", "hit"), 1);
+        assert_eq!(op("lookup", "miss"), 1);
+        assert_eq!(usage(), 4);
+        assert_eq!(shard_usage("0") + shard_usage("1"), 4);
+        assert_eq!(capacity(), 16);
+
+        // Concurrent fetches of the same key are deduplicated.
+        let e1 = cache.entry(10, || async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Ok::<_, tokio::sync::oneshot::error::RecvError>((10, CacheContext::Default))
+        });
+        let e2: Entry<_, _, tokio::sync::oneshot::error::RecvError> = cache.entry(10, || async move { unreachable!() });
+        assert_eq!(e2.state(), EntryState::Wait);
+        assert_eq!(e1.await.unwrap().value(), &10);
+        assert_eq!(e2.await.unwrap().value(), &10);
+        assert_eq!(op("entry", "fetch"), 1);
+        assert_eq!(op("entry", "queue"), 1);
+
+        cache.resize(2);
+        assert_eq!(capacity(), 2);
+        assert_eq!(usage(), cache.usage() as i64);
+        assert_eq!(op("evict", ""), 5 - cache.usage() as i64);
+
+        drop(cache);
+        assert_eq!(usage(), 0);
+        assert_eq!(shard_usage("0") + shard_usage("1"), 0);
+        assert_eq!(capacity(), 0);
+    }
 }
@@ -0,0 +1,517 @@
+//  Copyright 2024 Foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Debug,
+    ptr::NonNull,
+};
+
+use foyer_intrusive::{
+    core::adapter::Link,
+    dlist::{Dlist, DlistLink},
+    intrusive_adapter,
+};
+
+use crate::{
+    eviction::Eviction,
+    handle::{BaseHandle, Handle},
+    CacheContext,
+};
+
+#[derive(Debug, Clone)]
+pub struct ArcContext;
+
+impl From<CacheContext> for ArcContext {
+    fn from(_: CacheContext) -> Self {
+        Self
+    }
+}
+
+impl From<ArcContext> for CacheContext {
+    fn from(_: ArcContext) -> Self {
+        CacheContext::Default
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Queue {
+    None,
+    T1,
+    T2,
+}
+
+pub struct ArcHandle<T>
+where
+    T: Send + Sync + 'static,
+{
+    link: DlistLink,
+    base: BaseHandle<T, ArcContext>,
+    queue: Queue,
+}
+
+impl<T> Debug for ArcHandle<T>
+where
+    T: Send + Sync + 'static,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ArcHandle").finish()
+    }
+}
+
+intrusive_adapter! { ArcHandleDlistAdapter<T> = NonNull<ArcHandle<T>>: ArcHandle<T> { link: DlistLink } where T: Send + Sync + 'static }
+
+impl<T> Handle for ArcHandle<T>
+where
+    T: Send + Sync + 'static,
+{
+    type Data = T;
+    type Context = ArcContext;
+
+    fn new() -> Self {
+        Self {
+            link: DlistLink::default(),
+            base: BaseHandle::new(),
+            queue: Queue::None,
+        }
+    }
+
+    fn init(&mut self, hash: u64, data: Self::Data, charge: usize, context: Self::Context) {
+        self.base.init(hash, data, charge, context);
+    }
+
+    fn base(&self) -> &BaseHandle<Self::Data, Self::Context> {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut BaseHandle<Self::Data, Self::Context> {
+        &mut self.base
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ArcConfig {}
+
+/// Ghost list of ARC, records the hashes and charges of the evicted entries in LRU order.
+///
+/// Entries removed by hits are left in the queue as tombstones and skipped on pop.
+#[derive(Debug, Default)]
+struct GhostList {
+    queue: VecDeque<(u64, u64)>,
+    entries: HashMap<u64, (u64, usize)>,
+
+    charges: usize,
+    seq: u64,
+}
+
+impl GhostList {
+    fn push(&mut self, hash: u64, charge: usize) {
+        self.seq += 1;
+        if let Some((_, old)) = self.entries.insert(hash, (self.seq, charge)) {
+            self.charges -= old;
+        }
+        self.queue.push_back((hash, self.seq));
+        self.charges += charge;
+
+        // Compact the tombstones to keep the memory usage bounded.
+        if self.queue.len() > self.entries.len() * 2 + 16 {
+            let entries = &self.entries;
+            self.queue
+                .retain(|(hash, seq)| entries.get(hash).map(|(s, _)| s) == Some(seq));
+        }
+    }
+
+    fn pop(&mut self) {
+        while let Some((hash, seq)) = self.queue.pop_front() {
+            if self.entries.get(&hash).map(|(s, _)| *s) == Some(seq) {
+                let (_, charge) = self.entries.remove(&hash).unwrap();
+                self.charges -= charge;
+                return;
+            }
+        }
+    }
+
+    fn remove(&mut self, hash: u64) -> bool {
+        match self.entries.remove(&hash) {
+            Some((_, charge)) => {
+                self.charges -= charge;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn contains(&self, hash: u64) -> bool {
+        self.entries.contains_key(&hash)
+    }
+
+    fn clear(&mut self) {
+        self.queue.clear();
+        self.entries.clear();
+        self.charges = 0;
+    }
+}
+
+/// ARC (Adaptive Replacement Cache) eviction algorithm.
+///
+/// Entries seen once are kept in `t1`, entries seen at least twice are kept in `t2`. The hashes of the entries
+/// evicted from `t1` and `t2` are recorded in ghost lists `b1` and `b2`. A re-insertion that hits `b1` grows the
+/// target charges of `t1`, and a re-insertion that hits `b2` shrinks it, so the balance between recency and frequency
+/// adapts to the workload online.
+///
+/// All sizes are measured in charges.
+///
+/// Reference: ARC: A Self-Tuning, Low Overhead Replacement Cache (FAST'03).
+pub struct ArcEviction<T>
+where
+    T: Send + Sync + 'static,
+{
+    t1: Dlist<ArcHandleDlistAdapter<T>>,
+    t2: Dlist<ArcHandleDlistAdapter<T>>,
+    b1: GhostList,
+    b2: GhostList,
+
+    t1_charges: usize,
+    t2_charges: usize,
+
+    capacity: usize,
+    /// target charges of `t1`
+    p: usize,
+    /// if the latest insertion hits `b2`
+    b2_hit: bool,
+}
+
+impl<T> ArcEviction<T>
+where
+    T: Send + Sync + 'static,
+{
+    unsafe fn push_t2(&mut self, mut ptr: NonNull<ArcHandle<T>>) {
+        let handle = ptr.as_mut();
+        self.t2.push_back(ptr);
+        handle.queue = Queue::T2;
+        self.t2_charges += handle.base().charge();
+    }
+
+    unsafe fn unlink(&mut self, mut ptr: NonNull<ArcHandle<T>>) {
+        let handle = ptr.as_mut();
+        debug_assert!(handle.link.is_linked());
+
+        match handle.queue {
+            Queue::None => unreachable!(),
+            Queue::T1 => {
+                self.t1.remove_raw(handle.link.raw());
+                self.t1_charges -= handle.base().charge();
+            }
+            Queue::T2 => {
+                self.t2.remove_raw(handle.link.raw());
+                self.t2_charges -= handle.base().charge();
+            }
+        }
+        handle.queue = Queue::None;
+    }
+
+    /// Keep `t1 + b1 <= c` and `t1 + t2 + b1 + b2 <= 2c`.
+    fn trim_ghosts(&mut self) {
+        while self.t1_charges + self.b1.charges > self.capacity && self.b1.charges > 0 {
+            self.b1.pop();
+        }
+        while self.t1_charges + self.t2_charges + self.b1.charges + self.b2.charges > self.capacity * 2
+            && self.b2.charges > 0
+        {
+            self.b2.pop();
+        }
+    }
+}
+
+impl<T> Eviction for ArcEviction<T>
+where
+    T: Send + Sync + 'static,
+{
+    type Handle = ArcHandle<T>;
+    type Config = ArcConfig;
+    /// Whether the entry is in `t2`.
+    type State = bool;
+
+    unsafe fn new(capacity: usize, _config: &Self::Config) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            t1: Dlist::new(),
+            t2: Dlist::new(),
+            b1: GhostList::default(),
+            b2: GhostList::default(),
+            t1_charges: 0,
+            t2_charges: 0,
+            capacity,
+            p: 0,
+            b2_hit: false,
+        }
+    }
+
+    unsafe fn push(&mut self, mut ptr: NonNull<Self::Handle>) {
+        let handle = ptr.as_mut();
+        let hash = handle.base().hash();
+        let charge = handle.base().charge();
+
+        self.b2_hit = false;
+
+        if self.b1.contains(hash) {
+            let delta = if self.b1.charges >= self.b2.charges {
+                charge
+            } else {
+                charge * self.b2.charges / self.b1.charges.max(1)
+            };
+            self.p = std::cmp::min(self.p + delta, self.capacity);
+            self.b1.remove(hash);
+            self.push_t2(ptr);
+        } else if self.b2.contains(hash) {
+            let delta = if self.b2.charges >= self.b1.charges {
+                charge
+            } else {
+                charge * self.b1.charges / self.b2.charges.max(1)
+            };
+            self.p = self.p.saturating_sub(delta);
+            self.b2.remove(hash);
+            self.b2_hit = true;
+            self.push_t2(ptr);
+        } else {
+            self.t1.push_back(ptr);
+            handle.queue = Queue::T1;
+            self.t1_charges += charge;
+        }
+
+        handle.base_mut().set_in_eviction(true);
+
+        self.trim_ghosts();
+    }
+
+    unsafe fn pop(&mut self) -> Option<NonNull<Self::Handle>> {
+        let from_t1 = !self.t1.is_empty()
+            && (self.t1_charges > self.p || (self.t1_charges == self.p && self.b2_hit) || self.t2.is_empty());
+
+        let mut ptr = if from_t1 {
+            self.t1.pop_front()?
+        } else {
+            self.t2.pop_front()?
+        };
+
+        let handle = ptr.as_mut();
+        let hash = handle.base().hash();
+        let charge = handle.base().charge();
+        if from_t1 {
+            self.t1_charges -= charge;
+            self.b1.push(hash, charge);
+        } else {
+            self.t2_charges -= charge;
+            self.b2.push(hash, charge);
+        }
+        handle.queue = Queue::None;
+        handle.base_mut().set_in_eviction(false);
+
+        self.trim_ghosts();
+
+        Some(ptr)
+    }
+
+    unsafe fn release(&mut self, _: NonNull<Self::Handle>) {}
+
+    unsafe fn acquire(&mut self, ptr: NonNull<Self::Handle>) {
+        // The entry may have been popped but still held by external users.
+        if !ptr.as_ref().base().is_in_eviction() {
+            return;
+        }
+
+        // A hit moves the entry to the MRU end of `t2`.
+        self.unlink(ptr);
+        self.push_t2(ptr);
+    }
+
+    unsafe fn remove(&mut self, mut ptr: NonNull<Self::Handle>) {
+        self.unlink(ptr);
+        ptr.as_mut().base_mut().set_in_eviction(false);
+    }
+
+    unsafe fn clear(&mut self) -> Vec<NonNull<Self::Handle>> {
+        let mut res = Vec::with_capacity(self.len());
+        while let Some(mut ptr) = self.t1.pop_front().or_else(|| self.t2.pop_front()) {
+            let handle = ptr.as_mut();
+            handle.queue = Queue::None;
+            handle.base_mut().set_in_eviction(false);
+            res.push(ptr);
+        }
+        self.b1.clear();
+        self.b2.clear();
+        self.t1_charges = 0;
+        self.t2_charges = 0;
+        self.p = 0;
+        self.b2_hit = false;
+        res
+    }
+
+    unsafe fn resize(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.p = std::cmp::min(self.p, capacity);
+        self.trim_ghosts();
+    }
+
+    unsafe fn snapshot(&self) -> Vec<(NonNull<Self::Handle>, Self::State)> {
+        self.t1
+            .iter()
+            .map(|handle| (NonNull::from(handle), false))
+            .chain(self.t2.iter().map(|handle| (NonNull::from(handle), true)))
+            .collect()
+    }
+
+    /// The adaptive target and the ghost lists are not restored, and adapt again from the restored entries.
+    unsafe fn restore(&mut self, mut ptr: NonNull<Self::Handle>, in_t2: Self::State) {
+        let handle = ptr.as_mut();
+
+        debug_assert!(!handle.link.is_linked());
+
+        if in_t2 {
+            self.push_t2(ptr);
+        } else {
+            self.t1.push_back(ptr);
+            handle.queue = Queue::T1;
+            self.t1_charges += handle.base().charge();
+        }
+
+        handle.base_mut().set_in_eviction(true);
+    }
+
+    fn len(&self) -> usize {
+        self.t1.len() + self.t2.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+unsafe impl<T> Send for ArcEviction<T> where T: Send + Sync + 'static {}
+unsafe impl<T> Sync for ArcEviction<T> where T: Send + Sync + 'static {}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::eviction::test_utils::TestEviction;
+
+    impl<T> TestEviction for ArcEviction<T>
+    where
+        T: Send + Sync + 'static + Clone,
+    {
+        fn dump(&self) -> Vec<T> {
+            self.t1
+                .iter()
+                .chain(self.t2.iter())
+                .map(|handle| handle.base().data_unwrap_unchecked().clone())
+                .collect_vec()
+        }
+    }
+
+    type TestArc = ArcEviction<u64>;
+    type TestArcHandle = ArcHandle<u64>;
+
+    fn assert_test_arc(arc: &TestArc, t1: Vec<u64>, t2: Vec<u64>) {
+        let mut s = arc.dump();
+        let t = s.split_off(arc.t1.len());
+        assert_eq!((&s, &t), (&t1, &t2));
+        assert_eq!(arc.t1_charges, t1.len());
+        assert_eq!(arc.t2_charges, t2.len());
+    }
+
+    #[test]
+    fn test_arc() {
+        unsafe {
+            let ptrs = (0..8)
+                .map(|i| {
+                    let mut handle = Box::new(TestArcHandle::new());
+                    handle.init(i, i, 1, ArcContext);
+                    NonNull::new_unchecked(Box::into_raw(handle))
+                })
+                .collect_vec();
+
+            let mut arc = TestArc::new(4, &ArcConfig {});
+
+            (0..4).for_each(|i| arc.push(ptrs[i]));
+            assert_test_arc(&arc, vec![0, 1, 2, 3], vec![]);
+
+            // A hit moves the entry to `t2`.
+            arc.acquire(ptrs[0]);
+            assert_test_arc(&arc, vec![1, 2, 3], vec![0]);
+
+            assert_eq!(arc.pop(), Some(ptrs[1]));
+            assert!(arc.b1.contains(1));
+
+            // A `b1` hit grows the target of `t1`.
+            arc.push(ptrs[1]);
+            assert_eq!(arc.p, 1);
+            assert!(!arc.b1.contains(1));
+            assert_test_arc(&arc, vec![2, 3], vec![0, 1]);
+
+            assert_eq!(arc.pop(), Some(ptrs[2]));
+            // `t1` reaches its target, evict from `t2`.
+            assert_eq!(arc.pop(), Some(ptrs[0]));
+            assert!(arc.b1.contains(2));
+            assert!(arc.b2.contains(0));
+            assert_test_arc(&arc, vec![3], vec![1]);
+
+            // A `b2` hit shrinks the target of `t1`.
+            arc.push(ptrs[0]);
+            assert_eq!(arc.p, 0);
+            assert_test_arc(&arc, vec![3], vec![1, 0]);
+
+            assert_eq!(arc.pop(), Some(ptrs[3]));
+            assert_test_arc(&arc, vec![], vec![1, 0]);
+
+            // Ghost lists are bounded by the capacity.
+            (4..8).for_each(|i| arc.push(ptrs[i]));
+            assert!(arc.t1_charges + arc.b1.charges <= 4);
+            assert_test_arc(&arc, vec![4, 5, 6, 7], vec![1, 0]);
+
+            arc.remove(ptrs[5]);
+            assert_test_arc(&arc, vec![4, 6, 7], vec![1, 0]);
+
+            assert_eq!(arc.clear(), [4, 6, 7, 1, 0].into_iter().map(|i| ptrs[i]).collect_vec());
+            assert!(arc.is_empty());
+            assert_eq!(arc.b1.charges + arc.b2.charges, 0);
+
+            for ptr in ptrs {
+                let _ = Box::from_raw(ptr.as_ptr());
+            }
+        }
+    }
+
+    #[test]
+    fn test_arc_ghost_list() {
+        let mut ghost = GhostList::default();
+        (0..100).for_each(|i| ghost.push(i, 1));
+        (0..90).for_each(|i| assert!(ghost.remove(i)));
+        assert_eq!(ghost.charges, 10);
+
+        // Tombstones are compacted on push.
+        ghost.push(100, 1);
+        assert_eq!(ghost.queue.len(), 11);
+
+        // Tombstones are skipped on pop.
+        assert!(ghost.remove(90));
+        ghost.pop();
+        assert!(!ghost.contains(91));
+        assert!(ghost.contains(92));
+        assert_eq!(ghost.charges, 9);
+    }
+}
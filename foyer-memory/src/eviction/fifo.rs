@@ -102,6 +102,7 @@ where
 {
     type Handle = FifoHandle<T>;
     type Config = FifoConfig;
+    type State = ();
 
     unsafe fn new(_capacity: usize, _config: &Self::Config) -> Self
     where
@@ -141,6 +142,16 @@ where
         res
     }
 
+    unsafe fn resize(&mut self, _: usize) {}
+
+    unsafe fn snapshot(&self) -> Vec<(NonNull<Self::Handle>, Self::State)> {
+        self.queue.iter().map(|handle| (NonNull::from(handle), ())).collect()
+    }
+
+    unsafe fn restore(&mut self, ptr: NonNull<Self::Handle>, _: Self::State) {
+        self.push(ptr);
+    }
+
     fn len(&self) -> usize {
         self.queue.len()
     }
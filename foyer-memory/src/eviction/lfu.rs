@@ -12,7 +12,7 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 
-use std::{fmt::Debug, ptr::NonNull};
+use std::{f64::consts::LN_2, fmt::Debug, ptr::NonNull};
 
 use cmsketch::CMSketchU16;
 use foyer_intrusive::{
@@ -44,6 +44,201 @@ pub struct LfuConfig {
 
     pub cmsketch_eps: f64,
     pub cmsketch_confidence: f64,
+
+    /// Doorkeeper bloom filter in front of the count-min sketch, `None` to disable it.
+    ///
+    /// The first access of a key in each aging period is only recorded by the doorkeeper, so one-hit wonders don't
+    /// pollute the count-min sketch.
+    pub doorkeeper: Option<LfuDoorkeeperConfig>,
+
+    /// Aging sample size as a multiple of the cache capacity, `None` to use the width of the count-min sketch.
+    ///
+    /// After the count of the recorded accesses reaches the sample size, all frequencies are halved and the doorkeeper
+    /// is reset.
+    pub aging_sample_ratio: Option<f64>,
+
+    /// Adaptive window sizing with hill climbing, `None` to disable it.
+    ///
+    /// If enabled, `window_capacity_ratio` and `protected_capacity_ratio` are only the initial sizes.
+    pub hill_climbing: Option<LfuHillClimbingConfig>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LfuDoorkeeperConfig {
+    /// expected false positive rate of the doorkeeper with the keys accessed in an aging period
+    pub false_positive_rate: f64,
+}
+
+/// Bloom filter that records the keys accessed once in the current aging period.
+#[derive(Debug)]
+struct Doorkeeper {
+    bits: Vec<u64>,
+    hashes: u64,
+}
+
+impl Doorkeeper {
+    fn new(items: usize, false_positive_rate: f64) -> Self {
+        assert!(
+            false_positive_rate > 0.0 && false_positive_rate < 1.0,
+            "false_positive_rate must be in (0, 1), given: {}",
+            false_positive_rate
+        );
+
+        let items = items.max(1) as f64;
+        let bits = (-items * false_positive_rate.ln() / (LN_2 * LN_2)).ceil().max(64.0) as usize;
+        let hashes = (bits as f64 / items * LN_2).round().clamp(1.0, 16.0) as u64;
+
+        Self {
+            bits: vec![0; bits.div_ceil(64)],
+            hashes,
+        }
+    }
+
+    /// Insert the hash, return `true` if it may have been inserted before.
+    fn insert(&mut self, hash: u64) -> bool {
+        let len = self.bits.len() as u64 * 64;
+        let delta = hash.rotate_left(32) | 1;
+        let mut contains = true;
+        for i in 0..self.hashes {
+            let bit = hash.wrapping_add(i.wrapping_mul(delta)) % len;
+            let (word, mask) = ((bit / 64) as usize, 1 << (bit % 64));
+            if self.bits[word] & mask == 0 {
+                contains = false;
+                self.bits[word] |= mask;
+            }
+        }
+        contains
+    }
+
+    fn contains(&self, hash: u64) -> bool {
+        let len = self.bits.len() as u64 * 64;
+        let delta = hash.rotate_left(32) | 1;
+        (0..self.hashes).all(|i| {
+            let bit = hash.wrapping_add(i.wrapping_mul(delta)) % len;
+            self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0
+        })
+    }
+
+    fn clear(&mut self) {
+        self.bits.fill(0);
+    }
+}
+
+/// Config of the adaptive window sizing of [`Lfu`], inspired by the hill climber of
+/// [Caffeine](https://github.com/ben-manes/caffeine).
+///
+/// Accesses and insertions are sampled as hits and misses. After each sample period, the `window` capacity is moved by
+/// a step in the direction that improved the hit ratio in the last period, or in the reversed direction if the hit
+/// ratio dropped. The remaining capacity is split between `probation` and `protected` with the initial proportion.
+#[derive(Debug, Clone)]
+pub struct LfuHillClimbingConfig {
+    /// sample period, in the count of accesses and insertions, as a multiple of the cache capacity
+    pub sample_ratio: f64,
+    /// initial step size as a ratio of the cache capacity
+    pub step_ratio: f64,
+    /// decay rate of the step size after each sample period
+    pub step_decay: f64,
+    /// hit ratio change that restarts the step size from `step_ratio`
+    pub restart_threshold: f64,
+}
+
+impl Default for LfuHillClimbingConfig {
+    fn default() -> Self {
+        Self {
+            sample_ratio: 10.0,
+            step_ratio: 0.0625,
+            step_decay: 0.98,
+            restart_threshold: 0.05,
+        }
+    }
+}
+
+/// Current queue sizes of [`Lfu`], in charges.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LfuQueueSizes {
+    pub window: usize,
+    pub probation: usize,
+    pub protected: usize,
+
+    pub window_capacity: usize,
+    pub protected_capacity: usize,
+}
+
+impl std::ops::Add for LfuQueueSizes {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            window: self.window + rhs.window,
+            probation: self.probation + rhs.probation,
+            protected: self.protected + rhs.protected,
+            window_capacity: self.window_capacity + rhs.window_capacity,
+            protected_capacity: self.protected_capacity + rhs.protected_capacity,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct HillClimber {
+    config: LfuHillClimbingConfig,
+
+    sample_size: usize,
+    hits: usize,
+    misses: usize,
+
+    prev_hit_ratio: f64,
+    /// signed step size in charges
+    step: f64,
+}
+
+impl HillClimber {
+    fn new(capacity: usize, config: LfuHillClimbingConfig) -> Self {
+        let sample_size = std::cmp::max((capacity as f64 * config.sample_ratio) as usize, 1);
+        let step = capacity as f64 * config.step_ratio;
+        Self {
+            config,
+            sample_size,
+            hits: 0,
+            misses: 0,
+            prev_hit_ratio: 0.0,
+            step,
+        }
+    }
+
+    /// Restart climbing with the sample size and the step size of the new capacity.
+    fn resize(&mut self, capacity: usize) {
+        self.sample_size = std::cmp::max((capacity as f64 * self.config.sample_ratio) as usize, 1);
+        self.step = capacity as f64 * self.config.step_ratio * self.step.signum();
+        self.hits = 0;
+        self.misses = 0;
+    }
+
+    /// Return the adjustment of the `window` capacity if a sample period ends.
+    fn record(&mut self, hit: bool, capacity: usize) -> Option<f64> {
+        if hit {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+        if self.hits + self.misses < self.sample_size {
+            return None;
+        }
+
+        let hit_ratio = self.hits as f64 / (self.hits + self.misses) as f64;
+        let change = hit_ratio - self.prev_hit_ratio;
+        let amount = if change >= 0.0 { self.step } else { -self.step };
+        self.step = if change.abs() >= self.config.restart_threshold {
+            capacity as f64 * self.config.step_ratio * amount.signum()
+        } else {
+            amount * self.config.step_decay
+        };
+
+        self.prev_hit_ratio = hit_ratio;
+        self.hits = 0;
+        self.misses = 0;
+
+        Some(amount)
+    }
 }
 #[derive(Debug, Clone)]
 pub struct LfuContext;
@@ -146,10 +341,18 @@ where
     window_charges_capacity: usize,
     protected_charges_capacity: usize,
 
+    capacity: usize,
+    /// `protected` capacity ratio of the capacity excluding `window`
+    protected_ratio: f64,
+    hill_climber: Option<HillClimber>,
+
     frequencies: CMSketchU16,
+    doorkeeper: Option<Doorkeeper>,
 
     step: usize,
     decay: usize,
+
+    config: LfuConfig,
 }
 
 impl<T> Lfu<T>
@@ -176,12 +379,84 @@ where
         }
     }
 
+    /// Overflow entries from `window` to `probation` if `window` charges exceeds the capacity.
+    unsafe fn overflow_window(&mut self) {
+        while self.window_charges > self.window_charges_capacity {
+            debug_assert!(!self.window.is_empty());
+            let mut ptr = self.window.pop_front().unwrap_unchecked();
+            let handle = ptr.as_mut();
+            self.decrease_queue_charges(handle);
+            handle.queue = Queue::Probation;
+            self.increase_queue_charges(handle);
+            self.probation.push_back(ptr);
+        }
+    }
+
+    /// Overflow entries from `protected` to `probation` if `protected` charges exceeds the capacity.
+    unsafe fn overflow_protected(&mut self) {
+        while self.protected_charges > self.protected_charges_capacity {
+            debug_assert!(!self.protected.is_empty());
+            let mut ptr = self.protected.pop_front().unwrap_unchecked();
+            let handle = ptr.as_mut();
+            self.decrease_queue_charges(handle);
+            handle.queue = Queue::Probation;
+            self.increase_queue_charges(handle);
+            self.probation.push_back(ptr);
+        }
+    }
+
+    /// Feed the hill climber (if enabled) and resize the queues after each sample period.
+    unsafe fn climb(&mut self, hit: bool) {
+        let Some(amount) = self
+            .hill_climber
+            .as_mut()
+            .and_then(|climber| climber.record(hit, self.capacity))
+        else {
+            return;
+        };
+
+        let window = (self.window_charges_capacity as f64 + amount).clamp(0.0, self.capacity as f64) as usize;
+        self.window_charges_capacity = window;
+        self.protected_charges_capacity = ((self.capacity - window) as f64 * self.protected_ratio) as usize;
+
+        self.overflow_window();
+        self.overflow_protected();
+    }
+
+    fn estimate(&self, hash: u64) -> u16 {
+        let freq = self.frequencies.estimate(hash);
+        match self.doorkeeper.as_ref() {
+            Some(doorkeeper) if doorkeeper.contains(hash) => freq.saturating_add(1),
+            _ => freq,
+        }
+    }
+
+    /// Get the current queue sizes.
+    pub fn queue_sizes(&self) -> LfuQueueSizes {
+        LfuQueueSizes {
+            window: self.window_charges,
+            probation: self.probation_charges,
+            protected: self.protected_charges,
+            window_capacity: self.window_charges_capacity,
+            protected_capacity: self.protected_charges_capacity,
+        }
+    }
+
     fn update_frequencies(&mut self, hash: u64) {
-        self.frequencies.inc(hash);
+        let admitted = match self.doorkeeper.as_mut() {
+            Some(doorkeeper) => doorkeeper.insert(hash),
+            None => true,
+        };
+        if admitted {
+            self.frequencies.inc(hash);
+        }
         self.step += 1;
         if self.step >= self.decay {
             self.step >>= 1;
             self.frequencies.halve();
+            if let Some(doorkeeper) = self.doorkeeper.as_mut() {
+                doorkeeper.clear();
+            }
         }
     }
 }
@@ -192,6 +467,8 @@ where
 {
     type Handle = LfuHandle<T>;
     type Config = LfuConfig;
+    /// The queue that the entry is in (`0` for `window`, `1` for `probation`, `2` for `protected`), and its frequency.
+    type State = (u8, u16);
 
     unsafe fn new(capacity: usize, config: &Self::Config) -> Self
     where
@@ -217,8 +494,20 @@ where
 
         let window_charges_capacity = (capacity as f64 * config.window_capacity_ratio) as usize;
         let protected_charges_capacity = (capacity as f64 * config.protected_capacity_ratio) as usize;
+        let protected_ratio = config.protected_capacity_ratio / (1.0 - config.window_capacity_ratio);
+        let hill_climber = config
+            .hill_climbing
+            .clone()
+            .map(|config| HillClimber::new(capacity, config));
         let frequencies = CMSketchU16::new(config.cmsketch_eps, config.cmsketch_confidence);
-        let decay = frequencies.width();
+        let decay = match config.aging_sample_ratio {
+            Some(ratio) => std::cmp::max((capacity as f64 * ratio) as usize, 1),
+            None => frequencies.width(),
+        };
+        let doorkeeper = config
+            .doorkeeper
+            .as_ref()
+            .map(|config| Doorkeeper::new(decay, config.false_positive_rate));
 
         Self {
             window: Dlist::new(),
@@ -229,9 +518,14 @@ where
             protected_charges: 0,
             window_charges_capacity,
             protected_charges_capacity,
+            capacity,
+            protected_ratio,
+            hill_climber,
             frequencies,
+            doorkeeper,
             step: 0,
             decay,
+            config: config.clone(),
         }
     }
 
@@ -249,16 +543,9 @@ where
         self.increase_queue_charges(handle);
         self.update_frequencies(handle.base().hash());
 
-        // If `window` charges exceeds the capacity, overflow entry from `window` to `probation`.
-        while self.window_charges > self.window_charges_capacity {
-            debug_assert!(!self.window.is_empty());
-            let mut ptr = self.window.pop_front().unwrap_unchecked();
-            let handle = ptr.as_mut();
-            self.decrease_queue_charges(handle);
-            handle.queue = Queue::Probation;
-            self.increase_queue_charges(handle);
-            self.probation.push_back(ptr);
-        }
+        self.overflow_window();
+
+        self.climb(false);
     }
 
     unsafe fn pop(&mut self) -> Option<NonNull<Self::Handle>> {
@@ -269,8 +556,7 @@ where
             (None, Some(_)) => self.probation.pop_front(),
             (Some(_), None) => self.window.pop_front(),
             (Some(window), Some(probation)) => {
-                if self.frequencies.estimate(window.base().hash()) < self.frequencies.estimate(probation.base().hash())
-                {
+                if self.estimate(window.base().hash()) < self.estimate(probation.base().hash()) {
                     self.window.pop_front()
 
                     // TODO(MrCroxx): Rotate probation to prevent a high frequency but cold head holds back promotion
@@ -323,16 +609,7 @@ where
                 self.increase_queue_charges(handle);
                 self.protected.push_back(ptr);
 
-                // If `protected` charges exceeds the capacity, overflow entry from `protected` to `probation`.
-                while self.protected_charges > self.protected_charges_capacity {
-                    debug_assert!(!self.protected.is_empty());
-                    let mut ptr = self.protected.pop_front().unwrap_unchecked();
-                    let handle = ptr.as_mut();
-                    self.decrease_queue_charges(handle);
-                    handle.queue = Queue::Probation;
-                    self.increase_queue_charges(handle);
-                    self.probation.push_back(ptr);
-                }
+                self.overflow_protected();
             }
             Queue::Protected => {
                 // Move to MRU position of `protected`.
@@ -346,6 +623,7 @@ where
 
     unsafe fn acquire(&mut self, ptr: NonNull<Self::Handle>) {
         self.update_frequencies(ptr.as_ref().base().hash());
+        self.climb(true);
     }
 
     unsafe fn remove(&mut self, mut ptr: NonNull<Self::Handle>) {
@@ -383,6 +661,85 @@ where
         res
     }
 
+    unsafe fn resize(&mut self, capacity: usize) {
+        // Keep the `window` ratio, which may have been adapted by the hill climber.
+        let window_ratio = if self.capacity == 0 {
+            self.config.window_capacity_ratio
+        } else {
+            self.window_charges_capacity as f64 / self.capacity as f64
+        };
+        self.capacity = capacity;
+        self.window_charges_capacity = (capacity as f64 * window_ratio) as usize;
+        self.protected_charges_capacity =
+            ((capacity - self.window_charges_capacity) as f64 * self.protected_ratio) as usize;
+
+        if let Some(climber) = self.hill_climber.as_mut() {
+            climber.resize(capacity);
+        }
+
+        // The aging period and the doorkeeper are sized by the capacity only if `aging_sample_ratio` is set.
+        if let Some(ratio) = self.config.aging_sample_ratio {
+            self.decay = std::cmp::max((capacity as f64 * ratio) as usize, 1);
+            self.step = std::cmp::min(self.step, self.decay - 1);
+            self.doorkeeper = self
+                .config
+                .doorkeeper
+                .as_ref()
+                .map(|config| Doorkeeper::new(self.decay, config.false_positive_rate));
+        }
+
+        self.overflow_window();
+        self.overflow_protected();
+    }
+
+    unsafe fn snapshot(&self) -> Vec<(NonNull<Self::Handle>, Self::State)> {
+        [&self.window, &self.probation, &self.protected]
+            .into_iter()
+            .enumerate()
+            .flat_map(|(queue, list)| {
+                list.iter().map(move |handle| {
+                    let freq = self.frequencies.estimate(handle.base().hash());
+                    (NonNull::from(handle), (queue as u8, freq))
+                })
+            })
+            .collect()
+    }
+
+    unsafe fn restore(&mut self, mut ptr: NonNull<Self::Handle>, (queue, freq): Self::State) {
+        let handle = ptr.as_mut();
+
+        debug_assert!(!handle.link.is_linked());
+        debug_assert!(!handle.base().is_in_eviction());
+        debug_assert_eq!(handle.queue, Queue::None);
+
+        match queue {
+            0 => {
+                self.window.push_back(ptr);
+                handle.queue = Queue::Window;
+            }
+            1 => {
+                self.probation.push_back(ptr);
+                handle.queue = Queue::Probation;
+            }
+            _ => {
+                self.protected.push_back(ptr);
+                handle.queue = Queue::Protected;
+            }
+        }
+        handle.base_mut().set_in_eviction(true);
+        self.increase_queue_charges(handle);
+
+        // Raise the estimated frequency to the dumped one, the estimation may already be higher due to collisions.
+        let hash = handle.base().hash();
+        let estimate = self.frequencies.estimate(hash);
+        if freq > estimate {
+            self.frequencies.inc_by(hash, freq - estimate);
+        }
+
+        self.overflow_window();
+        self.overflow_protected();
+    }
+
     fn len(&self) -> usize {
         self.window.len() + self.probation.len() + self.protected.len()
     }
@@ -440,7 +797,7 @@ mod tests {
     }
 
     fn assert_min_frequency(lfu: &TestLfu, hash: u64, count: usize) {
-        let freq = lfu.frequencies.estimate(hash);
+        let freq = lfu.estimate(hash);
         assert!(freq >= count as u16, "assert {freq} >= {count} failed for {hash}");
     }
 
@@ -461,6 +818,9 @@ mod tests {
                 protected_capacity_ratio: 0.6,
                 cmsketch_eps: 0.01,
                 cmsketch_confidence: 0.95,
+                hill_climbing: None,
+                doorkeeper: None,
+                aging_sample_ratio: None,
             };
             let mut lfu = TestLfu::new(10, &config);
 
@@ -538,4 +898,167 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_lfu_hill_climbing() {
+        unsafe {
+            let ptrs = (0..30)
+                .map(|i| {
+                    let mut handle = Box::new(TestLfuHandle::new());
+                    handle.init(i, i, 1, LfuContext);
+                    NonNull::new_unchecked(Box::into_raw(handle))
+                })
+                .collect_vec();
+
+            // window: 10, probation: 10, protected: 80, sample period: 10, step: 10
+            let config = LfuConfig {
+                window_capacity_ratio: 0.1,
+                protected_capacity_ratio: 0.8,
+                cmsketch_eps: 0.01,
+                cmsketch_confidence: 0.95,
+                hill_climbing: Some(LfuHillClimbingConfig {
+                    sample_ratio: 0.1,
+                    step_ratio: 0.1,
+                    step_decay: 0.98,
+                    restart_threshold: 0.05,
+                }),
+                doorkeeper: None,
+                aging_sample_ratio: None,
+            };
+            let mut lfu = TestLfu::new(100, &config);
+
+            let sizes = |window, probation, window_capacity, protected_capacity| LfuQueueSizes {
+                window,
+                probation,
+                protected: 0,
+                window_capacity,
+                protected_capacity,
+            };
+
+            // The hit ratio does not drop, grow `window` by a step.
+            (0..10).for_each(|i| lfu.push(ptrs[i]));
+            assert_eq!(lfu.queue_sizes(), sizes(10, 0, 20, 71));
+
+            // The hit ratio raises, keep growing `window` by a decayed step.
+            (0..10).for_each(|_| lfu.acquire(ptrs[0]));
+            assert_eq!(lfu.queue_sizes(), sizes(10, 0, 29, 63));
+
+            // The hit ratio drops, shrink `window` by a restarted step.
+            (10..20).for_each(|i| lfu.push(ptrs[i]));
+            assert_eq!(lfu.queue_sizes(), sizes(19, 1, 19, 72));
+
+            // The hit ratio does not drop, keep shrinking `window`, entries overflow to `probation`.
+            (20..30).for_each(|i| lfu.push(ptrs[i]));
+            assert_eq!(lfu.queue_sizes(), sizes(9, 21, 9, 80));
+
+            lfu.clear();
+
+            for ptr in ptrs {
+                let _ = Box::from_raw(ptr.as_ptr());
+            }
+        }
+    }
+
+    #[test]
+    fn test_lfu_resize() {
+        unsafe {
+            let ptrs = (0..10)
+                .map(|i| {
+                    let mut handle = Box::new(TestLfuHandle::new());
+                    handle.init(i, i, 1, LfuContext);
+                    NonNull::new_unchecked(Box::into_raw(handle))
+                })
+                .collect_vec();
+
+            let config = LfuConfig {
+                window_capacity_ratio: 0.1,
+                protected_capacity_ratio: 0.8,
+                cmsketch_eps: 0.01,
+                cmsketch_confidence: 0.95,
+                hill_climbing: None,
+                doorkeeper: None,
+                aging_sample_ratio: Some(1.0),
+            };
+            let mut lfu = TestLfu::new(100, &config);
+            assert_eq!(lfu.decay, 100);
+
+            let sizes = |window, probation, window_capacity, protected_capacity| LfuQueueSizes {
+                window,
+                probation,
+                protected: 0,
+                window_capacity,
+                protected_capacity,
+            };
+
+            (0..10).for_each(|i| lfu.push(ptrs[i]));
+            assert_eq!(lfu.queue_sizes(), sizes(10, 0, 10, 80));
+
+            // Shrinking the capacity overflows `window` to `probation`, and shortens the aging period.
+            lfu.resize(50);
+            assert_eq!(lfu.queue_sizes(), sizes(5, 5, 5, 40));
+            assert_eq!(lfu.decay, 50);
+            assert_test_lfu(&lfu, 10, 5, 5, 0, vec![5, 6, 7, 8, 9, 0, 1, 2, 3, 4]);
+
+            lfu.resize(200);
+            assert_eq!(lfu.queue_sizes(), sizes(5, 5, 20, 160));
+            assert_eq!(lfu.decay, 200);
+
+            lfu.clear();
+
+            for ptr in ptrs {
+                let _ = Box::from_raw(ptr.as_ptr());
+            }
+        }
+    }
+
+    #[test]
+    fn test_lfu_doorkeeper_and_aging() {
+        unsafe {
+            let ptrs = (0..2)
+                .map(|i| {
+                    let mut handle = Box::new(TestLfuHandle::new());
+                    handle.init(i, i, 1, LfuContext);
+                    NonNull::new_unchecked(Box::into_raw(handle))
+                })
+                .collect_vec();
+
+            // aging sample size: 10
+            let config = LfuConfig {
+                window_capacity_ratio: 0.2,
+                protected_capacity_ratio: 0.6,
+                cmsketch_eps: 0.01,
+                cmsketch_confidence: 0.95,
+                hill_climbing: None,
+                doorkeeper: Some(LfuDoorkeeperConfig {
+                    false_positive_rate: 0.01,
+                }),
+                aging_sample_ratio: Some(1.0),
+            };
+            let mut lfu = TestLfu::new(10, &config);
+            assert_eq!(lfu.decay, 10);
+
+            // The first access is absorbed by the doorkeeper.
+            lfu.push(ptrs[0]);
+            assert_eq!(lfu.frequencies.estimate(0), 0);
+            assert_eq!(lfu.estimate(0), 1);
+
+            (0..7).for_each(|_| lfu.acquire(ptrs[0]));
+            assert_eq!(lfu.frequencies.estimate(0), 7);
+            assert_eq!(lfu.estimate(0), 8);
+
+            // The 10th access halves the frequencies and resets the doorkeeper.
+            lfu.push(ptrs[1]);
+            assert_eq!(lfu.estimate(1), 1);
+            lfu.acquire(ptrs[0]);
+            assert_eq!(lfu.step, 5);
+            assert_eq!(lfu.estimate(0), 4);
+            assert_eq!(lfu.estimate(1), 0);
+
+            lfu.clear();
+
+            for ptr in ptrs {
+                let _ = Box::from_raw(ptr.as_ptr());
+            }
+        }
+    }
 }
@@ -123,6 +123,7 @@ where
 
     high_priority_charges: usize,
     high_priority_charges_capacity: usize,
+    high_priority_pool_ratio: f64,
 }
 
 impl<T> Lru<T>
@@ -148,6 +149,8 @@ where
 {
     type Handle = LruHandle<T>;
     type Config = LruConfig;
+    /// Whether the entry is in the high priority pool.
+    type State = bool;
 
     unsafe fn new(capacity: usize, config: &Self::Config) -> Self
     where
@@ -166,6 +169,7 @@ where
             list: Dlist::new(),
             high_priority_charges: 0,
             high_priority_charges_capacity,
+            high_priority_pool_ratio: config.high_priority_pool_ratio,
         }
     }
 
@@ -256,6 +260,37 @@ where
         res
     }
 
+    unsafe fn resize(&mut self, capacity: usize) {
+        self.high_priority_charges_capacity = (capacity as f64 * self.high_priority_pool_ratio) as usize;
+        self.may_overflow_high_priority_pool();
+    }
+
+    unsafe fn snapshot(&self) -> Vec<(NonNull<Self::Handle>, Self::State)> {
+        self.list
+            .iter()
+            .chain(self.high_priority_list.iter())
+            .map(|handle| (NonNull::from(handle), handle.in_high_priority_pool))
+            .collect()
+    }
+
+    unsafe fn restore(&mut self, mut ptr: NonNull<Self::Handle>, in_high_priority_pool: Self::State) {
+        let handle = ptr.as_mut();
+
+        debug_assert!(!handle.link.is_linked());
+
+        handle.in_high_priority_pool = in_high_priority_pool;
+        if in_high_priority_pool {
+            self.high_priority_charges += handle.base().charge();
+            self.high_priority_list.push_back(ptr);
+
+            self.may_overflow_high_priority_pool();
+        } else {
+            self.list.push_back(ptr);
+        }
+
+        handle.base_mut().set_in_eviction(true);
+    }
+
     fn len(&self) -> usize {
         self.high_priority_list.len() + self.list.len()
     }
@@ -424,6 +459,19 @@ pub mod tests {
                 )
             );
 
+            // Shrinking the capacity overflows the high priority pool.
+            // 10, 11, 1, 3, 4, 5, [6, 0]
+            lru.resize(4);
+            assert_eq!(lru.high_priority_charges_capacity, 2);
+            assert_eq!(lru.high_priority_charges, 2);
+            assert_eq!(
+                dump_test_lru(&lru),
+                (
+                    vec![ptrs[10], ptrs[11], ptrs[1], ptrs[3], ptrs[4], ptrs[5]],
+                    vec![ptrs[6], ptrs[0]]
+                )
+            );
+
             let ps = lru.clear();
             assert_eq!(ps, [10, 11, 1, 3, 4, 5, 6, 0].map(|i| ptrs[i]));
 
@@ -14,12 +14,17 @@
 
 use std::ptr::NonNull;
 
+use serde::{de::DeserializeOwned, Serialize};
+
 /// The lifetime of `handle: Self::H` is managed by [`Indexer`].
 ///
 /// Each `handle`'s lifetime in [`Indexer`] must outlive the raw pointer in [`Eviction`].
 pub trait Eviction: Send + Sync + 'static {
     type Handle;
     type Config;
+    /// The per-entry state exported by [`Eviction::snapshot`] and imported by [`Eviction::restore`], e.g. the queue that
+    /// the entry is in and its frequency.
+    type State: Serialize + DeserializeOwned + Send + Sync + 'static;
 
     /// Create a new empty eviction container.
     ///
@@ -83,6 +88,33 @@ pub trait Eviction: Send + Sync + 'static {
     /// All base handles associated to the `ptr`s must be set NOT in cache.
     unsafe fn clear(&mut self) -> Vec<NonNull<Self::Handle>>;
 
+    /// Update the capacity and the internal capacities derived from it.
+    ///
+    /// The eviction container may rebalance its internal queues, but never evicts entries by itself. The caller is
+    /// responsible to `pop` entries until the usage fits the new capacity.
+    ///
+    /// # Safety
+    ///
+    /// The base handles associated to the `ptr`s in the eviction container must be kept valid.
+    unsafe fn resize(&mut self, capacity: usize);
+
+    /// Take a snapshot of all `ptr`s in the eviction container with their states, in the eviction order.
+    ///
+    /// Restoring the `ptr`s in the returned order with [`Eviction::restore`] into an empty eviction container rebuilds
+    /// the eviction order and the states of the entries.
+    ///
+    /// # Safety
+    ///
+    /// The base handles associated to the `ptr`s in the eviction container must be kept valid.
+    unsafe fn snapshot(&self) -> Vec<(NonNull<Self::Handle>, Self::State)>;
+
+    /// Push a handle `ptr` into the eviction container with the state dumped by [`Eviction::snapshot`].
+    ///
+    /// # Safety
+    ///
+    /// The same as [`Eviction::push`].
+    unsafe fn restore(&mut self, ptr: NonNull<Self::Handle>, state: Self::State);
+
     /// Return the count of the `ptr`s that in the eviction container.
     fn len(&self) -> usize;
 
@@ -90,10 +122,12 @@ pub trait Eviction: Send + Sync + 'static {
     fn is_empty(&self) -> bool;
 }
 
+pub mod arc;
 pub mod fifo;
 pub mod lfu;
 pub mod lru;
 pub mod s3fifo;
+pub mod sieve;
 
 #[cfg(test)]
 pub mod test_utils;
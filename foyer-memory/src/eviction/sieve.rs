@@ -0,0 +1,314 @@
+//  Copyright 2024 Foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{fmt::Debug, ptr::NonNull};
+
+use foyer_intrusive::{
+    dlist::{Dlist, DlistLink},
+    intrusive_adapter,
+};
+
+use crate::{
+    eviction::Eviction,
+    handle::{BaseHandle, Handle},
+    CacheContext,
+};
+
+#[derive(Debug, Clone)]
+pub struct SieveContext;
+
+impl From<CacheContext> for SieveContext {
+    fn from(_: CacheContext) -> Self {
+        Self
+    }
+}
+
+impl From<SieveContext> for CacheContext {
+    fn from(_: SieveContext) -> Self {
+        CacheContext::Default
+    }
+}
+
+pub struct SieveHandle<T>
+where
+    T: Send + Sync + 'static,
+{
+    link: DlistLink,
+    base: BaseHandle<T, SieveContext>,
+    visited: bool,
+}
+
+impl<T> Debug for SieveHandle<T>
+where
+    T: Send + Sync + 'static,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SieveHandle").finish()
+    }
+}
+
+intrusive_adapter! { SieveHandleDlistAdapter<T> = NonNull<SieveHandle<T>>: SieveHandle<T> { link: DlistLink } where T: Send + Sync + 'static }
+
+impl<T> Handle for SieveHandle<T>
+where
+    T: Send + Sync + 'static,
+{
+    type Data = T;
+    type Context = SieveContext;
+
+    fn new() -> Self {
+        Self {
+            link: DlistLink::default(),
+            base: BaseHandle::new(),
+            visited: false,
+        }
+    }
+
+    fn init(&mut self, hash: u64, data: Self::Data, charge: usize, context: Self::Context) {
+        self.base.init(hash, data, charge, context);
+    }
+
+    fn base(&self) -> &BaseHandle<Self::Data, Self::Context> {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut BaseHandle<Self::Data, Self::Context> {
+        &mut self.base
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SieveConfig {}
+
+/// SIEVE eviction algorithm.
+///
+/// New entries are pushed to the back of the queue. The hand moves from the front (the oldest) to the back (the
+/// newest), clears the visited bit of the entries it passes, and evicts the first entry that is not visited. The hand
+/// wraps to the front after it reaches the back.
+///
+/// Reference: SIEVE is Simpler than LRU: an Efficient Turn-Key Eviction Algorithm for Web Caches (NSDI'24).
+pub struct Sieve<T>
+where
+    T: Send + Sync + 'static,
+{
+    queue: Dlist<SieveHandleDlistAdapter<T>>,
+
+    /// The link of the next entry to be checked, `None` means the front of the queue.
+    hand: Option<NonNull<DlistLink>>,
+}
+
+impl<T> Eviction for Sieve<T>
+where
+    T: Send + Sync + 'static,
+{
+    type Handle = SieveHandle<T>;
+    type Config = SieveConfig;
+    /// Whether the entry is visited.
+    type State = bool;
+
+    unsafe fn new(_capacity: usize, _config: &Self::Config) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            queue: Dlist::new(),
+            hand: None,
+        }
+    }
+
+    unsafe fn push(&mut self, mut ptr: NonNull<Self::Handle>) {
+        let handle = ptr.as_mut();
+        handle.visited = false;
+        self.queue.push_back(ptr);
+        handle.base_mut().set_in_eviction(true);
+    }
+
+    unsafe fn pop(&mut self) -> Option<NonNull<Self::Handle>> {
+        if self.queue.is_empty() {
+            return None;
+        }
+
+        let mut iter = match self.hand {
+            Some(link) => self.queue.iter_mut_from_raw(link),
+            None => self.queue.iter_mut(),
+        };
+        if !iter.is_valid() {
+            iter.front();
+        }
+
+        // The loop must end within 2 rounds, for all visited bits are cleared in the first round.
+        loop {
+            let handle = iter.get_mut().unwrap_unchecked();
+            if handle.visited {
+                handle.visited = false;
+                iter.next();
+                if !iter.is_valid() {
+                    iter.front();
+                }
+            } else {
+                let mut ptr = iter.remove().unwrap_unchecked();
+                self.hand = iter.get().map(|handle| handle.link.raw());
+                ptr.as_mut().base_mut().set_in_eviction(false);
+                return Some(ptr);
+            }
+        }
+    }
+
+    unsafe fn release(&mut self, _: NonNull<Self::Handle>) {}
+
+    unsafe fn acquire(&mut self, mut ptr: NonNull<Self::Handle>) {
+        ptr.as_mut().visited = true;
+    }
+
+    unsafe fn remove(&mut self, mut ptr: NonNull<Self::Handle>) {
+        let link = ptr.as_mut().link.raw();
+        if self.hand == Some(link) {
+            self.hand = self.queue.next_of_raw(link).map(|handle| handle.link.raw());
+        }
+        let p = self.queue.iter_mut_from_raw(link).remove().unwrap();
+        assert_eq!(p, ptr);
+        ptr.as_mut().base_mut().set_in_eviction(false);
+    }
+
+    unsafe fn clear(&mut self) -> Vec<NonNull<Self::Handle>> {
+        let mut res = Vec::with_capacity(self.len());
+        while let Some(mut ptr) = self.queue.pop_front() {
+            ptr.as_mut().base_mut().set_in_eviction(false);
+            res.push(ptr);
+        }
+        self.hand = None;
+        res
+    }
+
+    unsafe fn resize(&mut self, _: usize) {}
+
+    unsafe fn snapshot(&self) -> Vec<(NonNull<Self::Handle>, Self::State)> {
+        let mut res: Vec<_> = self
+            .queue
+            .iter()
+            .map(|handle| (NonNull::from(handle), handle.visited))
+            .collect();
+        // The eviction resumes from the hand, rotate it to the front.
+        if let Some(hand) = self
+            .hand
+            .and_then(|link| res.iter().position(|(ptr, _)| ptr.as_ref().link.raw() == link))
+        {
+            res.rotate_left(hand);
+        }
+        res
+    }
+
+    unsafe fn restore(&mut self, mut ptr: NonNull<Self::Handle>, visited: Self::State) {
+        self.push(ptr);
+        ptr.as_mut().visited = visited;
+    }
+
+    fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+unsafe impl<T> Send for Sieve<T> where T: Send + Sync + 'static {}
+unsafe impl<T> Sync for Sieve<T> where T: Send + Sync + 'static {}
+
+#[cfg(test)]
+pub mod tests {
+
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::eviction::test_utils::TestEviction;
+
+    impl<T> TestEviction for Sieve<T>
+    where
+        T: Send + Sync + 'static + Clone,
+    {
+        fn dump(&self) -> Vec<T> {
+            self.queue
+                .iter()
+                .map(|handle| handle.base().data_unwrap_unchecked().clone())
+                .collect_vec()
+        }
+    }
+
+    type TestSieveHandle = SieveHandle<u64>;
+    type TestSieve = Sieve<u64>;
+
+    unsafe fn new_test_sieve_handle_ptr(data: u64) -> NonNull<TestSieveHandle> {
+        let mut handle = Box::new(TestSieveHandle::new());
+        handle.init(0, data, 1, SieveContext);
+        NonNull::new_unchecked(Box::into_raw(handle))
+    }
+
+    unsafe fn del_test_sieve_handle_ptr(ptr: NonNull<TestSieveHandle>) {
+        let _ = Box::from_raw(ptr.as_ptr());
+    }
+
+    #[test]
+    fn test_sieve() {
+        unsafe {
+            let ptrs = (0..8).map(|i| new_test_sieve_handle_ptr(i)).collect_vec();
+
+            let mut sieve = TestSieve::new(100, &SieveConfig {});
+
+            // 0, 1, 2, 3
+            (0..4).for_each(|i| sieve.push(ptrs[i]));
+            assert_eq!(sieve.dump(), vec![0, 1, 2, 3]);
+
+            // The hand skips visited 0 and 1, and clears their visited bits.
+            sieve.acquire(ptrs[0]);
+            sieve.acquire(ptrs[1]);
+            assert_eq!(sieve.pop(), Some(ptrs[2]));
+            assert_eq!(sieve.dump(), vec![0, 1, 3]);
+
+            // 0, 1, 3, 4, 5, hand on 3
+            sieve.push(ptrs[4]);
+            sieve.push(ptrs[5]);
+            sieve.acquire(ptrs[3]);
+            assert_eq!(sieve.pop(), Some(ptrs[4]));
+            assert_eq!(sieve.dump(), vec![0, 1, 3, 5]);
+
+            // Removing the entry under the hand moves the hand to the next one.
+            sieve.remove(ptrs[5]);
+            assert_eq!(sieve.dump(), vec![0, 1, 3]);
+
+            // The hand wraps to the front.
+            assert_eq!(sieve.pop(), Some(ptrs[0]));
+            assert_eq!(sieve.pop(), Some(ptrs[1]));
+            assert_eq!(sieve.dump(), vec![3]);
+
+            // 3 is visited again, so the later pushed 6 and 7 are evicted first.
+            sieve.acquire(ptrs[3]);
+            sieve.push(ptrs[6]);
+            sieve.push(ptrs[7]);
+            assert_eq!(sieve.pop(), Some(ptrs[6]));
+            assert_eq!(sieve.pop(), Some(ptrs[7]));
+            assert_eq!(sieve.pop(), Some(ptrs[3]));
+            assert_eq!(sieve.pop(), None);
+
+            sieve.push(ptrs[0]);
+            sieve.push(ptrs[1]);
+            assert_eq!(sieve.clear(), vec![ptrs[0], ptrs[1]]);
+            assert!(sieve.is_empty());
+
+            for ptr in ptrs {
+                del_test_sieve_handle_ptr(ptr);
+            }
+        }
+    }
+}
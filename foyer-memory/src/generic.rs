@@ -14,6 +14,7 @@
 
 use std::{
     borrow::Borrow,
+    collections::{BTreeSet, VecDeque},
     future::Future,
     hash::BuildHasher,
     hash::Hash,
@@ -23,6 +24,7 @@ use std::{
         atomic::{AtomicUsize, Ordering},
         Arc,
     },
+    time::Duration,
 };
 
 use ahash::RandomState;
@@ -33,23 +35,70 @@ use foyer_common::{
 use futures::FutureExt;
 use hashbrown::hash_map::{Entry as HashMapEntry, HashMap};
 use itertools::Itertools;
-use parking_lot::Mutex;
-use tokio::{sync::oneshot, task::JoinHandle};
+use parking_lot::{RwLock, RwLockWriteGuard};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::{sync::oneshot, task::JoinHandle, time::Instant};
 
 use crate::{
-    eviction::Eviction,
+    eviction::{
+        lfu::{Lfu, LfuHandle, LfuQueueSizes},
+        Eviction,
+    },
     handle::{Handle, KeyedHandle},
     indexer::Indexer,
-    listener::CacheEventListener,
-    metrics::Metrics,
+    listener::{CacheEventListener, RemovalCause},
+    metrics::{Metrics, METRICS},
+    read_buffer::ReadBuffer,
+    snapshot::{SnapshotEntry, SnapshotReader, SnapshotResult, SnapshotWriter},
     CacheContext,
 };
 
+/// Calculates the charge of an entry inserted without an explicit charge.
+pub type Weigher<K, V> = Arc<dyn Fn(&K, &V) -> usize + Send + Sync + 'static>;
+
+/// The key, value, context and charge of a released entry, and the cause of its removal.
+type ReleasedEntry<K, V, C> = (K, V, C, usize, RemovalCause);
+
 struct CacheSharedState<T, L> {
     metrics: Metrics,
     /// The object pool to avoid frequent handle allocating, shared by all shards.
     object_pool: ObjectPool<Box<T>>,
     listener: L,
+    refresh_policy: Option<RefreshPolicy>,
+    negative_cache: Option<NegativeCacheConfig>,
+}
+
+/// A cached failure of the fetch with the `entry` interface.
+struct NegativeEntry {
+    negative: Negative,
+    expire_at: Instant,
+    charge: usize,
+}
+
+/// Converts the errors of the `entry` interface to and from the cached failures.
+struct NegativeCodec<ER> {
+    encode: fn(&ER) -> Negative,
+    decode: fn(Negative) -> ER,
+}
+
+impl<ER> NegativeCodec<ER>
+where
+    ER: std::error::Error + From<Negative> + Clone + Send + Sync + 'static,
+{
+    fn new() -> Self {
+        Self {
+            encode: |error| Negative::Error(Arc::new(error.clone())),
+            // The cached error is returned as is to the fetches with the same error type, and converted otherwise.
+            decode: |negative| {
+                if let Negative::Error(error) = &negative {
+                    if let Some(error) = error.downcast_ref::<ER>() {
+                        return error.clone();
+                    }
+                }
+                ER::from(negative)
+            },
+        }
+    }
 }
 
 // TODO(MrCroxx): use `expect` after `lint_reasons` is stable.
@@ -67,11 +116,24 @@ where
     indexer: I,
     eviction: E,
 
+    /// index of the shard, used to label the shard metrics
+    id: usize,
+
     capacity: usize,
     usage: Arc<AtomicUsize>,
 
     waiters: HashMap<K, Vec<oneshot::Sender<GenericCacheEntry<K, V, E, I, L, S>>>>,
 
+    /// Handles with ttl in the indexer, ordered by their expire instants.
+    expirations: BTreeSet<(Instant, NonNull<E::Handle>)>,
+
+    /// Cached failures of the fetches with the `entry` interface.
+    negatives: HashMap<K, NegativeEntry>,
+    /// Keys of the cached failures, ordered by their expire instants. Keys that are removed or replaced are skipped.
+    negative_queue: VecDeque<(Instant, K)>,
+    /// Total charge of the cached failures.
+    negative_usage: usize,
+
     state: Arc<CacheSharedState<E::Handle, L>>,
 }
 
@@ -86,6 +148,7 @@ where
     S: BuildHasher + Send + Sync + 'static,
 {
     fn new(
+        id: usize,
         capacity: usize,
         eviction_config: &E::Config,
         usage: Arc<AtomicUsize>,
@@ -94,17 +157,27 @@ where
         let indexer = I::new();
         let eviction = unsafe { E::new(capacity, eviction_config) };
         let waiters = HashMap::default();
+        let expirations = BTreeSet::default();
         Self {
             indexer,
             eviction,
+            id,
             capacity,
             usage,
             waiters,
+            expirations,
+            negatives: HashMap::default(),
+            negative_queue: VecDeque::default(),
+            negative_usage: 0,
             state: context,
         }
     }
 
     /// Insert a new entry into the cache. The handle for the new entry is returned.
+    ///
+    /// The entry is pushed into the eviction container, or restored with the given state if any.
+    // TODO(MrCroxx): use `expect` after `lint_reasons` is stable.
+    #[allow(clippy::too_many_arguments)]
     unsafe fn insert(
         &mut self,
         hash: u64,
@@ -112,19 +185,34 @@ where
         value: V,
         charge: usize,
         context: <E::Handle as Handle>::Context,
-        last_reference_entries: &mut Vec<(K, V, <E::Handle as Handle>::Context, usize)>,
+        ttl: Option<Duration>,
+        state: Option<E::State>,
+        last_reference_entries: &mut Vec<ReleasedEntry<K, V, <E::Handle as Handle>::Context>>,
     ) -> NonNull<E::Handle> {
+        // The inserted entry supersedes the cached failure.
+        if !self.negatives.is_empty() {
+            self.remove_negative(&key);
+        }
+
         let mut handle = self.state.object_pool.acquire();
         handle.init(hash, (key, value), charge, context);
+        handle.base_mut().set_ttl(ttl);
+        handle.base_mut().set_refresh_at(
+            self.state
+                .refresh_policy
+                .map(|policy| Instant::now() + policy.duration()),
+        );
         let mut ptr = unsafe { NonNull::new_unchecked(Box::into_raw(handle)) };
 
         self.evict(charge, last_reference_entries);
 
         debug_assert!(!ptr.as_ref().base().is_in_indexer());
-        if let Some(old) = self.indexer.insert(ptr) {
+        if let Some(mut old) = self.indexer.insert(ptr) {
             self.state.metrics.replace.fetch_add(1, Ordering::Relaxed);
+            old.as_mut().base_mut().set_removal_cause(RemovalCause::Replaced);
 
             debug_assert!(!old.as_ref().base().is_in_indexer());
+            self.remove_expiration(old);
             if old.as_ref().base().is_in_eviction() {
                 self.eviction.remove(old);
             }
@@ -136,23 +224,55 @@ where
         } else {
             self.state.metrics.insert.fetch_add(1, Ordering::Relaxed);
         }
-        self.eviction.push(ptr);
+        match state {
+            Some(state) => self.eviction.restore(ptr, state),
+            None => self.eviction.push(ptr),
+        }
+        if let Some(expire_at) = ptr.as_ref().base().expire_at() {
+            self.expirations.insert((expire_at, ptr));
+        }
 
         debug_assert!(ptr.as_ref().base().is_in_indexer());
         debug_assert!(ptr.as_ref().base().is_in_indexer());
 
         self.usage.fetch_add(charge, Ordering::Relaxed);
+        self.state.metrics.update_usage(self.id, charge as i64);
         ptr.as_mut().base_mut().inc_refs();
 
         ptr
     }
 
-    unsafe fn get<Q>(&mut self, hash: u64, key: &Q) -> Option<NonNull<E::Handle>>
+    /// Get the handle of the key if exists and not expired.
+    ///
+    /// The expired handle is treated as a miss and removed from the cache.
+    unsafe fn get<Q>(
+        &mut self,
+        hash: u64,
+        key: &Q,
+        last_reference_entries: &mut Vec<ReleasedEntry<K, V, <E::Handle as Handle>::Context>>,
+    ) -> Option<NonNull<E::Handle>>
     where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
     {
-        let mut ptr = match self.indexer.get(hash, key) {
+        self.get_with_refresh(hash, key, last_reference_entries)
+            .map(|(ptr, _)| ptr)
+    }
+
+    /// Get the handle like [`Self::get`], and whether a hit with the `entry` interface should refresh it.
+    ///
+    /// The staleness is checked before the access, which postpones the refresh with the refresh-after-access policy.
+    unsafe fn get_with_refresh<Q>(
+        &mut self,
+        hash: u64,
+        key: &Q,
+        last_reference_entries: &mut Vec<ReleasedEntry<K, V, <E::Handle as Handle>::Context>>,
+    ) -> Option<(NonNull<E::Handle>, bool)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let mut ptr = match self.lookup(hash, key, last_reference_entries) {
             Some(ptr) => {
                 self.state.metrics.hit.fetch_add(1, Ordering::Relaxed);
                 ptr
@@ -165,28 +285,48 @@ where
         let base = ptr.as_mut().base_mut();
         debug_assert!(base.is_in_indexer());
 
+        let refresh = base.should_refresh();
         base.inc_refs();
-        self.eviction.acquire(ptr);
+        self.access(ptr);
 
-        Some(ptr)
+        Some((ptr, refresh))
     }
 
-    unsafe fn contains<Q>(&mut self, hash: u64, key: &Q) -> bool
+    /// Record the access of the handle in the eviction container, and postpone the refresh of the handle with the
+    /// refresh-after-access policy.
+    unsafe fn access(&mut self, mut ptr: NonNull<E::Handle>) {
+        self.eviction.acquire(ptr);
+        if let Some(RefreshPolicy::AfterAccess(duration)) = self.state.refresh_policy {
+            ptr.as_mut().base_mut().set_refresh_at(Some(Instant::now() + duration));
+        }
+    }
+
+    unsafe fn contains<Q>(
+        &mut self,
+        hash: u64,
+        key: &Q,
+        last_reference_entries: &mut Vec<ReleasedEntry<K, V, <E::Handle as Handle>::Context>>,
+    ) -> bool
     where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
     {
-        self.indexer.get(hash, key).is_some()
+        self.lookup(hash, key, last_reference_entries).is_some()
     }
 
-    unsafe fn touch<Q>(&mut self, hash: u64, key: &Q) -> bool
+    unsafe fn touch<Q>(
+        &mut self,
+        hash: u64,
+        key: &Q,
+        last_reference_entries: &mut Vec<ReleasedEntry<K, V, <E::Handle as Handle>::Context>>,
+    ) -> bool
     where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
     {
-        let res = self.indexer.get(hash, key);
+        let res = self.lookup(hash, key, last_reference_entries);
         if let Some(ptr) = res {
-            self.eviction.acquire(ptr);
+            self.access(ptr);
         }
         res.is_some()
     }
@@ -199,8 +339,14 @@ where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
     {
+        if !self.negatives.is_empty() {
+            self.remove_negative(key);
+        }
+
         let mut ptr = self.indexer.remove(hash, key)?;
+        self.remove_expiration(ptr);
         let handle = ptr.as_mut();
+        handle.base_mut().set_removal_cause(RemovalCause::Removed);
 
         self.state.metrics.remove.fetch_add(1, Ordering::Relaxed);
 
@@ -216,24 +362,46 @@ where
         Some(ptr)
     }
 
+    /// Update the capacity of the shard and evict entries until the usage fits.
+    unsafe fn resize(
+        &mut self,
+        capacity: usize,
+        last_reference_entries: &mut Vec<ReleasedEntry<K, V, <E::Handle as Handle>::Context>>,
+    ) {
+        self.capacity = capacity;
+        self.eviction.resize(capacity);
+        self.evict(0, last_reference_entries);
+    }
+
     /// Remove a key based on the eviction algorithm if exists.s
     unsafe fn pop(&mut self) -> Option<NonNull<E::Handle>> {
-        let ptr = self.eviction.pop()?;
+        let mut ptr = self.eviction.pop()?;
 
         let handle = ptr.as_ref();
 
         // If the `ptr` is in the eviction container, it must be the latest version of the key and in the indexer.
         let p = self.remove(handle.base().hash(), handle.key()).unwrap();
         debug_assert_eq!(ptr, p);
+        ptr.as_mut().base_mut().set_removal_cause(RemovalCause::Evicted);
 
         Some(ptr)
     }
 
     /// Clear all cache entries.
-    unsafe fn clear(&mut self, last_reference_entries: &mut Vec<(K, V, <E::Handle as Handle>::Context, usize)>) {
+    unsafe fn clear(&mut self, last_reference_entries: &mut Vec<ReleasedEntry<K, V, <E::Handle as Handle>::Context>>) {
         // TODO(MrCroxx): Avoid collecting here?
         let ptrs = self.indexer.drain().collect_vec();
         let eptrs = self.eviction.clear();
+        self.expirations.clear();
+        let negatives = self
+            .negatives
+            .drain()
+            .map(|(_, negative)| negative.charge)
+            .sum::<usize>();
+        self.negative_queue.clear();
+        self.negative_usage = 0;
+        self.usage.fetch_sub(negatives, Ordering::Relaxed);
+        self.state.metrics.update_usage(self.id, -(negatives as i64));
 
         // Assert that the handles in the indexer covers the handles in the eviction container.
         if cfg!(debug_assertions) {
@@ -247,23 +415,72 @@ where
 
         // The handles in the indexer covers the handles in the eviction container.
         // So only the handles drained from the indexer need to be released.
-        for ptr in ptrs {
+        for mut ptr in ptrs {
             debug_assert!(!ptr.as_ref().base().is_in_indexer());
+            ptr.as_mut().base_mut().set_removal_cause(RemovalCause::Cleared);
             if let Some(entry) = self.try_release_handle(ptr, false) {
                 last_reference_entries.push(entry);
             }
         }
     }
 
+    /// Remove the entries that the predicate returns `false` for, return the count of the removed entries.
+    unsafe fn retain(
+        &mut self,
+        f: &mut impl FnMut(&K, &V) -> bool,
+        last_reference_entries: &mut Vec<ReleasedEntry<K, V, <E::Handle as Handle>::Context>>,
+    ) -> usize {
+        let ptrs = self
+            .indexer
+            .iter()
+            .filter(|ptr| {
+                let (key, value) = ptr.as_ref().base().data_unwrap_unchecked();
+                !f(key, value)
+            })
+            .collect_vec();
+
+        self.state.metrics.remove.fetch_add(ptrs.len(), Ordering::Relaxed);
+
+        for mut ptr in ptrs.iter().copied() {
+            ptr.as_mut().base_mut().set_removal_cause(RemovalCause::Removed);
+            let handle = ptr.as_ref();
+
+            self.indexer.remove(handle.base().hash(), handle.key());
+            self.remove_expiration(ptr);
+            if handle.base().is_in_eviction() {
+                self.eviction.remove(ptr);
+            }
+
+            if let Some(entry) = self.try_release_handle(ptr, false) {
+                last_reference_entries.push(entry);
+            }
+        }
+
+        ptrs.len()
+    }
+
     unsafe fn evict(
         &mut self,
         charge: usize,
-        last_reference_entries: &mut Vec<(K, V, <E::Handle as Handle>::Context, usize)>,
+        last_reference_entries: &mut Vec<ReleasedEntry<K, V, <E::Handle as Handle>::Context>>,
     ) {
+        let mut now = None;
         // TODO(MrCroxx): Use `let_chains` here after it is stable.
         while self.usage.load(Ordering::Relaxed) + charge > self.capacity {
+            // Expired entries are always evicted before the ones chosen by the eviction algorithm.
+            if let Some(&(expire_at, ptr)) = self.expirations.first() {
+                if expire_at <= *now.get_or_insert_with(Instant::now) {
+                    self.expire(ptr, last_reference_entries);
+                    continue;
+                }
+            }
+            if self.pop_negative(*now.get_or_insert_with(Instant::now), false) {
+                continue;
+            }
             let evicted = match self.eviction.pop() {
                 Some(evicted) => evicted,
+                // Cached failures are dropped only if there is no other entry to evict.
+                None if self.pop_negative(*now.get_or_insert_with(Instant::now), true) => continue,
                 None => break,
             };
             self.state.metrics.evict.fetch_add(1, Ordering::Relaxed);
@@ -281,22 +498,178 @@ where
     /// Return `Some(..)` if the handle is released, or `None` if the handle is still in use.
     unsafe fn try_release_external_handle(
         &mut self,
-        mut ptr: NonNull<E::Handle>,
-    ) -> Option<(K, V, <E::Handle as Handle>::Context, usize)> {
-        ptr.as_mut().base_mut().dec_refs();
+        ptr: NonNull<E::Handle>,
+    ) -> Option<ReleasedEntry<K, V, <E::Handle as Handle>::Context>> {
+        ptr.as_ref().base().dec_refs();
         self.try_release_handle(ptr, true)
     }
 
+    /// Apply the accesses recorded by the read buffer in batch, and release the references they carry.
+    unsafe fn drain_read_buffer(
+        &mut self,
+        buffer: &ReadBuffer<E::Handle>,
+        last_reference_entries: &mut Vec<ReleasedEntry<K, V, <E::Handle as Handle>::Context>>,
+    ) {
+        buffer.drain(|ptr| {
+            self.state.metrics.read_buffer_drain.fetch_add(1, Ordering::Relaxed);
+            // The entry may have been removed or updated since it was read.
+            if ptr.as_ref().base().is_in_indexer() {
+                self.access(ptr);
+            }
+            if let Some(entry) = self.try_release_external_handle(ptr) {
+                last_reference_entries.push(entry);
+            }
+        });
+    }
+
+    /// Get the handle of the key without updating the eviction container.
+    ///
+    /// The expired handle is removed from the cache and `None` is returned.
+    unsafe fn lookup<Q>(
+        &mut self,
+        hash: u64,
+        key: &Q,
+        last_reference_entries: &mut Vec<ReleasedEntry<K, V, <E::Handle as Handle>::Context>>,
+    ) -> Option<NonNull<E::Handle>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let ptr = self.indexer.get(hash, key)?;
+        if ptr.as_ref().base().is_expired() {
+            self.expire(ptr, last_reference_entries);
+            return None;
+        }
+        Some(ptr)
+    }
+
+    /// Remove an expired handle from the indexer and the eviction container, and release it if possible.
+    unsafe fn expire(
+        &mut self,
+        mut ptr: NonNull<E::Handle>,
+        last_reference_entries: &mut Vec<ReleasedEntry<K, V, <E::Handle as Handle>::Context>>,
+    ) {
+        ptr.as_mut().base_mut().set_removal_cause(RemovalCause::Expired);
+        let handle = ptr.as_ref();
+        debug_assert!(handle.base().is_in_indexer());
+
+        self.state.metrics.expire.fetch_add(1, Ordering::Relaxed);
+
+        self.indexer.remove(handle.base().hash(), handle.key());
+        self.remove_expiration(ptr);
+        if handle.base().is_in_eviction() {
+            self.eviction.remove(ptr);
+        }
+
+        if let Some(entry) = self.try_release_handle(ptr, false) {
+            last_reference_entries.push(entry);
+        }
+    }
+
+    /// Cache the failure of the fetch of the key, which expires after the ttl of the negative cache config.
+    unsafe fn insert_negative(
+        &mut self,
+        key: K,
+        negative: Negative,
+        config: &NegativeCacheConfig,
+        last_reference_entries: &mut Vec<ReleasedEntry<K, V, <E::Handle as Handle>::Context>>,
+    ) where
+        K: Clone,
+    {
+        let now = Instant::now();
+        while self.pop_negative(now, false) {}
+        self.remove_negative(&key);
+
+        // Replace the oldest cached failures instead of evicting the entries, so a burst of failures can take no more
+        // than its share of the capacity.
+        let limit = (self.capacity as f64 * config.capacity_ratio) as usize;
+        while self.negative_usage + config.charge > limit && self.pop_negative(now, true) {}
+        if config.charge > limit {
+            return;
+        }
+
+        self.evict(config.charge, last_reference_entries);
+
+        let expire_at = now + config.ttl;
+        self.negative_queue.push_back((expire_at, key.clone()));
+        self.negatives.insert(
+            key,
+            NegativeEntry {
+                negative,
+                expire_at,
+                charge: config.charge,
+            },
+        );
+        self.negative_usage += config.charge;
+        self.usage.fetch_add(config.charge, Ordering::Relaxed);
+        self.state.metrics.update_usage(self.id, config.charge as i64);
+    }
+
+    /// Get the cached failure of the key if exists and not expired.
+    fn get_negative<Q>(&mut self, key: &Q) -> Option<&NegativeEntry>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.negatives.get(key)?.expire_at <= Instant::now() {
+            self.remove_negative(key);
+            return None;
+        }
+        self.negatives.get(key)
+    }
+
+    /// Remove the cached failure of the key, return `true` if it exists.
+    fn remove_negative<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.negatives.remove(key) {
+            Some(negative) => {
+                self.negative_usage -= negative.charge;
+                self.usage.fetch_sub(negative.charge, Ordering::Relaxed);
+                self.state.metrics.update_usage(self.id, -(negative.charge as i64));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove the oldest cached failure if it is expired, or regardless of that if `force` is `true`.
+    ///
+    /// Return `true` if a cached failure is removed.
+    fn pop_negative(&mut self, now: Instant, force: bool) -> bool {
+        while let Some((expire_at, key)) = self.negative_queue.front() {
+            // Skip the keys whose cached failures are removed or replaced.
+            if !matches!(self.negatives.get(key), Some(negative) if negative.expire_at == *expire_at) {
+                self.negative_queue.pop_front();
+                continue;
+            }
+            if !force && *expire_at > now {
+                return false;
+            }
+            let (_, key) = self.negative_queue.pop_front().unwrap();
+            return self.remove_negative(&key);
+        }
+        false
+    }
+
+    unsafe fn remove_expiration(&mut self, ptr: NonNull<E::Handle>) {
+        if let Some(expire_at) = ptr.as_ref().base().expire_at() {
+            self.expirations.remove(&(expire_at, ptr));
+        }
+    }
+
     /// Try release handle if there is no external reference and no reinsertion is needed.
     ///
-    /// Return the entry if the handle is released.
+    /// Return the entry and the cause of its removal if the handle is released.
     ///
     /// Recycle it if possible.
     unsafe fn try_release_handle(
         &mut self,
         mut ptr: NonNull<E::Handle>,
         reinsert: bool,
-    ) -> Option<(K, V, <E::Handle as Handle>::Context, usize)> {
+    ) -> Option<ReleasedEntry<K, V, <E::Handle as Handle>::Context>> {
         let handle = ptr.as_mut();
 
         if handle.base().has_refs() {
@@ -306,13 +679,17 @@ where
         debug_assert!(handle.base().is_inited());
         debug_assert!(!handle.base().has_refs());
 
+        let expired = handle.base().is_expired();
+
         // If the entry is not updated or removed from the cache, try to reinsert it or remove it from the indexer and
         // the eviction container.
         if handle.base().is_in_indexer() {
             // The usage is higher than the capacity means most handles are held externally,
             // the cache shard cannot release enough charges for the new inserted entries.
             // In this case, the reinsertion should be given up.
-            if reinsert && self.usage.load(Ordering::Relaxed) <= self.capacity {
+            //
+            // Expired entries are never reinserted.
+            if reinsert && !expired && self.usage.load(Ordering::Relaxed) <= self.capacity {
                 let was_in_eviction = handle.base().is_in_eviction();
                 self.eviction.release(ptr);
                 if ptr.as_ref().base().is_in_eviction() {
@@ -325,6 +702,7 @@ where
 
             // If the entry has not been reinserted, remove it from the indexer and the eviction container (if needed).
             self.indexer.remove(handle.base().hash(), handle.key());
+            self.remove_expiration(ptr);
             if ptr.as_ref().base().is_in_eviction() {
                 self.eviction.remove(ptr);
             }
@@ -338,15 +716,48 @@ where
         self.state.metrics.release.fetch_add(1, Ordering::Relaxed);
 
         self.usage.fetch_sub(handle.base().charge(), Ordering::Relaxed);
+        self.state
+            .metrics
+            .update_usage(self.id, -(handle.base().charge() as i64));
+        // The entries that are not removed explicitly leave the cache by expiration or eviction on release.
+        let cause = handle.base().removal_cause().unwrap_or(if expired {
+            RemovalCause::Expired
+        } else {
+            RemovalCause::Evicted
+        });
         let ((key, value), context, charge) = handle.base_mut().take();
 
         let handle = Box::from_raw(ptr.as_ptr());
         self.state.object_pool.release(handle);
 
-        Some((key, value, context, charge))
+        Some((key, value, context, charge, cause))
     }
 }
 
+unsafe impl<K, V, E, I, L, S> Send for CacheShard<K, V, E, I, L, S>
+where
+    K: Key,
+    V: Value,
+    E: Eviction,
+    E::Handle: KeyedHandle<Key = K, Data = (K, V)>,
+    I: Indexer<Key = K, Handle = E::Handle>,
+    L: CacheEventListener<K, V>,
+    S: BuildHasher + Send + Sync + 'static,
+{
+}
+
+unsafe impl<K, V, E, I, L, S> Sync for CacheShard<K, V, E, I, L, S>
+where
+    K: Key,
+    V: Value,
+    E: Eviction,
+    E::Handle: KeyedHandle<Key = K, Data = (K, V)>,
+    I: Indexer<Key = K, Handle = E::Handle>,
+    L: CacheEventListener<K, V>,
+    S: BuildHasher + Send + Sync + 'static,
+{
+}
+
 impl<K, V, E, I, L, S> Drop for CacheShard<K, V, E, I, L, S>
 where
     K: Key,
@@ -371,12 +782,75 @@ where
     L: CacheEventListener<K, V>,
     S: BuildHasher + Send + Sync + 'static,
 {
+    /// Metrics of this cache instance has label `foyer = {{ name }}`.
+    pub name: String,
     pub capacity: usize,
     pub shards: usize,
     pub eviction_config: E::Config,
     pub object_pool_capacity: usize,
     pub hash_builder: S,
     pub event_listener: L,
+    /// The default time-to-live of the inserted entries, `None` means never expire.
+    pub ttl: Option<Duration>,
+    /// The capacity of the per-shard read buffers, `None` means the read buffers are disabled.
+    ///
+    /// With the read buffers enabled, cache hits only take the shard lock in shared mode, and the accesses are
+    /// recorded in the read buffers and applied to the eviction container in batch. Accesses are dropped when the
+    /// read buffer is full.
+    pub read_buffer_capacity: Option<usize>,
+    /// The weigher that calculates the charge of the entries inserted without an explicit charge.
+    pub weigher: Weigher<K, V>,
+    /// The policy of refreshing the entries with the `entry` interface, `None` means the entries are never refreshed.
+    pub refresh_policy: Option<RefreshPolicy>,
+    /// The config of caching the failures of the fetches with the `entry` interface, `None` means the failures are
+    /// not cached.
+    pub negative_cache: Option<NegativeCacheConfig>,
+}
+
+/// The policy of refreshing the entries with the `entry` interface.
+///
+/// A hit of a stale entry with the `entry` interface returns the current value immediately, and reloads the entry in
+/// background.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshPolicy {
+    /// Refresh the entries after the duration since they are written.
+    AfterWrite(Duration),
+    /// Refresh the entries after the duration since they are last accessed or written.
+    ///
+    /// With the read buffers enabled, the accesses recorded in the read buffers postpone the refresh only after they
+    /// are applied.
+    AfterAccess(Duration),
+}
+
+impl RefreshPolicy {
+    fn duration(&self) -> Duration {
+        match self {
+            RefreshPolicy::AfterWrite(duration) | RefreshPolicy::AfterAccess(duration) => *duration,
+        }
+    }
+}
+
+/// A failure of the fetch with the `entry` interface, which is kept by the negative cache.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum Negative {
+    /// The fetch found nothing for the key.
+    #[error("not found")]
+    NotFound,
+    /// The fetch failed with the error.
+    #[error("fetch error: {0}")]
+    Error(Arc<dyn std::error::Error + Send + Sync + 'static>),
+}
+
+/// The config of caching the failures of the fetches with the `entry` interface.
+#[derive(Debug, Clone)]
+pub struct NegativeCacheConfig {
+    /// the duration for which a failure is cached
+    pub ttl: Duration,
+    /// the charge of a cached failure, which counts in the usage of the cache
+    pub charge: usize,
+    /// the max ratio of the capacity that the cached failures take, the oldest cached failure is dropped to make room
+    /// for a new one if exceeded
+    pub capacity_ratio: f64,
 }
 
 // TODO(MrCroxx): use `expect` after `lint_reasons` is stable.
@@ -396,6 +870,7 @@ where
     Hit(GenericCacheEntry<K, V, E, I, L, S>),
     Wait(oneshot::Receiver<GenericCacheEntry<K, V, E, I, L, S>>),
     Miss(JoinHandle<std::result::Result<GenericCacheEntry<K, V, E, I, L, S>, ER>>),
+    Negative(Box<ER>),
 }
 
 impl<K, V, E, I, L, S, ER> Default for GenericEntry<K, V, E, I, L, S, ER>
@@ -434,6 +909,10 @@ where
                 GenericEntry::Hit(entry) => entry,
                 _ => unreachable!(),
             })),
+            Self::Negative(_) => std::task::Poll::Ready(Err(match std::mem::take(&mut *self) {
+                GenericEntry::Negative(error) => *error,
+                _ => unreachable!(),
+            })),
             Self::Wait(waiter) => waiter.poll_unpin(cx).map_err(|err| err.into()),
             Self::Miss(join_handle) => join_handle.poll_unpin(cx).map(|join_result| join_result.unwrap()),
         }
@@ -452,14 +931,18 @@ where
     L: CacheEventListener<K, V>,
     S: BuildHasher + Send + Sync + 'static,
 {
-    shards: Vec<Mutex<CacheShard<K, V, E, I, L, S>>>,
+    shards: Vec<RwLock<CacheShard<K, V, E, I, L, S>>>,
+    read_buffers: Option<Vec<ReadBuffer<E::Handle>>>,
 
-    capacity: usize,
+    capacity: AtomicUsize,
     usages: Vec<Arc<AtomicUsize>>,
 
     context: Arc<CacheSharedState<E::Handle, L>>,
 
     hash_builder: S,
+
+    ttl: Option<Duration>,
+    weigher: Weigher<K, V>,
 }
 
 impl<K, V, E, I, L, S> GenericCache<K, V, E, I, L, S>
@@ -475,31 +958,61 @@ where
     pub fn new(config: GenericCacheConfig<K, V, E, L, S>) -> Self {
         let usages = (0..config.shards).map(|_| Arc::new(AtomicUsize::new(0))).collect_vec();
         let context = Arc::new(CacheSharedState {
-            metrics: Metrics::default(),
+            metrics: METRICS.foyer(&config.name, config.shards),
             object_pool: ObjectPool::new_with_create(config.object_pool_capacity, || {
                 Box::new(<E::Handle as Handle>::new())
             }),
             listener: config.event_listener,
+            refresh_policy: config.refresh_policy,
+            negative_cache: config.negative_cache,
         });
 
         let shard_capacity = config.capacity / config.shards;
 
+        context.metrics.capacity.add(config.capacity as i64);
+
         let shards = usages
             .iter()
-            .map(|usage| CacheShard::new(shard_capacity, &config.eviction_config, usage.clone(), context.clone()))
-            .map(Mutex::new)
+            .enumerate()
+            .map(|(id, usage)| {
+                CacheShard::new(
+                    id,
+                    shard_capacity,
+                    &config.eviction_config,
+                    usage.clone(),
+                    context.clone(),
+                )
+            })
+            .map(RwLock::new)
             .collect_vec();
+        let read_buffers = config
+            .read_buffer_capacity
+            .map(|capacity| (0..config.shards).map(|_| ReadBuffer::new(capacity)).collect_vec());
 
         Self {
             shards,
-            capacity: config.capacity,
+            read_buffers,
+            capacity: AtomicUsize::new(config.capacity),
             usages,
             context,
             hash_builder: config.hash_builder,
+            ttl: config.ttl,
+            weigher: config.weigher,
         }
     }
 
-    pub fn insert(self: &Arc<Self>, key: K, value: V, charge: usize) -> GenericCacheEntry<K, V, E, I, L, S> {
+    /// Insert an entry with the charge calculated by the weigher.
+    pub fn insert(self: &Arc<Self>, key: K, value: V) -> GenericCacheEntry<K, V, E, I, L, S> {
+        let charge = (self.weigher)(&key, &value);
+        self.insert_with_charge(key, value, charge)
+    }
+
+    pub fn insert_with_charge(
+        self: &Arc<Self>,
+        key: K,
+        value: V,
+        charge: usize,
+    ) -> GenericCacheEntry<K, V, E, I, L, S> {
         self.insert_with_context(key, value, charge, CacheContext::default())
     }
 
@@ -509,15 +1022,52 @@ where
         value: V,
         charge: usize,
         context: CacheContext,
+    ) -> GenericCacheEntry<K, V, E, I, L, S> {
+        self.insert_inner(key, value, charge, context, self.ttl, false)
+    }
+
+    /// Insert an entry that expires after `ttl`, regardless of the default ttl of the cache.
+    pub fn insert_with_ttl(
+        self: &Arc<Self>,
+        key: K,
+        value: V,
+        charge: usize,
+        ttl: Duration,
+    ) -> GenericCacheEntry<K, V, E, I, L, S> {
+        self.insert_inner(key, value, charge, CacheContext::default(), Some(ttl), false)
+    }
+
+    fn insert_inner(
+        self: &Arc<Self>,
+        key: K,
+        value: V,
+        charge: usize,
+        context: CacheContext,
+        ttl: Option<Duration>,
+        fetched: bool,
     ) -> GenericCacheEntry<K, V, E, I, L, S> {
         let hash = self.hash_builder.hash_one(&key);
 
         let mut to_deallocate = vec![];
 
         let (entry, waiters) = unsafe {
-            let mut shard = self.shards[hash as usize % self.shards.len()].lock();
+            let mut shard = self.write_shard(hash as usize % self.shards.len(), &mut to_deallocate);
             let waiters = shard.waiters.remove(&key);
-            let mut ptr = shard.insert(hash, key, value, charge, context.into(), &mut to_deallocate);
+            // The waiters of the fetch are taken by the entry inserted since the fetch started, which supersedes the
+            // fetched one.
+            if fetched && waiters.is_none() {
+                if let Some(ptr) = shard.get(hash, &key, &mut to_deallocate) {
+                    drop(shard);
+                    for entry in to_deallocate {
+                        self.notify_release(entry);
+                    }
+                    return GenericCacheEntry {
+                        cache: self.clone(),
+                        ptr,
+                    };
+                }
+            }
+            let mut ptr = shard.insert(hash, key, value, charge, context.into(), ttl, None, &mut to_deallocate);
             if let Some(waiters) = waiters.as_ref() {
                 ptr.as_mut().base_mut().inc_refs_by(waiters.len());
             }
@@ -528,6 +1078,10 @@ where
             (entry, waiters)
         };
 
+        self.context
+            .listener
+            .on_insert(entry.key(), entry.value(), context, entry.charge());
+
         if let Some(waiters) = waiters {
             for waiter in waiters {
                 let _ = waiter.send(GenericCacheEntry {
@@ -538,8 +1092,8 @@ where
         }
 
         // Do not deallocate data within the lock section.
-        for (key, value, context, charges) in to_deallocate {
-            self.context.listener.on_release(key, value, context.into(), charges)
+        for entry in to_deallocate {
+            self.notify_release(entry);
         }
 
         entry
@@ -553,7 +1107,7 @@ where
         let hash = self.hash_builder.hash_one(key);
 
         unsafe {
-            let mut shard = self.shards[hash as usize % self.shards.len()].lock();
+            let mut shard = self.shards[hash as usize % self.shards.len()].write();
             shard.remove(hash, key).map(|ptr| GenericCacheEntry {
                 cache: self.clone(),
                 ptr,
@@ -562,7 +1116,7 @@ where
     }
 
     pub fn pop(self: &Arc<Self>) -> Option<GenericCacheEntry<K, V, E, I, L, S>> {
-        let mut shards = self.shards.iter().map(|shard| shard.lock()).collect_vec();
+        let mut shards = self.shards.iter().map(|shard| shard.write()).collect_vec();
 
         let shard = self
             .usages
@@ -602,7 +1156,7 @@ where
             .0?;
 
         unsafe {
-            let mut shard = self.shards[shard].lock();
+            let mut shard = self.shards[shard].write();
             shard.pop().map(|ptr| GenericCacheEntry {
                 cache: self.clone(),
                 ptr,
@@ -616,14 +1170,30 @@ where
         Q: Hash + Eq + ?Sized,
     {
         let hash = self.hash_builder.hash_one(key);
+        let shard = hash as usize % self.shards.len();
 
-        unsafe {
-            let mut shard = self.shards[hash as usize % self.shards.len()].lock();
-            shard.get(hash, key).map(|ptr| GenericCacheEntry {
+        if self.read_buffers.is_some() {
+            if let Some(entry) = unsafe { self.get_shared(shard, hash, key) } {
+                return entry;
+            }
+        }
+
+        let mut to_deallocate = vec![];
+
+        let entry = unsafe {
+            let mut shard = self.write_shard(shard, &mut to_deallocate);
+            shard.get(hash, key, &mut to_deallocate).map(|ptr| GenericCacheEntry {
                 cache: self.clone(),
                 ptr,
             })
+        };
+
+        // Do not deallocate data within the lock section.
+        for entry in to_deallocate {
+            self.notify_release(entry);
         }
+
+        entry
     }
 
     pub fn contains<Q>(self: &Arc<Self>, key: &Q) -> bool
@@ -633,10 +1203,19 @@ where
     {
         let hash = self.hash_builder.hash_one(key);
 
-        unsafe {
-            let mut shard = self.shards[hash as usize % self.shards.len()].lock();
-            shard.contains(hash, key)
+        let mut to_deallocate = vec![];
+
+        let res = unsafe {
+            let mut shard = self.shards[hash as usize % self.shards.len()].write();
+            shard.contains(hash, key, &mut to_deallocate)
+        };
+
+        // Do not deallocate data within the lock section.
+        for entry in to_deallocate {
+            self.notify_release(entry);
         }
+
+        res
     }
 
     pub fn touch<Q>(&self, key: &Q) -> bool
@@ -646,22 +1225,89 @@ where
     {
         let hash = self.hash_builder.hash_one(key);
 
-        unsafe {
-            let mut shard = self.shards[hash as usize % self.shards.len()].lock();
-            shard.touch(hash, key)
+        let mut to_deallocate = vec![];
+
+        let res = unsafe {
+            let mut shard = self.shards[hash as usize % self.shards.len()].write();
+            shard.touch(hash, key, &mut to_deallocate)
+        };
+
+        // Do not deallocate data within the lock section.
+        for entry in to_deallocate {
+            self.notify_release(entry);
         }
+
+        res
     }
 
     pub fn clear(&self) {
         let mut to_deallocate = vec![];
-        for shard in self.shards.iter() {
-            let mut shard = shard.lock();
-            unsafe { shard.clear(&mut to_deallocate) };
+        for shard in 0..self.shards.len() {
+            unsafe {
+                let mut shard = self.write_shard(shard, &mut to_deallocate);
+                shard.clear(&mut to_deallocate);
+            }
+        }
+
+        // Do not deallocate data within the lock section.
+        for entry in to_deallocate {
+            self.notify_release(entry);
+        }
+    }
+
+    /// Retain only the entries that the predicate returns `true` for, return the count of the removed entries.
+    ///
+    /// The shards are visited one at a time with the shard lock held. The removed entries are released the same as
+    /// the ones removed by `remove`.
+    pub fn retain(&self, mut f: impl FnMut(&K, &V) -> bool) -> usize {
+        let mut removed = 0;
+        for shard in 0..self.shards.len() {
+            let mut to_deallocate = vec![];
+            unsafe {
+                let mut shard = self.write_shard(shard, &mut to_deallocate);
+                removed += shard.retain(&mut f, &mut to_deallocate);
+            }
+
+            // Do not deallocate data within the lock section.
+            for entry in to_deallocate {
+                self.notify_release(entry);
+            }
         }
+        removed
+    }
+
+    /// Remove the entries that the predicate returns `true` for, return the count of the removed entries.
+    ///
+    /// See [`Self::retain`].
+    pub fn remove_if(&self, mut f: impl FnMut(&K, &V) -> bool) -> usize {
+        self.retain(|key, value| !f(key, value))
     }
 
     pub fn capacity(&self) -> usize {
-        self.capacity
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    /// Update the capacity of the cache, which is split evenly across the shards.
+    ///
+    /// Entries are evicted until the usage fits the new capacity. Entries that are still held by external users are
+    /// released after they are dropped.
+    pub fn resize(&self, capacity: usize) {
+        let shard_capacity = capacity / self.shards.len();
+
+        let mut to_deallocate = vec![];
+        for shard in 0..self.shards.len() {
+            unsafe {
+                let mut shard = self.write_shard(shard, &mut to_deallocate);
+                shard.resize(shard_capacity, &mut to_deallocate);
+            }
+        }
+        let old = self.capacity.swap(capacity, Ordering::Relaxed);
+        self.context.metrics.capacity.add(capacity as i64 - old as i64);
+
+        // Do not deallocate data within the lock section.
+        for entry in to_deallocate {
+            self.notify_release(entry);
+        }
     }
 
     pub fn usage(&self) -> usize {
@@ -672,17 +1318,327 @@ where
         &self.context.metrics
     }
 
+    pub fn shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Iterate over the entries of the cache.
+    ///
+    /// The iteration is weakly consistent. The shards are visited one at a time, and the entries of a shard are
+    /// collected at once when the shard is visited. Entries inserted or removed concurrently may or may not be
+    /// yielded. Expired entries are skipped.
+    ///
+    /// Iterating does not count as access to the entries.
+    pub fn iter(self: &Arc<Self>) -> impl Iterator<Item = GenericCacheEntry<K, V, E, I, L, S>> {
+        let cache = self.clone();
+        (0..self.shards.len()).flat_map(move |shard| cache.shard_entries(shard))
+    }
+
+    /// Iterate over the keys of the cache.
+    ///
+    /// The iteration is weakly consistent, the same as [`Self::iter`], but holds no references of the entries.
+    pub fn keys(self: &Arc<Self>) -> impl Iterator<Item = K>
+    where
+        K: Clone,
+    {
+        let cache = self.clone();
+        (0..self.shards.len()).flat_map(move |shard| cache.shard_keys(shard))
+    }
+
+    /// Collect the entries of the shard.
+    pub(crate) fn shard_entries(self: &Arc<Self>, shard: usize) -> Vec<GenericCacheEntry<K, V, E, I, L, S>> {
+        // The handles cannot be released while the shard lock is held, so it is safe to take references of them
+        // with the shard lock in shared mode.
+        let shard = self.shards[shard].read();
+        unsafe {
+            shard
+                .indexer
+                .iter()
+                .filter(|ptr| !ptr.as_ref().base().is_expired())
+                .map(|ptr| {
+                    ptr.as_ref().base().inc_refs();
+                    GenericCacheEntry {
+                        cache: self.clone(),
+                        ptr,
+                    }
+                })
+                .collect_vec()
+        }
+    }
+
+    /// Collect the keys of the shard.
+    pub(crate) fn shard_keys(&self, shard: usize) -> Vec<K>
+    where
+        K: Clone,
+    {
+        let shard = self.shards[shard].read();
+        unsafe {
+            shard
+                .indexer
+                .iter()
+                .filter(|ptr| !ptr.as_ref().base().is_expired())
+                .map(|ptr| ptr.as_ref().key().clone())
+                .collect_vec()
+        }
+    }
+
+    /// Append the entries of all shards to the snapshot writer.
+    ///
+    /// The entries are dumped with the eviction states in the order dumped by the eviction containers. The entries of
+    /// the shards are interleaved by their relative positions in the shards, so that the order is kept approximately
+    /// after the entries are rehashed into the shards of the restoring cache, and kept exactly within each shard if
+    /// the restoring cache shares the same hasher. Expired entries are skipped.
+    ///
+    /// The shards are dumped with the shard locks in shared mode one at a time, the entries are written to the writer
+    /// after all locks are released. The accesses buffered in the read buffers are not reflected.
+    pub(crate) fn dump_snapshot(self: &Arc<Self>, writer: &mut SnapshotWriter) -> SnapshotResult<()>
+    where
+        K: Serialize,
+        V: Serialize,
+    {
+        let now = Instant::now();
+
+        let shards = (0..self.shards.len())
+            .map(|shard| {
+                // The handles cannot be released while the shard lock is held, so it is safe to take references of
+                // them with the shard lock in shared mode.
+                let shard = self.shards[shard].read();
+                unsafe {
+                    let rest = shard
+                        .indexer
+                        .iter()
+                        .filter(|ptr| !ptr.as_ref().base().is_in_eviction())
+                        .map(|ptr| (ptr, None))
+                        .collect_vec();
+                    shard
+                        .eviction
+                        .snapshot()
+                        .into_iter()
+                        .map(|(ptr, state)| (ptr, Some(state)))
+                        .chain(rest)
+                        .filter_map(|(ptr, state)| {
+                            let ttl = match ptr.as_ref().base().expire_at() {
+                                Some(expire_at) if expire_at <= now => return None,
+                                Some(expire_at) => Some(expire_at - now),
+                                None => None,
+                            };
+                            ptr.as_ref().base().inc_refs();
+                            let entry = GenericCacheEntry {
+                                cache: self.clone(),
+                                ptr,
+                            };
+                            Some((entry, state, ttl))
+                        })
+                        .collect_vec()
+                }
+            })
+            .collect_vec();
+
+        // Interleave the entries by `(index + 0.5) / len` of each shard, ties are broken by the shard index.
+        let mut order = shards
+            .iter()
+            .enumerate()
+            .flat_map(|(shard, entries)| (0..entries.len()).map(move |index| (shard, index, entries.len())))
+            .collect_vec();
+        order.sort_by(|&(s1, i1, l1), &(s2, i2, l2)| ((2 * i1 + 1) * l2, s1).cmp(&((2 * i2 + 1) * l1, s2)));
+
+        order.into_iter().try_for_each(|(shard, index, _)| {
+            let (entry, state, ttl) = &shards[shard][index];
+            writer.append(&SnapshotEntry {
+                key: entry.key(),
+                value: entry.value(),
+                charge: entry.charge(),
+                context: entry.context().clone().into(),
+                ttl: *ttl,
+                state: state.as_ref(),
+            })
+        })
+    }
+
+    /// Restore the entries from the snapshot reader.
+    ///
+    /// The entries are restored into the eviction containers with the dumped states. If the capacity is smaller than
+    /// the snapshot, the entries dumped first are evicted first. Return the count of the restored entries.
+    pub(crate) fn restore_snapshot(&self, reader: SnapshotReader) -> SnapshotResult<usize>
+    where
+        K: DeserializeOwned,
+        V: DeserializeOwned,
+    {
+        let mut to_deallocate = vec![];
+        let mut count = 0;
+
+        let res = reader.entries::<K, V, E::State>()?.try_for_each(|entry| {
+            let entry = entry?;
+            let hash = self.hash_builder.hash_one(&entry.key);
+            unsafe {
+                let mut shard = self.write_shard(hash as usize % self.shards.len(), &mut to_deallocate);
+                let ptr = shard.insert(
+                    hash,
+                    entry.key,
+                    entry.value,
+                    entry.charge,
+                    entry.context.into(),
+                    entry.ttl,
+                    entry.state,
+                    &mut to_deallocate,
+                );
+                // The entry is held by the cache only, the reference is released without touching the eviction
+                // container to keep the restored order.
+                ptr.as_ref().base().dec_refs();
+            }
+            count += 1;
+            Ok(())
+        });
+
+        // Do not deallocate data within the lock section.
+        for entry in to_deallocate {
+            self.notify_release(entry);
+        }
+
+        res.map(|_| count)
+    }
+
+    pub fn weigher(&self) -> &Weigher<K, V> {
+        &self.weigher
+    }
+
     unsafe fn try_release_external_handle(&self, ptr: NonNull<E::Handle>) {
+        // With the read buffers enabled, the references that are not the last one are released without locking.
+        if self.read_buffers.is_some() && ptr.as_ref().base().dec_refs_if_shared() {
+            return;
+        }
+
+        let mut to_deallocate = vec![];
+
         let entry = {
             let base = ptr.as_ref().base();
-            let mut shard = self.shards[base.hash() as usize % self.shards.len()].lock();
+            let mut shard = self.write_shard(base.hash() as usize % self.shards.len(), &mut to_deallocate);
             shard.try_release_external_handle(ptr)
         };
+        to_deallocate.extend(entry);
 
         // Do not deallocate data within the lock section.
-        if let Some((key, value, context, charges)) = entry {
-            self.context.listener.on_release(key, value, context.into(), charges);
+        for entry in to_deallocate {
+            self.notify_release(entry);
+        }
+    }
+
+    /// Get the entry with the shard lock in shared mode, and record the access in the read buffer of the shard.
+    ///
+    /// Return `None` if the entry is expired, which must be removed with the shard lock in exclusive mode.
+    // TODO(MrCroxx): use `expect` after `lint_reasons` is stable.
+    #[allow(clippy::type_complexity)]
+    unsafe fn get_shared<Q>(
+        self: &Arc<Self>,
+        shard: usize,
+        hash: u64,
+        key: &Q,
+    ) -> Option<Option<GenericCacheEntry<K, V, E, I, L, S>>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let buffer = &self.read_buffers.as_ref().unwrap_unchecked()[shard];
+
+        let (ptr, len) = {
+            let guard = self.shards[shard].read();
+
+            let ptr = match guard.indexer.get(hash, key) {
+                Some(ptr) => ptr,
+                None => {
+                    self.context.metrics.miss.fetch_add(1, Ordering::Relaxed);
+                    return Some(None);
+                }
+            };
+            let base = ptr.as_ref().base();
+            if base.is_expired() {
+                return None;
+            }
+            self.context.metrics.hit.fetch_add(1, Ordering::Relaxed);
+
+            // One reference for the returned entry, and one for the recorded access, which keeps the handle alive
+            // until the read buffer is drained.
+            base.inc_refs_by(2);
+            let len = match buffer.push(ptr) {
+                Ok(len) => len,
+                Err(_) => {
+                    self.context.metrics.read_buffer_drop.fetch_add(1, Ordering::Relaxed);
+                    base.dec_refs();
+                    0
+                }
+            };
+
+            (ptr, len)
+        };
+
+        // Drain the read buffer when it is half full, unless another thread is holding the shard lock.
+        if len >= buffer.capacity() / 2 {
+            if let Some(mut guard) = self.shards[shard].try_write() {
+                let mut to_deallocate = vec![];
+                guard.drain_read_buffer(buffer, &mut to_deallocate);
+                drop(guard);
+
+                // Do not deallocate data within the lock section.
+                for entry in to_deallocate {
+                    self.notify_release(entry);
+                }
+            }
+        }
+
+        Some(Some(GenericCacheEntry {
+            cache: self.clone(),
+            ptr,
+        }))
+    }
+
+    /// Lock the shard in exclusive mode, and apply the accesses recorded in the read buffer of the shard.
+    // TODO(MrCroxx): use `expect` after `lint_reasons` is stable.
+    #[allow(clippy::type_complexity)]
+    unsafe fn write_shard(
+        &self,
+        shard: usize,
+        last_reference_entries: &mut Vec<ReleasedEntry<K, V, <E::Handle as Handle>::Context>>,
+    ) -> RwLockWriteGuard<'_, CacheShard<K, V, E, I, L, S>> {
+        let mut guard = self.shards[shard].write();
+        if let Some(buffers) = self.read_buffers.as_ref() {
+            guard.drain_read_buffer(&buffers[shard], last_reference_entries);
         }
+        guard
+    }
+
+    fn notify_release(
+        &self,
+        (key, value, context, charges, cause): ReleasedEntry<K, V, <E::Handle as Handle>::Context>,
+    ) {
+        self.context
+            .listener
+            .on_release_with_cause(key, value, context.into(), charges, cause);
+    }
+}
+
+impl<K, V, E, I, L, S> Drop for GenericCache<K, V, E, I, L, S>
+where
+    K: Key,
+    V: Value,
+    E: Eviction,
+    E::Handle: KeyedHandle<Key = K, Data = (K, V)>,
+    I: Indexer<Key = K, Handle = E::Handle>,
+    L: CacheEventListener<K, V>,
+    S: BuildHasher + Send + Sync + 'static,
+{
+    fn drop(&mut self) {
+        // The recorded accesses hold references of the handles, release them before the shards are dropped.
+        if let Some(buffers) = self.read_buffers.as_ref() {
+            for (shard, buffer) in self.shards.iter_mut().zip_eq(buffers.iter()) {
+                unsafe { shard.get_mut().drain_read_buffer(buffer, &mut vec![]) }
+            }
+        }
+
+        // The usage is withdrawn by the shards on drop.
+        self.context
+            .metrics
+            .capacity
+            .sub(self.capacity.load(Ordering::Relaxed) as i64);
     }
 }
 
@@ -697,55 +1653,232 @@ where
     L: CacheEventListener<K, V>,
     S: BuildHasher + Send + Sync + 'static,
 {
+    /// Get the entry of the key, or fetch it with `f` on miss.
+    ///
+    /// The negative cache is neither looked up nor filled, see [`Self::entry_with_negative_cache`].
     pub fn entry<F, FU, ER>(self: &Arc<Self>, key: K, f: F) -> GenericEntry<K, V, E, I, L, S, ER>
     where
         F: FnOnce() -> FU,
-        FU: Future<Output = std::result::Result<(V, usize, CacheContext), ER>> + Send + 'static,
+        FU: Future<Output = std::result::Result<(V, CacheContext), ER>> + Send + 'static,
+        ER: std::error::Error + Send + 'static,
+    {
+        self.entry_inner(key, || f().map(|res| res.map(Some)), None)
+    }
+
+    /// The same as [`Self::entry`], but `f` may find nothing for the key, and the failure of the fetch is cached if
+    /// the negative cache is configured.
+    ///
+    /// The cached failure is returned without calling `f` until it expires.
+    pub fn entry_with_negative_cache<F, FU, ER>(self: &Arc<Self>, key: K, f: F) -> GenericEntry<K, V, E, I, L, S, ER>
+    where
+        F: FnOnce() -> FU,
+        FU: Future<Output = std::result::Result<Option<(V, CacheContext)>, ER>> + Send + 'static,
+        ER: std::error::Error + From<Negative> + Clone + Send + Sync + 'static,
+    {
+        self.entry_inner(key, f, Some(NegativeCodec::new()))
+    }
+
+    fn entry_inner<F, FU, ER>(
+        self: &Arc<Self>,
+        key: K,
+        f: F,
+        codec: Option<NegativeCodec<ER>>,
+    ) -> GenericEntry<K, V, E, I, L, S, ER>
+    where
+        F: FnOnce() -> FU,
+        FU: Future<Output = std::result::Result<Option<(V, CacheContext)>, ER>> + Send + 'static,
         ER: std::error::Error + Send + 'static,
     {
         let hash = self.hash_builder.hash_one(&key);
 
-        unsafe {
-            let mut shard = self.shards[hash as usize % self.shards.len()].lock();
-            if let Some(ptr) = shard.get(hash, &key) {
-                return GenericEntry::Hit(GenericCacheEntry {
+        let mut to_deallocate = vec![];
+
+        let entry = unsafe {
+            let mut shard = self.write_shard(hash as usize % self.shards.len(), &mut to_deallocate);
+            if let Some((mut ptr, refresh)) = shard.get_with_refresh(hash, &key, &mut to_deallocate) {
+                let entry = GenericCacheEntry {
                     cache: self.clone(),
                     ptr,
-                });
+                };
+                // The stale entry is returned, and refreshed in background.
+                if refresh {
+                    ptr.as_mut().base_mut().set_refreshing(true);
+                    shard.state.metrics.refresh.fetch_add(1, Ordering::Relaxed);
+                    self.refresh(entry.clone(), f());
+                }
+                GenericEntry::Hit(entry)
+            } else if let Some(error) = codec
+                .as_ref()
+                .and_then(|codec| Some((codec.decode)(shard.get_negative(&key)?.negative.clone())))
+            {
+                shard.state.metrics.negative.fetch_add(1, Ordering::Relaxed);
+                GenericEntry::Negative(Box::new(error))
+            } else {
+                let entry = match shard.waiters.entry(key.clone()) {
+                    HashMapEntry::Occupied(mut o) => {
+                        let (tx, rx) = oneshot::channel();
+                        o.get_mut().push(tx);
+                        GenericEntry::Wait(rx)
+                    }
+                    HashMapEntry::Vacant(v) => {
+                        v.insert(vec![]);
+                        GenericEntry::Miss(self.fetch(hash, key, f(), codec))
+                    }
+                };
+                match entry {
+                    GenericEntry::Wait(_) => shard.state.metrics.queue.fetch_add(1, Ordering::Relaxed),
+                    GenericEntry::Miss(_) => shard.state.metrics.fetch.fetch_add(1, Ordering::Relaxed),
+                    _ => unreachable!(),
+                };
+                entry
             }
-            let entry = match shard.waiters.entry(key.clone()) {
-                HashMapEntry::Occupied(mut o) => {
-                    let (tx, rx) = oneshot::channel();
-                    o.get_mut().push(tx);
-                    GenericEntry::Wait(rx)
+        };
+
+        // Do not deallocate data within the lock section.
+        for entry in to_deallocate {
+            self.notify_release(entry);
+        }
+
+        entry
+    }
+
+    /// Spawn the fetch of the key, whose waiters must have been registered.
+    ///
+    /// The fetched entry is inserted into the cache and sent to the waiters. If the key is inserted since the fetch
+    /// started, the inserted entry is returned instead and the fetched one is dropped. The waiters are dropped if the
+    /// fetch fails or finds nothing, and the failure is cached if `codec` is given and the negative cache is
+    /// configured. The fetch can find nothing only with `codec` given.
+    // TODO(MrCroxx): use `expect` after `lint_reasons` is stable.
+    #[allow(clippy::type_complexity)]
+    fn fetch<FU, ER>(
+        self: &Arc<Self>,
+        hash: u64,
+        key: K,
+        future: FU,
+        codec: Option<NegativeCodec<ER>>,
+    ) -> JoinHandle<std::result::Result<GenericCacheEntry<K, V, E, I, L, S>, ER>>
+    where
+        FU: Future<Output = std::result::Result<Option<(V, CacheContext)>, ER>> + Send + 'static,
+        ER: std::error::Error + Send + 'static,
+    {
+        let cache = self.clone();
+        tokio::spawn(async move {
+            let (error, negative) = match future.await {
+                Ok(Some((value, context))) => {
+                    let charge = (cache.weigher)(&key, &value);
+                    let entry = cache.insert_inner(key, value, charge, context, cache.ttl, true);
+                    return Ok(entry);
                 }
-                HashMapEntry::Vacant(v) => {
-                    v.insert(vec![]);
-                    let cache = self.clone();
-                    let future = f();
-                    let join = tokio::spawn(async move {
-                        let (value, charge, context) = match future.await {
-                            Ok((value, charge, context)) => (value, charge, context),
-                            Err(e) => {
-                                let mut shard = cache.shards[hash as usize % cache.shards.len()].lock();
-                                shard.waiters.remove(&key);
-                                return Err(e);
-                            }
-                        };
-                        let entry = cache.insert_with_context(key, value, charge, context);
-                        Ok(entry)
-                    });
-                    GenericEntry::Miss(join)
+                Ok(None) => match codec.as_ref() {
+                    Some(codec) => ((codec.decode)(Negative::NotFound), Some(Negative::NotFound)),
+                    None => unreachable!(),
+                },
+                Err(e) => {
+                    let negative = codec.as_ref().map(|codec| (codec.encode)(&e));
+                    (e, negative)
                 }
             };
-            match entry {
-                GenericEntry::Wait(_) => shard.state.metrics.queue.fetch_add(1, Ordering::Relaxed),
-                GenericEntry::Miss(_) => shard.state.metrics.fetch.fetch_add(1, Ordering::Relaxed),
-                _ => unreachable!(),
-            };
-            entry
+            cache.remove_waiters(hash, &key, negative);
+            Err(error)
+        })
+    }
+
+    /// Drop the waiters of the failed fetch of the key, and cache the failure if the negative cache is configured.
+    fn remove_waiters(&self, hash: u64, key: &K, negative: Option<Negative>) {
+        let mut to_deallocate = vec![];
+        unsafe {
+            let mut shard = self.write_shard(hash as usize % self.shards.len(), &mut to_deallocate);
+            shard.waiters.remove(key);
+            if let (Some(negative), Some(config)) = (negative, self.context.negative_cache.as_ref()) {
+                shard.insert_negative(key.clone(), negative, config, &mut to_deallocate);
+            }
+        }
+        // Do not deallocate data within the lock section.
+        for entry in to_deallocate {
+            self.notify_release(entry);
         }
     }
+
+    /// Spawn the refresh of the stale entry, which must have been marked as refreshing.
+    ///
+    /// The refreshed entry replaces the stale one with the context and the time-to-live of the stale one. The result
+    /// is dropped if the stale entry has been removed or replaced since the refresh started, and the stale entry is
+    /// kept if the refresh fails or finds nothing.
+    fn refresh<FU, ER>(self: &Arc<Self>, entry: GenericCacheEntry<K, V, E, I, L, S>, future: FU)
+    where
+        FU: Future<Output = std::result::Result<Option<(V, CacheContext)>, ER>> + Send + 'static,
+        ER: std::error::Error + Send + 'static,
+    {
+        let cache = self.clone();
+        tokio::spawn(async move {
+            let value = future.await.ok().flatten().map(|(value, _)| {
+                let charge = (cache.weigher)(entry.key(), &value);
+                (value, charge)
+            });
+
+            let hash = unsafe { entry.ptr.as_ref().base().hash() };
+            let mut to_deallocate = vec![];
+
+            let refreshed = unsafe {
+                let mut shard = cache.write_shard(hash as usize % cache.shards.len(), &mut to_deallocate);
+                let mut ptr = entry.ptr;
+                match value {
+                    Some((value, charge)) if ptr.as_ref().base().is_in_indexer() => {
+                        let base = ptr.as_ref().base();
+                        let ptr = shard.insert(
+                            hash,
+                            entry.key().clone(),
+                            value,
+                            charge,
+                            base.context().clone(),
+                            base.ttl(),
+                            None,
+                            &mut to_deallocate,
+                        );
+                        Some(GenericCacheEntry {
+                            cache: cache.clone(),
+                            ptr,
+                        })
+                    }
+                    _ => {
+                        ptr.as_mut().base_mut().set_refreshing(false);
+                        None
+                    }
+                }
+            };
+
+            if let Some(refreshed) = refreshed.as_ref() {
+                cache.context.listener.on_insert(
+                    refreshed.key(),
+                    refreshed.value(),
+                    refreshed.context().clone().into(),
+                    refreshed.charge(),
+                );
+            }
+
+            // Do not deallocate data within the lock section.
+            for entry in to_deallocate {
+                cache.notify_release(entry);
+            }
+        });
+    }
+}
+
+impl<K, V, I, L, S> GenericCache<K, V, Lfu<(K, V)>, I, L, S>
+where
+    K: Key,
+    V: Value,
+    I: Indexer<Key = K, Handle = LfuHandle<(K, V)>>,
+    L: CacheEventListener<K, V>,
+    S: BuildHasher + Send + Sync + 'static,
+{
+    /// Get the sum of the current queue sizes of all shards.
+    pub fn lfu_queue_sizes(&self) -> LfuQueueSizes {
+        self.shards
+            .iter()
+            .map(|shard| shard.read().eviction.queue_sizes())
+            .fold(LfuQueueSizes::default(), |acc, sizes| acc + sizes)
+    }
 }
 
 pub struct GenericCacheEntry<K, V, E, I, L, S = RandomState>
@@ -804,17 +1937,15 @@ where
     S: BuildHasher + Send + Sync + 'static,
 {
     fn clone(&self) -> Self {
-        let mut ptr = self.ptr;
-
         unsafe {
-            let base = ptr.as_mut().base_mut();
+            let base = self.ptr.as_ref().base();
             debug_assert!(base.has_refs());
             base.inc_refs();
         }
 
         Self {
             cache: self.cache.clone(),
-            ptr,
+            ptr: self.ptr,
         }
     }
 }
@@ -902,12 +2033,18 @@ mod tests {
         const CAPACITY: usize = 256;
 
         let config = GenericCacheConfig {
+            name: "".to_string(),
             capacity: CAPACITY,
             shards: 4,
             eviction_config: FifoConfig {},
             object_pool_capacity: 16,
             hash_builder: RandomState::default(),
             event_listener: DefaultCacheEventListener::default(),
+            ttl: None,
+            read_buffer_capacity: None,
+            refresh_policy: None,
+            negative_cache: None,
+            weigher: Arc::new(|_, _| 1),
         };
         let cache = Arc::new(FifoCache::<u64, u64>::new(config));
 
@@ -919,25 +2056,32 @@ mod tests {
                 drop(entry);
                 continue;
             }
-            cache.insert(key, key, 1);
+            cache.insert(key, key);
         }
         assert_eq!(cache.usage(), CAPACITY);
     }
 
     fn fifo(capacity: usize) -> Arc<FifoCache<u64, String>> {
         let config = GenericCacheConfig {
+            name: "".to_string(),
             capacity,
             shards: 1,
             eviction_config: FifoConfig {},
             object_pool_capacity: 1,
             hash_builder: RandomState::default(),
             event_listener: DefaultCacheEventListener::default(),
+            ttl: None,
+            read_buffer_capacity: None,
+            refresh_policy: None,
+            negative_cache: None,
+            weigher: Arc::new(|_, _| 1),
         };
         Arc::new(FifoCache::<u64, String>::new(config))
     }
 
     fn lru(capacity: usize) -> Arc<LruCache<u64, String>> {
         let config = GenericCacheConfig {
+            name: "".to_string(),
             capacity,
             shards: 1,
             eviction_config: LruConfig {
@@ -946,16 +2090,41 @@ mod tests {
             object_pool_capacity: 1,
             hash_builder: RandomState::default(),
             event_listener: DefaultCacheEventListener::default(),
+            ttl: None,
+            read_buffer_capacity: None,
+            refresh_policy: None,
+            negative_cache: None,
+            weigher: Arc::new(|_, _| 1),
+        };
+        Arc::new(LruCache::<u64, String>::new(config))
+    }
+
+    fn lru_with_read_buffer(capacity: usize, read_buffer_capacity: usize) -> Arc<LruCache<u64, String>> {
+        let config = GenericCacheConfig {
+            name: "".to_string(),
+            capacity,
+            shards: 1,
+            eviction_config: LruConfig {
+                high_priority_pool_ratio: 0.0,
+            },
+            object_pool_capacity: 1,
+            hash_builder: RandomState::default(),
+            event_listener: DefaultCacheEventListener::default(),
+            ttl: None,
+            read_buffer_capacity: Some(read_buffer_capacity),
+            refresh_policy: None,
+            negative_cache: None,
+            weigher: Arc::new(|_, _| 1),
         };
         Arc::new(LruCache::<u64, String>::new(config))
     }
 
     fn insert_fifo(cache: &Arc<FifoCache<u64, String>>, key: u64, value: &str) -> FifoCacheEntry<u64, String> {
-        cache.insert(key, value.to_string(), value.len())
+        cache.insert_with_charge(key, value.to_string(), value.len())
     }
 
     fn insert_lru(cache: &Arc<LruCache<u64, String>>, key: u64, value: &str) -> LruCacheEntry<u64, String> {
-        cache.insert(key, value.to_string(), value.len())
+        cache.insert_with_charge(key, value.to_string(), value.len())
     }
 
     #[test]
@@ -998,7 +2167,7 @@ mod tests {
         assert_eq!(cache.usage(), 8);
 
         assert_eq!(
-            cache.shards[0].lock().eviction.dump(),
+            cache.shards[0].read().eviction.dump(),
             vec![(514, "QwQ".to_string()), (114, "(0.0)".to_string())],
         );
     }
@@ -1051,7 +2220,7 @@ mod tests {
         assert_eq!(cache.usage(), 12);
 
         // `111`, `222` and `333` are evicted from the eviction container to make space for `444`.
-        assert_eq!(cache.shards[0].lock().eviction.dump(), vec![(4, "444".to_string()),]);
+        assert_eq!(cache.shards[0].read().eviction.dump(), vec![(4, "444".to_string()),]);
 
         // `e1` cannot be reinserted for the usage has already exceeds the capacity.
         drop(e1);
@@ -1061,7 +2230,7 @@ mod tests {
         drop(e2);
         drop(e3);
         assert_eq!(
-            cache.shards[0].lock().eviction.dump(),
+            cache.shards[0].read().eviction.dump(),
             vec![(4, "444".to_string()), (2, "222".to_string()), (3, "333".to_string()),]
         );
         assert_eq!(cache.usage(), 9);
@@ -1069,7 +2238,7 @@ mod tests {
         // `444` will be reinserted
         drop(e4);
         assert_eq!(
-            cache.shards[0].lock().eviction.dump(),
+            cache.shards[0].read().eviction.dump(),
             vec![(2, "222".to_string()), (3, "333".to_string()), (4, "444".to_string()),]
         );
         assert_eq!(cache.usage(), 9);
@@ -1089,7 +2258,7 @@ mod tests {
         assert_eq!(cache.usage(), 12);
 
         // `111`, `222` and `333` are evicted from the eviction container to make space for `444`.
-        assert_eq!(cache.shards[0].lock().eviction.dump(), vec![(4, "444".to_string()),]);
+        assert_eq!(cache.shards[0].read().eviction.dump(), vec![(4, "444".to_string()),]);
 
         // `e1` cannot be reinserted for the usage has already exceeds the capacity.
         drop(e1);
@@ -1097,7 +2266,7 @@ mod tests {
 
         // `222` and `333` will be not reinserted because fifo will ignore reinsert operations.
         drop([e2, e3, e4]);
-        assert_eq!(cache.shards[0].lock().eviction.dump(), vec![(4, "444".to_string()),]);
+        assert_eq!(cache.shards[0].read().eviction.dump(), vec![(4, "444".to_string()),]);
         assert_eq!(cache.usage(), 3);
 
         // Note:
@@ -1105,4 +2274,99 @@ mod tests {
         // For cache policy like FIFO, the entries will not be reinserted while all handles are referenced.
         // It's okay for this is not a common situation and is not supposed to happen in real workload.
     }
+
+    #[test]
+    fn test_read_buffer() {
+        let cache = lru_with_read_buffer(10, 4);
+
+        insert_lru(&cache, 1, "111");
+        insert_lru(&cache, 2, "222");
+        insert_lru(&cache, 3, "333");
+
+        // The access is recorded in the read buffer, which holds a reference until drained.
+        let e1 = cache.get(&1).unwrap();
+        assert_eq!(e1.refs(), 2);
+        drop(e1);
+        assert_eq!(
+            cache.shards[0].read().eviction.dump(),
+            vec![(1, "111".to_string()), (2, "222".to_string()), (3, "333".to_string())]
+        );
+
+        // The read buffer is drained when it is half full, `e2` is still in use and not reinserted yet.
+        let e2 = cache.get(&2).unwrap();
+        assert_eq!(e2.refs(), 1);
+        assert_eq!(
+            cache.shards[0].read().eviction.dump(),
+            vec![(2, "222".to_string()), (3, "333".to_string()), (1, "111".to_string())]
+        );
+        drop(e2);
+        assert_eq!(
+            cache.shards[0].read().eviction.dump(),
+            vec![(3, "333".to_string()), (1, "111".to_string()), (2, "222".to_string())]
+        );
+
+        assert!(cache.get(&4).is_none());
+        assert_eq!(cache.metrics().hit.load(Ordering::Relaxed), 2);
+        assert_eq!(cache.metrics().miss.load(Ordering::Relaxed), 1);
+        assert_eq!(cache.metrics().read_buffer_drain.load(Ordering::Relaxed), 2);
+
+        // The read buffer is drained before eviction, so `333` is not evicted.
+        drop(cache.get(&3).unwrap());
+        insert_lru(&cache, 4, "444");
+        assert_eq!(
+            cache.shards[0].read().eviction.dump(),
+            vec![(2, "222".to_string()), (3, "333".to_string()), (4, "444".to_string())]
+        );
+        assert_eq!(cache.usage(), 9);
+
+        // The references held by the read buffer are released on clear.
+        drop(cache.get(&4).unwrap());
+        cache.clear();
+        assert_eq!(cache.usage(), 0);
+    }
+
+    #[test]
+    fn test_read_buffer_concurrent() {
+        const CAPACITY: usize = 256;
+
+        let config = GenericCacheConfig {
+            name: "".to_string(),
+            capacity: CAPACITY,
+            shards: 4,
+            eviction_config: FifoConfig {},
+            object_pool_capacity: 16,
+            hash_builder: RandomState::default(),
+            event_listener: DefaultCacheEventListener::default(),
+            ttl: None,
+            read_buffer_capacity: Some(16),
+            refresh_policy: None,
+            negative_cache: None,
+            weigher: Arc::new(|_, _| 1),
+        };
+        let cache = Arc::new(FifoCache::<u64, u64>::new(config));
+
+        let handles = (0..4)
+            .map(|i| {
+                let cache = cache.clone();
+                std::thread::spawn(move || {
+                    let mut rng = SmallRng::seed_from_u64(i);
+                    for _ in 0..10000 {
+                        let key = rng.next_u64() % (CAPACITY as u64 * 2);
+                        if let Some(entry) = cache.get(&key) {
+                            assert_eq!(key, *entry);
+                            drop(entry);
+                            continue;
+                        }
+                        cache.insert(key, key);
+                    }
+                })
+            })
+            .collect_vec();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        cache.clear();
+        assert_eq!(cache.usage(), 0);
+    }
 }
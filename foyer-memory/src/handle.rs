@@ -12,17 +12,24 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
 use bitflags::bitflags;
 
 use foyer_common::code::{Key, Value};
+use tokio::time::Instant;
 
-use crate::context::Context;
+use crate::{context::Context, listener::RemovalCause};
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
     struct BaseHandleFlags: u8 {
         const IN_INDEXER = 0b00000001;
         const IN_EVICTION = 0b00000010;
+        const REFRESHING = 0b00000100;
     }
 }
 
@@ -65,9 +72,17 @@ pub struct BaseHandle<T, C> {
     /// entry charge
     charge: usize,
     /// external reference count
-    refs: usize,
+    refs: AtomicUsize,
+    /// the time-to-live the entry is inserted with, `None` means never expire
+    ttl: Option<Duration>,
+    /// the instant after which the entry is expired, `None` means never expire
+    expire_at: Option<Instant>,
+    /// the instant after which a hit with the `entry` interface refreshes the entry, `None` means never refresh
+    refresh_at: Option<Instant>,
     /// flags that used by the general cache abstraction
     flags: BaseHandleFlags,
+    /// the cause of the removal from the indexer, `None` if it is not removed or not known yet
+    removal_cause: Option<RemovalCause>,
 }
 
 impl<T, C> Default for BaseHandle<T, C> {
@@ -84,8 +99,12 @@ impl<T, C> BaseHandle<T, C> {
             entry: None,
             hash: 0,
             charge: 0,
-            refs: 0,
+            refs: AtomicUsize::new(0),
+            ttl: None,
+            expire_at: None,
+            refresh_at: None,
             flags: BaseHandleFlags::empty(),
+            removal_cause: None,
         }
     }
 
@@ -96,8 +115,12 @@ impl<T, C> BaseHandle<T, C> {
         self.hash = hash;
         self.entry = Some((data, context));
         self.charge = charge;
-        self.refs = 0;
+        self.refs = AtomicUsize::new(0);
+        self.ttl = None;
+        self.expire_at = None;
+        self.refresh_at = None;
         self.flags = BaseHandleFlags::empty();
+        self.removal_cause = None;
     }
 
     /// Take key and value from the handle and reset it to the uninited state.
@@ -156,30 +179,97 @@ impl<T, C> BaseHandle<T, C> {
         self.charge
     }
 
+    /// Set the instant after which the handle is expired. `None` means the handle never expires.
+    #[inline(always)]
+    pub fn set_expire_at(&mut self, expire_at: Option<Instant>) {
+        self.expire_at = expire_at;
+    }
+
+    /// Set the time-to-live of the handle from now on. `None` means the handle never expires.
+    #[inline(always)]
+    pub fn set_ttl(&mut self, ttl: Option<Duration>) {
+        self.ttl = ttl;
+        self.expire_at = ttl.map(|ttl| Instant::now() + ttl);
+    }
+
+    /// Get the time-to-live the handle is inserted with.
+    #[inline(always)]
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    /// Get the instant after which the handle is expired.
+    #[inline(always)]
+    pub fn expire_at(&self) -> Option<Instant> {
+        self.expire_at
+    }
+
+    /// Return `true` if the handle is expired.
+    #[inline(always)]
+    pub fn is_expired(&self) -> bool {
+        matches!(self.expire_at, Some(expire_at) if expire_at <= Instant::now())
+    }
+
+    /// Set the instant after which a hit with the `entry` interface refreshes the handle. `None` means never refresh.
+    #[inline(always)]
+    pub fn set_refresh_at(&mut self, refresh_at: Option<Instant>) {
+        self.refresh_at = refresh_at;
+    }
+
+    /// Return `true` if a hit with the `entry` interface should refresh the handle.
+    ///
+    /// The handle is not refreshed again while a refresh of it is ongoing.
+    #[inline(always)]
+    pub fn should_refresh(&self) -> bool {
+        !self.is_refreshing() && matches!(self.refresh_at, Some(refresh_at) if refresh_at <= Instant::now())
+    }
+
+    #[inline(always)]
+    pub fn set_refreshing(&mut self, refreshing: bool) {
+        if refreshing {
+            self.flags |= BaseHandleFlags::REFRESHING;
+        } else {
+            self.flags -= BaseHandleFlags::REFRESHING;
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_refreshing(&self) -> bool {
+        !(self.flags & BaseHandleFlags::REFRESHING).is_empty()
+    }
+
     /// Increase the external reference count of the handle, returns the new reference count.
     #[inline(always)]
-    pub fn inc_refs(&mut self) -> usize {
+    pub fn inc_refs(&self) -> usize {
         self.inc_refs_by(1)
     }
 
     /// Increase the external reference count of the handle, returns the new reference count.
     #[inline(always)]
-    pub fn inc_refs_by(&mut self, val: usize) -> usize {
-        self.refs += val;
-        self.refs
+    pub fn inc_refs_by(&self, val: usize) -> usize {
+        self.refs.fetch_add(val, Ordering::AcqRel) + val
     }
 
     /// Decrease the external reference count of the handle, returns the new reference count.
     #[inline(always)]
-    pub fn dec_refs(&mut self) -> usize {
-        self.refs -= 1;
+    pub fn dec_refs(&self) -> usize {
+        self.refs.fetch_sub(1, Ordering::AcqRel) - 1
+    }
+
+    /// Decrease the external reference count of the handle only if it is not the last reference.
+    ///
+    /// Return `true` if the reference count is decreased.
+    #[inline(always)]
+    pub fn dec_refs_if_shared(&self) -> bool {
         self.refs
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |refs| (refs > 1).then(|| refs - 1))
+            .is_ok()
     }
 
     /// Get the external reference count of the handle.
     #[inline(always)]
     pub fn refs(&self) -> usize {
-        self.refs
+        self.refs.load(Ordering::Acquire)
     }
 
     /// Return `true` if there are external references.
@@ -188,6 +278,18 @@ impl<T, C> BaseHandle<T, C> {
         self.refs() > 0
     }
 
+    /// Set the cause of the removal from the indexer.
+    #[inline(always)]
+    pub fn set_removal_cause(&mut self, cause: RemovalCause) {
+        self.removal_cause = Some(cause);
+    }
+
+    /// Get the cause of the removal from the indexer.
+    #[inline(always)]
+    pub fn removal_cause(&self) -> Option<RemovalCause> {
+        self.removal_cause
+    }
+
     #[inline(always)]
     pub fn set_in_indexer(&mut self, in_cache: bool) {
         if in_cache {
@@ -237,4 +339,56 @@ mod tests {
         assert!(!h.is_in_indexer());
         assert!(!h.is_in_eviction());
     }
+
+    #[test]
+    fn test_base_handle_refs() {
+        let h = BaseHandle::<(), ()>::new();
+        assert!(!h.has_refs());
+
+        assert_eq!(h.inc_refs_by(2), 2);
+        assert!(h.dec_refs_if_shared());
+        assert_eq!(h.refs(), 1);
+
+        // The last reference can only be released with `dec_refs`.
+        assert!(!h.dec_refs_if_shared());
+        assert_eq!(h.dec_refs(), 0);
+        assert!(!h.has_refs());
+    }
+
+    #[test]
+    fn test_base_handle_expire() {
+        let mut h = BaseHandle::<(), ()>::new();
+        assert!(!h.is_expired());
+
+        h.set_expire_at(Some(Instant::now() + Duration::from_secs(3600)));
+        assert!(!h.is_expired());
+
+        h.set_expire_at(Some(Instant::now()));
+        assert!(h.is_expired());
+
+        h.set_ttl(Some(Duration::from_secs(3600)));
+        assert_eq!(h.ttl(), Some(Duration::from_secs(3600)));
+        assert!(!h.is_expired());
+
+        h.init(0, (), 1, ());
+        assert_eq!(h.ttl(), None);
+        assert_eq!(h.expire_at(), None);
+    }
+
+    #[test]
+    fn test_base_handle_refresh() {
+        let mut h = BaseHandle::<(), ()>::new();
+        h.set_refresh_at(Some(Instant::now()));
+        assert!(h.should_refresh());
+
+        // The ongoing refresh is not started again.
+        h.set_refreshing(true);
+        assert!(!h.should_refresh());
+        h.set_refreshing(false);
+        assert!(h.should_refresh());
+
+        h.set_refreshing(true);
+        h.init(0, (), 1, ());
+        assert!(!h.is_refreshing());
+    }
 }
@@ -35,6 +35,7 @@ pub trait Indexer: Send + Sync + 'static {
         Self::Key: Borrow<Q>,
         Q: Hash + Eq + ?Sized;
     unsafe fn drain(&mut self) -> impl Iterator<Item = NonNull<Self::Handle>>;
+    unsafe fn iter(&self) -> impl Iterator<Item = NonNull<Self::Handle>>;
 }
 
 pub struct HashTableIndexer<K, H>
@@ -132,4 +133,8 @@ where
             ptr
         })
     }
+
+    unsafe fn iter(&self) -> impl Iterator<Item = NonNull<Self::Handle>> {
+        self.table.iter().copied()
+    }
 }
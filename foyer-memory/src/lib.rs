@@ -71,5 +71,7 @@ mod indexer;
 mod listener;
 mod metrics;
 mod prelude;
+mod read_buffer;
+mod snapshot;
 
 pub use prelude::*;
@@ -18,15 +18,82 @@ use foyer_common::code::{Key, Value};
 
 use crate::CacheContext;
 
+/// The cause of an entry leaving the cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RemovalCause {
+    /// The entry is evicted by the eviction algorithm, or popped from the cache.
+    Evicted,
+    /// The entry is expired.
+    Expired,
+    /// The entry is removed explicitly.
+    Removed,
+    /// The entry is replaced by a new entry with the same key.
+    Replaced,
+    /// The entry is removed by clearing the cache.
+    Cleared,
+}
+
 pub trait CacheEventListener<K, V>: Send + Sync + 'static
 where
     K: Key,
     V: Value,
 {
+    /// The function is called after an entry is inserted into the cache.
+    ///
+    /// The default implementation does nothing.
+    fn on_insert(&self, _key: &K, _value: &V, _context: CacheContext, _charges: usize) {}
+
     /// The function is called when an entry is released by the cache and all external users.
     ///
     /// The arguments includes the key and value with ownership.
     fn on_release(&self, key: K, value: V, context: CacheContext, charges: usize);
+
+    /// The function is called when an entry is released by the cache and all external users, with the cause of its
+    /// removal.
+    ///
+    /// The default implementation dispatches the entry to the callback of the cause, and entries removed by clearing
+    /// the cache to `on_release`.
+    fn on_release_with_cause(&self, key: K, value: V, context: CacheContext, charges: usize, cause: RemovalCause) {
+        match cause {
+            RemovalCause::Evicted => self.on_evict(key, value, context, charges),
+            RemovalCause::Expired => self.on_expire(key, value, context, charges),
+            RemovalCause::Removed => self.on_remove(key, value, context, charges),
+            RemovalCause::Replaced => self.on_replace(key, value, context, charges),
+            RemovalCause::Cleared => self.on_release(key, value, context, charges),
+        }
+    }
+
+    /// The function is called instead of `on_release` when an evicted entry is released by the cache and all
+    /// external users.
+    ///
+    /// The default implementation forwards the evicted entry to `on_release`.
+    fn on_evict(&self, key: K, value: V, context: CacheContext, charges: usize) {
+        self.on_release(key, value, context, charges)
+    }
+
+    /// The function is called instead of `on_release` when an expired entry is released by the cache and all external
+    /// users.
+    ///
+    /// The default implementation forwards the expired entry to `on_release`.
+    fn on_expire(&self, key: K, value: V, context: CacheContext, charges: usize) {
+        self.on_release(key, value, context, charges)
+    }
+
+    /// The function is called instead of `on_release` when an explicitly removed entry is released by the cache and
+    /// all external users.
+    ///
+    /// The default implementation forwards the removed entry to `on_release`.
+    fn on_remove(&self, key: K, value: V, context: CacheContext, charges: usize) {
+        self.on_release(key, value, context, charges)
+    }
+
+    /// The function is called instead of `on_release` when a replaced entry is released by the cache and all external
+    /// users.
+    ///
+    /// The default implementation forwards the replaced entry to `on_release`.
+    fn on_replace(&self, key: K, value: V, context: CacheContext, charges: usize) {
+        self.on_release(key, value, context, charges)
+    }
 }
 
 pub struct DefaultCacheEventListener<K, V>(PhantomData<(K, V)>)
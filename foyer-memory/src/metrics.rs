@@ -12,33 +12,217 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 
-use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-#[derive(Debug, Default)]
+use foyer_common::metrics::get_metrics_registry;
+use prometheus::{
+    register_int_counter_vec_with_registry, register_int_gauge_vec_with_registry, IntCounter, IntCounterVec, IntGauge,
+    IntGaugeVec, Registry,
+};
+
+// TODO(MrCroxx): Use `LazyLock` after `lazy_cell` is stable.
+lazy_static::lazy_static! {
+    /// Multiple in-memory cache instances share the same global metrics with different label `foyer` name.
+    pub static ref METRICS: GlobalMetrics = GlobalMetrics::default();
+}
+
+#[derive(Debug)]
+pub struct GlobalMetrics {
+    op_total: IntCounterVec,
+    usage: IntGaugeVec,
+    capacity: IntGaugeVec,
+    shard_usage: IntGaugeVec,
+}
+
+impl Default for GlobalMetrics {
+    fn default() -> Self {
+        Self::new(get_metrics_registry())
+    }
+}
+
+impl GlobalMetrics {
+    pub fn new(registry: &Registry) -> Self {
+        let op_total = register_int_counter_vec_with_registry!(
+            "foyer_memory_op_total",
+            "foyer memory cache op total",
+            &["foyer", "op", "extra"],
+            registry,
+        )
+        .unwrap();
+
+        let usage = register_int_gauge_vec_with_registry!(
+            "foyer_memory_usage",
+            "foyer memory cache usage",
+            &["foyer"],
+            registry,
+        )
+        .unwrap();
+
+        let capacity = register_int_gauge_vec_with_registry!(
+            "foyer_memory_capacity",
+            "foyer memory cache capacity",
+            &["foyer"],
+            registry,
+        )
+        .unwrap();
+
+        let shard_usage = register_int_gauge_vec_with_registry!(
+            "foyer_memory_shard_usage",
+            "foyer memory cache shard usage",
+            &["foyer", "shard"],
+            registry,
+        )
+        .unwrap();
+
+        Self {
+            op_total,
+            usage,
+            capacity,
+            shard_usage,
+        }
+    }
+
+    pub fn foyer(&self, name: &str, shards: usize) -> Metrics {
+        Metrics::new(self, name, shards)
+    }
+}
+
+/// A counter of the cache instance, which is also added to the exported counter of the cache name.
+#[derive(Debug)]
+pub struct Counter {
+    local: AtomicUsize,
+    exported: IntCounter,
+}
+
+impl Counter {
+    fn new(exported: IntCounter) -> Self {
+        Self {
+            local: AtomicUsize::new(0),
+            exported,
+        }
+    }
+
+    pub fn fetch_add(&self, val: usize, order: Ordering) -> usize {
+        self.exported.inc_by(val as u64);
+        self.local.fetch_add(val, order)
+    }
+
+    pub fn load(&self, order: Ordering) -> usize {
+        self.local.load(order)
+    }
+}
+
+#[derive(Debug)]
 pub struct Metrics {
     /// successful inserts without replaces
-    pub insert: AtomicUsize,
+    pub insert: Counter,
     /// successful replaces
-    pub replace: AtomicUsize,
+    pub replace: Counter,
 
     /// get hits
-    pub hit: AtomicUsize,
+    pub hit: Counter,
     /// get misses
-    pub miss: AtomicUsize,
+    pub miss: Counter,
 
     /// fetches after cache miss with `entry` interface
-    pub fetch: AtomicUsize,
+    pub fetch: Counter,
     /// deduped fetches after cache miss with `entry` interface
-    pub queue: AtomicUsize,
+    pub queue: Counter,
+    /// background refreshes after cache hit with `entry` interface
+    pub refresh: Counter,
+    /// cached failures returned with `entry` interface
+    pub negative: Counter,
 
     /// successful removes
-    pub remove: AtomicUsize,
+    pub remove: Counter,
 
     /// evicts from the eviction container
-    pub evict: AtomicUsize,
+    pub evict: Counter,
     /// successful reinserts, only counts successful reinserts after evicted
-    pub reinsert: AtomicUsize,
+    pub reinsert: Counter,
+    /// expired entries removed from the cache
+    pub expire: Counter,
 
     /// released handles
-    pub release: AtomicUsize,
+    pub release: Counter,
+
+    /// accesses applied to the eviction container from the read buffers
+    pub read_buffer_drain: Counter,
+    /// accesses dropped because the read buffers are full or contended
+    pub read_buffer_drop: Counter,
+
+    /// exported usage of all shards
+    pub(crate) usage: IntGauge,
+    /// exported capacity of all shards
+    pub(crate) capacity: IntGauge,
+    /// exported usage of each shard
+    pub(crate) shard_usage: Vec<IntGauge>,
+}
+
+impl Metrics {
+    pub fn new(global: &GlobalMetrics, foyer: &str, shards: usize) -> Self {
+        let counter = |op: &str, extra: &str| Counter::new(global.op_total.with_label_values(&[foyer, op, extra]));
+
+        let insert = counter("insert", "inserted");
+        let replace = counter("insert", "replaced");
+
+        let hit = counter("lookup", "hit");
+        let miss = counter("lookup", "miss");
+
+        let fetch = counter("entry", "fetch");
+        let queue = counter("entry", "queue");
+        let refresh = counter("entry", "refresh");
+        let negative = counter("entry", "negative");
+
+        let remove = counter("remove", "");
+
+        let evict = counter("evict", "");
+        let reinsert = counter("reinsert", "");
+        let expire = counter("expire", "");
+
+        let release = counter("release", "");
+
+        let read_buffer_drain = counter("read_buffer", "drain");
+        let read_buffer_drop = counter("read_buffer", "drop");
+
+        let usage = global.usage.with_label_values(&[foyer]);
+        let capacity = global.capacity.with_label_values(&[foyer]);
+        let shard_usage = (0..shards)
+            .map(|shard| global.shard_usage.with_label_values(&[foyer, &shard.to_string()]))
+            .collect();
+
+        Self {
+            insert,
+            replace,
+
+            hit,
+            miss,
+
+            fetch,
+            queue,
+            refresh,
+            negative,
+
+            remove,
+
+            evict,
+            reinsert,
+            expire,
+
+            release,
+
+            read_buffer_drain,
+            read_buffer_drop,
+
+            usage,
+            capacity,
+            shard_usage,
+        }
+    }
+
+    /// Update the exported usage of the shard by the delta of the charges.
+    pub(crate) fn update_usage(&self, shard: usize, delta: i64) {
+        self.usage.add(delta);
+        self.shard_usage[shard].add(delta);
+    }
 }
@@ -15,8 +15,18 @@
 pub use crate::{
     cache::{Cache, CacheBuilder, CacheEntry, Entry, EntryState, EvictionConfig},
     context::CacheContext,
-    eviction::{fifo::FifoConfig, lfu::LfuConfig, lru::LruConfig, s3fifo::S3FifoConfig},
-    listener::{CacheEventListener, DefaultCacheEventListener},
+    eviction::{
+        arc::ArcConfig,
+        fifo::FifoConfig,
+        lfu::{LfuConfig, LfuDoorkeeperConfig, LfuHillClimbingConfig, LfuQueueSizes},
+        lru::LruConfig,
+        s3fifo::S3FifoConfig,
+        sieve::SieveConfig,
+    },
+    generic::{Negative, NegativeCacheConfig, RefreshPolicy, Weigher},
+    listener::{CacheEventListener, DefaultCacheEventListener, RemovalCause},
     metrics::Metrics,
+    snapshot::{SnapshotError, SnapshotResult},
 };
 pub use ahash::RandomState;
+pub use foyer_common::metrics::{get_metrics_registry, set_metrics_registry};
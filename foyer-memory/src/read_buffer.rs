@@ -0,0 +1,162 @@
+//  Copyright 2024 Foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    ptr::NonNull,
+    sync::atomic::{AtomicPtr, AtomicUsize, Ordering},
+};
+
+/// A bounded lossy multi-producer single-consumer ring buffer of handle pointers.
+///
+/// Producers push without locking and give up when the buffer is full or another producer wins the slot. The consumer
+/// must be exclusive, which is guaranteed by the shard lock of the cache.
+pub struct ReadBuffer<T> {
+    slots: Box<[AtomicPtr<T>]>,
+    mask: usize,
+
+    /// the position of the next slot to drain, only updated by the consumer
+    head: AtomicUsize,
+    /// the position of the next slot to push
+    tail: AtomicUsize,
+}
+
+impl<T> ReadBuffer<T> {
+    /// Create a read buffer, the capacity is rounded up to the next power of two.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1).next_power_of_two();
+        let slots = (0..capacity).map(|_| AtomicPtr::default()).collect();
+        Self {
+            slots,
+            mask: capacity - 1,
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Try to push the pointer into the buffer.
+    ///
+    /// Return the buffered count after pushing, or give the pointer back if the buffer is full or contended.
+    pub fn push(&self, ptr: NonNull<T>) -> Result<usize, NonNull<T>> {
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        let len = tail.wrapping_sub(head);
+        if len >= self.slots.len() {
+            return Err(ptr);
+        }
+        if self
+            .tail
+            .compare_exchange(tail, tail.wrapping_add(1), Ordering::AcqRel, Ordering::Relaxed)
+            .is_err()
+        {
+            return Err(ptr);
+        }
+        self.slots[tail & self.mask].store(ptr.as_ptr(), Ordering::Release);
+        Ok(len + 1)
+    }
+
+    /// Drain the pushed pointers in order.
+    ///
+    /// The draining stops at the first slot that is reserved but not written yet, and continues from it next time.
+    ///
+    /// # Safety
+    ///
+    /// There must be no concurrent drainers.
+    pub unsafe fn drain(&self, mut f: impl FnMut(NonNull<T>)) {
+        let mut head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Acquire);
+        while head != tail {
+            let ptr = self.slots[head & self.mask].swap(std::ptr::null_mut(), Ordering::Acquire);
+            let Some(ptr) = NonNull::new(ptr) else {
+                break;
+            };
+            f(ptr);
+            head = head.wrapping_add(1);
+        }
+        self.head.store(head, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, thread};
+
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[test]
+    fn test_read_buffer() {
+        let mut data = (0..8u64).collect_vec();
+        let ptrs = data.iter_mut().map(NonNull::from).collect_vec();
+
+        let buffer = ReadBuffer::new(3);
+        assert_eq!(buffer.capacity(), 4);
+
+        for (i, ptr) in ptrs.iter().take(4).enumerate() {
+            assert_eq!(buffer.push(*ptr), Ok(i + 1));
+        }
+        assert_eq!(buffer.push(ptrs[4]), Err(ptrs[4]));
+
+        let mut drained = vec![];
+        unsafe { buffer.drain(|ptr| drained.push(ptr)) };
+        assert_eq!(drained, ptrs[..4]);
+
+        // The slots are reused after draining.
+        for ptr in ptrs.iter().skip(4) {
+            buffer.push(*ptr).unwrap();
+        }
+        drained.clear();
+        unsafe { buffer.drain(|ptr| drained.push(ptr)) };
+        assert_eq!(drained, ptrs[4..]);
+    }
+
+    #[test]
+    fn test_read_buffer_concurrent() {
+        const THREADS: usize = 4;
+        const PUSHES: usize = 10000;
+
+        let buffer = Arc::new(ReadBuffer::<u64>::new(64));
+        let consumer = Arc::new(parking_lot::Mutex::new(0usize));
+
+        let handles = (0..THREADS)
+            .map(|_| {
+                let buffer = buffer.clone();
+                let consumer = consumer.clone();
+                thread::spawn(move || {
+                    let mut pushed = 0;
+                    for i in 1..=PUSHES {
+                        let ptr = NonNull::new(i as *mut u64).unwrap();
+                        if buffer.push(ptr).is_ok() {
+                            pushed += 1;
+                        }
+                        if i % 16 == 0 {
+                            let mut drained = consumer.lock();
+                            unsafe { buffer.drain(|_| *drained += 1) };
+                        }
+                    }
+                    pushed
+                })
+            })
+            .collect_vec();
+        let pushed: usize = handles.into_iter().map(|handle| handle.join().unwrap()).sum();
+
+        let mut drained = consumer.lock();
+        unsafe { buffer.drain(|_| *drained += 1) };
+        assert_eq!(*drained, pushed);
+    }
+}
@@ -0,0 +1,284 @@
+//  Copyright 2024 Foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! Snapshot file of the in-memory cache.
+//!
+//! A snapshot file is laid out as a header followed by the body:
+//!
+//! ```plain
+//! | magic (u64) | version (u32) | eviction (str) | entries (u64) | checksum (u64) | body |
+//! ```
+//!
+//! The body is the encoded entries of all shards, interleaved by their positions in the order dumped by the eviction
+//! containers. The checksum is calculated on the body.
+//!
+//! Both the writer and the reader stream the body, so the snapshot is never buffered in memory as a whole.
+
+use std::{
+    ffi::OsString,
+    fs::File,
+    hash::Hasher,
+    io::{BufReader, BufWriter, Seek, SeekFrom, Write},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bincode::Options;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use twox_hash::XxHash64;
+
+use crate::context::CacheContext;
+
+const MAGIC: u64 = 0x666f_7965_725f_736e;
+const VERSION: u32 = 1;
+
+/// The header is small, the limit prevents a corrupted header from allocating a huge buffer.
+const HEADER_LIMIT: u64 = 4096;
+
+#[derive(thiserror::Error, Debug)]
+pub enum SnapshotError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serde error: {0}")]
+    Serde(#[from] bincode::Error),
+    #[error("invalid snapshot: {0}")]
+    Invalid(String),
+}
+
+pub type SnapshotResult<T> = core::result::Result<T, SnapshotError>;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotHeader {
+    magic: u64,
+    version: u32,
+    eviction: String,
+    entries: u64,
+    checksum: u64,
+}
+
+impl SnapshotHeader {
+    /// The fixed-size integer encoding keeps the header size unchanged when it is rewritten with the final counts.
+    fn options() -> impl Options {
+        bincode::options().with_fixint_encoding().with_limit(HEADER_LIMIT)
+    }
+}
+
+/// An entry of the snapshot.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotEntry<K, V, S> {
+    pub key: K,
+    pub value: V,
+    pub charge: usize,
+    pub context: CacheContext,
+    /// the remaining time-to-live, `None` means never expire
+    pub ttl: Option<Duration>,
+    /// the state dumped by the eviction container, `None` if the entry is not in the eviction container
+    pub state: Option<S>,
+}
+
+/// Calculates the checksum of the bytes written through it.
+#[derive(Debug)]
+struct ChecksumWriter<W> {
+    inner: W,
+    hasher: XxHash64,
+}
+
+impl<W> ChecksumWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: XxHash64::with_seed(0),
+        }
+    }
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        self.hasher.write(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[derive(Debug)]
+pub struct SnapshotWriter {
+    writer: ChecksumWriter<BufWriter<File>>,
+    header: SnapshotHeader,
+    path: PathBuf,
+    tmp: OsString,
+    finished: bool,
+}
+
+impl SnapshotWriter {
+    /// Create the snapshot writer.
+    ///
+    /// The snapshot is written to a temporary file first and then renamed to `path` by [`SnapshotWriter::finish`], so
+    /// an existing snapshot is never left half overwritten.
+    pub fn create(path: impl AsRef<Path>, eviction: &str) -> SnapshotResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+
+        let header = SnapshotHeader {
+            magic: MAGIC,
+            version: VERSION,
+            eviction: eviction.to_string(),
+            entries: 0,
+            checksum: 0,
+        };
+
+        // Reserve the header, it is rewritten with the final counts and checksum on finish.
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        SnapshotHeader::options().serialize_into(&mut writer, &header)?;
+
+        Ok(Self {
+            writer: ChecksumWriter::new(writer),
+            header,
+            path,
+            tmp,
+            finished: false,
+        })
+    }
+
+    pub fn append<K, V, S>(&mut self, entry: &SnapshotEntry<&K, &V, S>) -> SnapshotResult<()>
+    where
+        K: Serialize,
+        V: Serialize,
+        S: Serialize,
+    {
+        bincode::serialize_into(&mut self.writer, entry)?;
+        self.header.entries += 1;
+        Ok(())
+    }
+
+    /// Write the header, sync the snapshot file and rename it to the target path.
+    pub fn finish(mut self) -> SnapshotResult<()> {
+        self.header.checksum = self.writer.hasher.finish();
+
+        let writer = &mut self.writer.inner;
+        writer.seek(SeekFrom::Start(0))?;
+        SnapshotHeader::options().serialize_into(&mut *writer, &self.header)?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+
+        std::fs::rename(&self.tmp, &self.path)?;
+        self.finished = true;
+        Ok(())
+    }
+}
+
+impl Drop for SnapshotWriter {
+    fn drop(&mut self) {
+        // Remove the temporary file of the unfinished snapshot.
+        if !self.finished {
+            let _ = std::fs::remove_file(&self.tmp);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SnapshotReader {
+    reader: BufReader<File>,
+    offset: u64,
+    entries: u64,
+}
+
+impl SnapshotReader {
+    /// Open the snapshot file and verify it.
+    pub fn open(path: impl AsRef<Path>, eviction: &str) -> SnapshotResult<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let header: SnapshotHeader = SnapshotHeader::options().deserialize_from(&mut reader)?;
+        if header.magic != MAGIC {
+            return Err(SnapshotError::Invalid(format!(
+                "magic mismatch, expected: {MAGIC}, got: {}",
+                header.magic
+            )));
+        }
+        if header.version != VERSION {
+            return Err(SnapshotError::Invalid(format!(
+                "version mismatch, expected: {VERSION}, got: {}",
+                header.version
+            )));
+        }
+        if header.eviction != eviction {
+            return Err(SnapshotError::Invalid(format!(
+                "eviction mismatch, expected: {eviction}, got: {}",
+                header.eviction
+            )));
+        }
+
+        let offset = reader.stream_position()?;
+        let mut hasher = ChecksumWriter::new(std::io::sink());
+        std::io::copy(&mut reader, &mut hasher)?;
+        let checksum = hasher.hasher.finish();
+        if checksum != header.checksum {
+            return Err(SnapshotError::Invalid(format!(
+                "checksum mismatch, expected: {}, got: {checksum}",
+                header.checksum
+            )));
+        }
+
+        Ok(Self {
+            reader,
+            offset,
+            entries: header.entries,
+        })
+    }
+
+    /// Decode the entries in the order they are appended.
+    pub fn entries<K, V, S>(mut self) -> SnapshotResult<SnapshotEntries<K, V, S>>
+    where
+        K: DeserializeOwned,
+        V: DeserializeOwned,
+        S: DeserializeOwned,
+    {
+        self.reader.seek(SeekFrom::Start(self.offset))?;
+        Ok(SnapshotEntries {
+            reader: self.reader,
+            remaining: self.entries,
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// Iterator of the entries decoded from the snapshot file.
+#[derive(Debug)]
+pub struct SnapshotEntries<K, V, S> {
+    reader: BufReader<File>,
+    remaining: u64,
+    _marker: PhantomData<(K, V, S)>,
+}
+
+impl<K, V, S> Iterator for SnapshotEntries<K, V, S>
+where
+    K: DeserializeOwned,
+    V: DeserializeOwned,
+    S: DeserializeOwned,
+{
+    type Item = SnapshotResult<SnapshotEntry<K, V, S>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(bincode::deserialize_from(&mut self.reader).map_err(SnapshotError::from))
+    }
+}
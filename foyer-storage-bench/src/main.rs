@@ -367,6 +367,9 @@ async fn main() {
         protected_capacity_ratio: 0.8,
         cmsketch_eps: 0.001,
         cmsketch_confidence: 0.9,
+        hill_climbing: None,
+        doorkeeper: None,
+        aging_sample_ratio: None,
     });
 
     let device_config = FsDeviceConfig {
@@ -412,6 +415,7 @@ async fn main() {
         recover_concurrency: args.recover_concurrency,
         clean_region_threshold,
         compression,
+        tombstone_log_config: None,
     };
 
     let config = if args.runtime {
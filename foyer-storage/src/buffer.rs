@@ -189,6 +189,7 @@ where
             value,
             sequence,
             compression,
+            expire_at,
         }: Entry<K, V>,
     ) -> BufferResult<Either<Vec<PositionedEntry<K, V>>, Entry<K, V>>> {
         // Notify caller to rotate buffer if there is not enough space for the entry.
@@ -206,6 +207,7 @@ where
                 value,
                 sequence,
                 compression,
+                expire_at,
             }));
         }
 
@@ -262,6 +264,7 @@ where
             sequence,
             compression,
             checksum,
+            expire_at,
         };
         header.write(&mut self.buffer[cursor..cursor + EntryHeader::serialized_len()]);
 
@@ -273,6 +276,7 @@ where
                 value,
                 sequence,
                 compression,
+                expire_at,
             }));
         }
 
@@ -287,6 +291,7 @@ where
                 value,
                 sequence,
                 compression,
+                expire_at,
             },
             region: self.region.unwrap(),
             offset: self.offset + old,
@@ -317,6 +322,7 @@ mod tests {
             value: vec![b'x'; size],
             compression: Compression::None,
             sequence: 0,
+            expire_at: None,
         }
     }
 
@@ -16,8 +16,11 @@ use std::{
     borrow::Borrow,
     collections::hash_map::{Entry, HashMap},
     hash::{Hash, Hasher},
-    sync::Arc,
-    time::Instant,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Instant, SystemTime},
 };
 
 use foyer_common::code::{StorageKey, StorageValue};
@@ -32,6 +35,9 @@ use crate::{
 
 pub type Sequence = u64;
 
+/// The bits of the removal watermark slots of each `items` shard.
+const REMOVED_SLOT_BITS: usize = 10;
+
 #[derive(Debug, Clone)]
 pub enum Index<K, V>
 where
@@ -50,6 +56,7 @@ where
 {
     sequence: Sequence,
     index: Index<K, V>,
+    expire_at: Option<SystemTime>,
 
     inserted: Option<Instant>,
 }
@@ -59,10 +66,11 @@ where
     K: StorageKey,
     V: StorageValue,
 {
-    pub fn new(sequence: Sequence, index: Index<K, V>) -> Self {
+    pub fn new(sequence: Sequence, index: Index<K, V>, expire_at: Option<SystemTime>) -> Self {
         Self {
             sequence,
             index,
+            expire_at,
             inserted: None,
         }
     }
@@ -75,6 +83,15 @@ where
         &self.index
     }
 
+    pub fn expire_at(&self) -> Option<SystemTime> {
+        self.expire_at
+    }
+
+    /// Return `true` if the entry has a ttl and is expired.
+    pub fn is_expired(&self) -> bool {
+        matches!(self.expire_at, Some(expire_at) if expire_at <= SystemTime::now())
+    }
+
     pub fn consume(self) -> (Sequence, Index<K, V>) {
         (self.sequence, self.index)
     }
@@ -95,6 +112,15 @@ where
     /// Sharded by region id.
     regions: Vec<Mutex<HashMap<K, u64>>>,
 
+    /// Items with a lower sequence are cleared and will not be inserted.
+    watermark: AtomicU64,
+
+    /// The sequences of the latest removals, indexed by the low bits of the key hash.
+    ///
+    /// The low bits of the slot index are the index of the `items` shard, so the slots are updated and checked under
+    /// the shard lock.
+    removed: Vec<AtomicU64>,
+
     metrics: Arc<Metrics>,
 }
 
@@ -106,28 +132,66 @@ where
     pub fn new(regions: usize, bits: usize, metrics: Arc<Metrics>) -> Self {
         let infos = (0..1 << bits).map(|_| RwLock::new(HashMap::new())).collect_vec();
         let regions = (0..regions).map(|_| Mutex::new(HashMap::new())).collect_vec();
+        let removed = (0..1 << (bits + REMOVED_SLOT_BITS))
+            .map(|_| AtomicU64::new(0))
+            .collect_vec();
         Self {
             bits,
             items: infos,
             regions,
+            watermark: AtomicU64::new(0),
+            removed,
 
             metrics,
         }
     }
 
-    pub fn insert(&self, key: K, mut item: Item<K, V>) {
-        // TODO(MrCroxx): compare sequence.
-
+    /// Insert the item if there is no item of the key with a larger sequence.
+    ///
+    /// Return `true` if the item is inserted. Stale items from slow flushers, reclaimers or the recovery of older
+    /// regions are dropped.
+    ///
+    /// Items older than the latest removal of a key in the same removal slot are dropped too, unless they update the
+    /// index of the item that is still indexed. So a removed entry is never brought back by its flush or reinsertion.
+    /// Entries of other keys in the slot may be dropped as well, which is safe for a cache.
+    pub fn insert(&self, key: K, mut item: Item<K, V>) -> bool {
+        // Track the key in the region before inserting the item, so the item can always be taken by `take_region`.
+        //
+        // Never overwrite the sequence of a newer item in the same region, otherwise `take_region` will miss it.
         if let Index::Region { view } = &item.index {
-            self.regions[*view.id() as usize]
-                .lock()
-                .insert(key.clone(), item.sequence);
+            match self.regions[*view.id() as usize].lock().entry(key.clone()) {
+                Entry::Occupied(o) if *o.get() > item.sequence => {}
+                Entry::Occupied(mut o) => {
+                    o.insert(item.sequence);
+                }
+                Entry::Vacant(v) => {
+                    v.insert(item.sequence);
+                }
+            }
         };
 
-        let shard = self.shard(&key);
-        // TODO(MrCroxx): handle old key?
+        let hash = self.hash(&key);
+        let shard = self.shard_of(hash);
         let old = {
             let mut guard = self.items[shard].write();
+            // Check under the shard lock to make sure no cleared item is inserted after `clear`.
+            if item.sequence < self.watermark.load(Ordering::Acquire) {
+                self.metrics.inner_op_count_insert_catalog_cleared.inc();
+                return false;
+            }
+            let old = guard.get(&key);
+            if let Some(old) = old {
+                if old.sequence > item.sequence {
+                    self.metrics.inner_op_count_insert_catalog_stale.inc();
+                    return false;
+                }
+            }
+            if item.sequence < self.removed[self.slot_of(hash)].load(Ordering::Relaxed)
+                && !matches!(old, Some(old) if old.sequence == item.sequence)
+            {
+                self.metrics.inner_op_count_insert_catalog_stale.inc();
+                return false;
+            }
             item.inserted = Some(Instant::now());
             guard.insert(key.clone(), item)
         };
@@ -139,6 +203,7 @@ where
                     .observe(old.inserted.unwrap().elapsed().as_secs_f64());
             }
         }
+        true
     }
 
     pub fn lookup<Q>(&self, key: &Q) -> Option<Item<K, V>>
@@ -150,20 +215,53 @@ where
         self.items[shard].read().get(key).cloned()
     }
 
-    pub fn remove<Q>(&self, key: &Q) -> Option<Item<K, V>>
+    /// Remove the item of the key, and drop the following inserts of the items of the key older than `sequence`.
+    pub fn remove<Q>(&self, key: &Q, sequence: Sequence) -> Option<Item<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hash(key);
+        let shard = self.shard_of(hash);
+        let info = {
+            let mut guard = self.items[shard].write();
+            self.removed[self.slot_of(hash)].fetch_max(sequence, Ordering::Relaxed);
+            guard.remove(key)
+        };
+        self.untrack(key, &info);
+        info
+    }
+
+    /// Remove the item of the key only if it has the given sequence, so a newer item inserted concurrently is kept.
+    pub fn remove_if_sequence<Q>(&self, key: &Q, sequence: Sequence) -> Option<Item<K, V>>
     where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
     {
         let shard = self.shard(key);
-        let info: Option<Item<K, V>> = self.items[shard].write().remove(key);
+        let info: Option<Item<K, V>> = {
+            let mut guard = self.items[shard].write();
+            match guard.get(key) {
+                Some(item) if item.sequence == sequence => guard.remove(key),
+                _ => None,
+            }
+        };
+        self.untrack(key, &info);
+        info
+    }
+
+    /// Stop tracking the removed item in its region.
+    fn untrack<Q>(&self, key: &Q, info: &Option<Item<K, V>>)
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
         // TODO(MrCroxx): Use `let_chains` here after it is stable.
-        if let Some(info) = &info {
+        if let Some(info) = info {
             if let Index::Region { view } = &info.index {
                 self.regions[*view.id() as usize].lock().remove(key);
             }
         }
-        info
     }
 
     pub fn take_region(&self, region: &RegionId) -> Vec<(K, Item<K, V>)> {
@@ -186,6 +284,11 @@ where
         items
     }
 
+    /// Reject the following inserts of items with a sequence lower than `watermark`.
+    pub fn set_watermark(&self, watermark: Sequence) {
+        self.watermark.fetch_max(watermark, Ordering::AcqRel);
+    }
+
     pub fn clear(&self) {
         for shard in self.items.iter() {
             shard.write().clear();
@@ -200,10 +303,18 @@ where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
     {
-        self.hash(key) as usize & ((1 << self.bits) - 1)
+        self.shard_of(self.hash(key))
+    }
+
+    fn shard_of(&self, hash: u64) -> usize {
+        hash as usize & ((1 << self.bits) - 1)
+    }
+
+    fn slot_of(&self, hash: u64) -> usize {
+        hash as usize & ((1 << (self.bits + REMOVED_SLOT_BITS)) - 1)
     }
 
-    fn hash<Q>(&self, key: &Q) -> u64
+    pub fn hash<Q>(&self, key: &Q) -> u64
     where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
@@ -213,3 +324,188 @@ where
         hasher.finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{device::tests::NullDevice, metrics::METRICS, region::Region};
+
+    fn catalog() -> Catalog<u64, Vec<u8>> {
+        Catalog::new(4, 2, Arc::new(METRICS.foyer("test-catalog")))
+    }
+
+    fn region(id: RegionId, sequence: Sequence) -> Item<u64, Vec<u8>> {
+        let view = Region::new(id, NullDevice::new(4096)).view(0, 4096);
+        Item::new(sequence, Index::Region { view }, None)
+    }
+
+    fn inflight(sequence: Sequence) -> Item<u64, Vec<u8>> {
+        Item::new(
+            sequence,
+            Index::Inflight {
+                key: 1,
+                value: vec![sequence as u8],
+            },
+            None,
+        )
+    }
+
+    #[test]
+    fn test_catalog_late_flush() {
+        let catalog = catalog();
+
+        assert!(catalog.insert(1, inflight(1)));
+        assert!(catalog.insert(1, inflight(2)));
+
+        // The flusher of the older entry finishes after the newer entry is inserted.
+        assert!(!catalog.insert(1, region(0, 1)));
+        assert_eq!(*catalog.lookup(&1).unwrap().sequence(), 2);
+
+        // Update the index of the entry with the same sequence.
+        assert!(catalog.insert(1, region(1, 2)));
+        let item = catalog.lookup(&1).unwrap();
+        assert_eq!(*item.sequence(), 2);
+        assert!(matches!(item.index(), Index::Region { view } if *view.id() == 1));
+    }
+
+    #[test]
+    fn test_catalog_reinsertion() {
+        let catalog = catalog();
+
+        assert!(catalog.insert(1, region(0, 1)));
+        let items = catalog.take_region(&0);
+        assert_eq!(items.len(), 1);
+
+        // A newer entry is inserted while the reclaimer is reinserting the old one with its original sequence.
+        assert!(catalog.insert(1, region(1, 2)));
+        assert!(!catalog.insert(1, region(2, 1)));
+        assert_eq!(*catalog.lookup(&1).unwrap().sequence(), 2);
+        assert!(catalog.take_region(&2).is_empty());
+    }
+
+    #[test]
+    fn test_catalog_out_of_order_recovery() {
+        let catalog = catalog();
+
+        // Regions are recovered concurrently, the newer region may be recovered first.
+        assert!(catalog.insert(1, region(2, 3)));
+        assert!(!catalog.insert(1, region(0, 1)));
+        assert!(!catalog.insert(1, region(1, 2)));
+        assert_eq!(*catalog.lookup(&1).unwrap().sequence(), 3);
+
+        assert!(catalog.take_region(&0).is_empty());
+        assert!(catalog.take_region(&1).is_empty());
+        let items = catalog.take_region(&2);
+        assert_eq!(items.len(), 1);
+        assert_eq!(*items[0].1.sequence(), 3);
+        assert!(catalog.lookup(&1).is_none());
+    }
+
+    #[test]
+    fn test_catalog_remove_if_sequence() {
+        let catalog = catalog();
+
+        assert!(catalog.insert(1, region(0, 1)));
+
+        // A newer item is inserted after the old one is looked up, removing the old one must keep the newer one.
+        assert!(catalog.insert(1, inflight(2)));
+        assert!(catalog.remove_if_sequence(&1, 1).is_none());
+        assert_eq!(*catalog.lookup(&1).unwrap().sequence(), 2);
+
+        assert_eq!(*catalog.remove_if_sequence(&1, 2).unwrap().sequence(), 2);
+        assert!(catalog.lookup(&1).is_none());
+    }
+
+    #[test]
+    fn test_catalog_remove_during_reclaim() {
+        let catalog = catalog();
+
+        assert!(catalog.insert(1, region(0, 1)));
+        assert_eq!(catalog.take_region(&0).len(), 1);
+
+        // The key is removed after its item is taken by the reclaimer, the reinsertion with the original sequence is
+        // dropped, both the inflight item and the flushed one.
+        assert!(catalog.remove(&1, 2).is_none());
+        assert!(!catalog.insert(1, inflight(1)));
+        assert!(!catalog.insert(1, region(1, 1)));
+        assert!(catalog.lookup(&1).is_none());
+
+        // The entries inserted after the removal are not affected.
+        assert!(catalog.insert(1, inflight(3)));
+        assert!(catalog.insert(1, region(1, 3)));
+        assert_eq!(*catalog.lookup(&1).unwrap().sequence(), 3);
+    }
+
+    #[test]
+    fn test_catalog_remove_before_flush() {
+        let catalog = catalog();
+
+        // The entry is removed before it is flushed, the flushed index must not bring it back.
+        assert!(catalog.insert(1, inflight(1)));
+        assert_eq!(*catalog.remove(&1, 2).unwrap().sequence(), 1);
+        assert!(!catalog.insert(1, region(0, 1)));
+        assert!(catalog.lookup(&1).is_none());
+        assert!(catalog.take_region(&0).is_empty());
+    }
+
+    const KEYS: u64 = 1000;
+
+    #[test]
+    fn test_catalog_concurrent_flush_reclaim() {
+        let catalog = catalog();
+        let barrier = std::sync::Barrier::new(2);
+
+        // The flusher writes the newer entries to region 0, while the reclaimer reinserts the older ones to region 1.
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                barrier.wait();
+                for key in 0..KEYS {
+                    catalog.insert(key, inflight(key * 2 + 1));
+                    catalog.insert(key, region(0, key * 2 + 1));
+                }
+            });
+            s.spawn(|| {
+                barrier.wait();
+                for key in 0..KEYS {
+                    catalog.insert(key, region(1, key * 2));
+                }
+            });
+        });
+
+        for key in 0..KEYS {
+            let item = catalog.lookup(&key).unwrap();
+            assert_eq!(*item.sequence(), key * 2 + 1);
+            assert!(matches!(item.index(), Index::Region { view } if *view.id() == 0));
+        }
+        assert!(catalog.take_region(&1).is_empty());
+        assert_eq!(catalog.take_region(&0).len(), KEYS as usize);
+    }
+
+    #[test]
+    fn test_catalog_parallel_recovery() {
+        let catalog = catalog();
+        let barrier = std::sync::Barrier::new(4);
+
+        // Every region holds a version of each key, the versions in the regions with larger ids are newer.
+        std::thread::scope(|s| {
+            for id in 0..4 {
+                let catalog = &catalog;
+                let barrier = &barrier;
+                s.spawn(move || {
+                    barrier.wait();
+                    for key in 0..KEYS {
+                        catalog.insert(key, region(id, id as Sequence * KEYS + key));
+                    }
+                });
+            }
+        });
+
+        for key in 0..KEYS {
+            assert_eq!(*catalog.lookup(&key).unwrap().sequence(), 3 * KEYS + key);
+        }
+        for id in 0..3 {
+            assert!(catalog.take_region(&id).is_empty());
+        }
+        assert_eq!(catalog.take_region(&3).len(), KEYS as usize);
+    }
+}
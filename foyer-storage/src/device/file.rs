@@ -0,0 +1,728 @@
+//  Copyright 2024 Foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! Device on a single preallocated file or a raw block device.
+//!
+//! # Layout
+//!
+//! | superblock (align) | meta slot A (meta size) | meta slot B (meta size) | region 0 | region 1 | ... |
+//!
+//! The superblock records the layout and is validated on reopen. Metadata blobs are written to the two meta slots
+//! alternately, the slot with the larger valid sequence wins on reopen, so a torn metadata write never loses the
+//! previous metadata.
+
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::{Seek, SeekFrom},
+    os::{
+        fd::{AsRawFd, BorrowedFd, RawFd},
+        unix::fs::FileTypeExt,
+    },
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use allocator_api2::vec::Vec as VecA;
+use bytes::{Buf, BufMut};
+use foyer_common::{bits, fs::freespace, range::RangeBoundsExt};
+use tokio::sync::Mutex;
+
+use super::{allocator::AlignedAllocator, asyncify, Device, DeviceError, DeviceResult, IoBuf, IoBufMut, IoRange};
+use crate::{generic::checksum, region::RegionId};
+
+const SUPERBLOCK_MAGIC: u64 = 0x20240421;
+const SUPERBLOCK_VERSION: u32 = 1;
+
+const META_MAGIC: u64 = 0x20240422;
+
+#[derive(Debug)]
+pub struct FileDeviceConfigBuilder {
+    pub path: PathBuf,
+    pub capacity: Option<usize>,
+    pub region_size: Option<usize>,
+    pub meta_size: Option<usize>,
+    pub align: Option<usize>,
+    pub io_size: Option<usize>,
+}
+
+impl FileDeviceConfigBuilder {
+    const DEFAULT_ALIGN: usize = 4096;
+    const DEFAULT_IO_SIZE: usize = 16 * 1024;
+    const DEFAULT_REGION_SIZE: usize = 64 * 1024 * 1024;
+    const DEFAULT_META_SIZE: usize = 1024 * 1024;
+
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().into();
+        Self {
+            path,
+            capacity: None,
+            region_size: None,
+            meta_size: None,
+            align: None,
+            io_size: None,
+        }
+    }
+
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    pub fn with_region_size(mut self, region_size: usize) -> Self {
+        self.region_size = Some(region_size);
+        self
+    }
+
+    pub fn with_meta_size(mut self, meta_size: usize) -> Self {
+        self.meta_size = Some(meta_size);
+        self
+    }
+
+    pub fn with_align(mut self, align: usize) -> Self {
+        self.align = Some(align);
+        self
+    }
+
+    pub fn with_io_size(mut self, io_size: usize) -> Self {
+        self.io_size = Some(io_size);
+        self
+    }
+
+    /// Build the config.
+    ///
+    /// If `capacity` is not set, the whole block device is used, or the size of the existing file, or 80% of the free
+    /// space of the file system for a new file.
+    pub fn build(self) -> FileDeviceConfig {
+        let align_v = |value: usize, align: usize| value - value % align;
+
+        let path = self.path;
+
+        let align = self.align.unwrap_or(Self::DEFAULT_ALIGN);
+
+        let capacity = self.capacity.unwrap_or_else(|| Self::detect_capacity(&path));
+        let capacity = align_v(capacity, align);
+
+        let meta_size = bits::align_up(align, self.meta_size.unwrap_or(Self::DEFAULT_META_SIZE).max(align));
+
+        let region_size = self
+            .region_size
+            .unwrap_or(Self::DEFAULT_REGION_SIZE)
+            .clamp(align, capacity.saturating_sub(align + 2 * meta_size).max(align));
+        let region_size = align_v(region_size, align);
+
+        let io_size = self.io_size.unwrap_or(Self::DEFAULT_IO_SIZE).max(align);
+        let io_size = align_v(io_size, align);
+
+        FileDeviceConfig {
+            path,
+            capacity,
+            region_size,
+            meta_size,
+            align,
+            io_size,
+        }
+    }
+
+    fn detect_capacity(path: &Path) -> usize {
+        match std::fs::metadata(path) {
+            Ok(metadata) if metadata.file_type().is_block_device() => FileDevice::block_device_size(path).unwrap(),
+            Ok(metadata) if metadata.len() > 0 => metadata.len() as usize,
+            _ => {
+                let dir = path
+                    .parent()
+                    .filter(|dir| !dir.as_os_str().is_empty())
+                    .unwrap_or(Path::new("."));
+                freespace(dir).unwrap() / 10 * 8
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FileDeviceConfig {
+    /// path of the file or the raw block device
+    pub path: PathBuf,
+
+    /// total size of the file or the used part of the block device, including the superblock and the meta slots
+    pub capacity: usize,
+
+    /// must be multipliers of `align`
+    pub region_size: usize,
+
+    /// size of each meta slot, must be multipliers of `align`
+    pub meta_size: usize,
+
+    /// io block alignment, must be pow of 2
+    pub align: usize,
+
+    /// recommended optimized io block size
+    pub io_size: usize,
+}
+
+impl FileDeviceConfig {
+    pub fn assert(&self) {
+        assert!(self.align.is_power_of_two());
+        assert_eq!(self.capacity % self.align, 0);
+        assert_eq!(self.region_size % self.align, 0);
+        assert_eq!(self.meta_size % self.align, 0);
+        assert!(self.regions() > 0, "no space for any region: {self:?}");
+    }
+
+    fn data_offset(&self) -> usize {
+        self.align + 2 * self.meta_size
+    }
+
+    fn regions(&self) -> usize {
+        self.capacity.saturating_sub(self.data_offset()) / self.region_size
+    }
+}
+
+/// Layout persisted at the head of the device.
+///
+/// Format:
+///
+/// | magic (8B) | version (4B) | align (8B) | region size (8B) | regions (8B) | meta size (8B) | checksum (8B) |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Superblock {
+    align: u64,
+    region_size: u64,
+    regions: u64,
+    meta_size: u64,
+}
+
+impl Superblock {
+    const fn serialized_len() -> usize {
+        8 + 4 + 8 + 8 + 8 + 8 + 8
+    }
+
+    fn write(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(Self::serialized_len());
+        buf.put_u64(SUPERBLOCK_MAGIC);
+        buf.put_u32(SUPERBLOCK_VERSION);
+        buf.put_u64(self.align);
+        buf.put_u64(self.region_size);
+        buf.put_u64(self.regions);
+        buf.put_u64(self.meta_size);
+        buf.put_u64(checksum(&buf));
+        buf
+    }
+
+    /// Return `None` if there is no valid superblock.
+    fn read(buf: &[u8]) -> Option<Self> {
+        let (data, mut tail) = buf[..Self::serialized_len()].split_at(Self::serialized_len() - 8);
+        if checksum(data) != tail.get_u64() {
+            return None;
+        }
+        let mut data = data;
+        if data.get_u64() != SUPERBLOCK_MAGIC || data.get_u32() != SUPERBLOCK_VERSION {
+            return None;
+        }
+        Some(Self {
+            align: data.get_u64(),
+            region_size: data.get_u64(),
+            regions: data.get_u64(),
+            meta_size: data.get_u64(),
+        })
+    }
+}
+
+/// All metadata blobs, persisted to the meta slots as a whole.
+///
+/// Format:
+///
+/// | magic (8B) | sequence (8B) | len (8B) | [ name len (4B) | name | blob len (4B) | blob ] * N | checksum (8B) |
+#[derive(Debug, Default)]
+struct Metas {
+    blobs: BTreeMap<String, Vec<u8>>,
+    sequence: u64,
+}
+
+impl Metas {
+    fn write(&self, sequence: u64) -> Vec<u8> {
+        let len = self
+            .blobs
+            .iter()
+            .map(|(name, blob)| 4 + name.len() + 4 + blob.len())
+            .sum::<usize>();
+        let mut buf = Vec::with_capacity(8 + 8 + 8 + len + 8);
+        buf.put_u64(META_MAGIC);
+        buf.put_u64(sequence);
+        buf.put_u64(len as u64);
+        for (name, blob) in self.blobs.iter() {
+            buf.put_u32(name.len() as u32);
+            buf.put_slice(name.as_bytes());
+            buf.put_u32(blob.len() as u32);
+            buf.put_slice(blob);
+        }
+        buf.put_u64(checksum(&buf));
+        buf
+    }
+
+    /// Return `None` if there is no valid metadata in the slot.
+    fn read(buf: &[u8]) -> Option<Self> {
+        let mut header = buf.get(..24)?;
+        if header.get_u64() != META_MAGIC {
+            return None;
+        }
+        let sequence = header.get_u64();
+        let len = header.get_u64() as usize;
+
+        let data = buf.get(..24 + len)?;
+        let mut tail = buf.get(24 + len..24 + len + 8)?;
+        if checksum(data) != tail.get_u64() {
+            return None;
+        }
+
+        let mut data = &data[24..];
+        let mut blobs = BTreeMap::new();
+        while data.has_remaining() {
+            let len = data.get_u32() as usize;
+            let name = String::from_utf8(data[..len].to_vec()).ok()?;
+            data.advance(len);
+            let len = data.get_u32() as usize;
+            let blob = data[..len].to_vec();
+            data.advance(len);
+            blobs.insert(name, blob);
+        }
+        Some(Self { blobs, sequence })
+    }
+}
+
+#[derive(Debug)]
+struct FileDeviceInner {
+    config: FileDeviceConfig,
+
+    file: File,
+
+    metas: Mutex<Metas>,
+
+    io_buffer_allocator: AlignedAllocator,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileDevice {
+    inner: Arc<FileDeviceInner>,
+}
+
+impl Device for FileDevice {
+    type Config = FileDeviceConfig;
+    type IoBufferAllocator = AlignedAllocator;
+
+    async fn open(config: FileDeviceConfig) -> DeviceResult<Self> {
+        Self::open(config).await
+    }
+
+    async fn write<B>(&self, buf: B, range: impl IoRange, region: RegionId, offset: usize) -> (DeviceResult<usize>, B)
+    where
+        B: IoBuf,
+    {
+        let region_size = self.inner.config.region_size;
+
+        let range = range.bounds(0..buf.as_ref().len());
+        let len = RangeBoundsExt::size(&range).unwrap();
+
+        assert!(
+            offset + len <= region_size,
+            "offset ({offset}) + len ({len}) <= region size ({region_size})"
+        );
+
+        let fd = self.fd();
+        let offset = self.offset(region, offset);
+
+        asyncify(move || {
+            let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+            let res = nix::sys::uio::pwrite(fd, &buf.as_ref()[range], offset as i64).map_err(DeviceError::from);
+            (res, buf)
+        })
+        .await
+    }
+
+    async fn read<B>(
+        &self,
+        mut buf: B,
+        range: impl IoRange,
+        region: RegionId,
+        offset: usize,
+    ) -> (DeviceResult<usize>, B)
+    where
+        B: IoBufMut,
+    {
+        let region_size = self.inner.config.region_size;
+
+        let range = range.bounds(0..buf.as_ref().len());
+        let len = RangeBoundsExt::size(&range).unwrap();
+
+        assert!(
+            offset + len <= region_size,
+            "offset ({offset}) + len ({len}) <= region size ({region_size})"
+        );
+
+        let fd = self.fd();
+        let offset = self.offset(region, offset);
+
+        asyncify(move || {
+            let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+            let res = nix::sys::uio::pread(fd, &mut buf.as_mut()[range], offset as i64).map_err(DeviceError::from);
+            (res, buf)
+        })
+        .await
+    }
+
+    async fn flush(&self) -> DeviceResult<()> {
+        let fd = self.fd();
+        asyncify(move || nix::unistd::fsync(fd).map_err(DeviceError::from)).await
+    }
+
+    async fn read_meta(&self, name: &str) -> DeviceResult<Option<Vec<u8>>> {
+        Ok(self.inner.metas.lock().await.blobs.get(name).cloned())
+    }
+
+    async fn write_meta(&self, name: &str, buf: Vec<u8>) -> DeviceResult<()> {
+        let mut metas = self.inner.metas.lock().await;
+
+        let old = metas.blobs.insert(name.to_string(), buf);
+        let sequence = metas.sequence + 1;
+        let data = metas.write(sequence);
+        let meta_size = self.inner.config.meta_size;
+        if data.len() > meta_size {
+            match old {
+                Some(old) => metas.blobs.insert(name.to_string(), old),
+                None => metas.blobs.remove(name),
+            };
+            return Err(DeviceError::Other(
+                format!("metadata size ({}) exceeds meta size ({meta_size})", data.len()).into(),
+            ));
+        }
+
+        // Write to the slot that does not hold the latest metadata.
+        let offset = self.inner.config.align + (sequence as usize % 2) * meta_size;
+        let mut buf = self.io_buffer(bits::align_up(self.align(), data.len()), meta_size);
+        buf[..data.len()].copy_from_slice(&data);
+
+        let fd = self.fd();
+        asyncify(move || {
+            let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+            nix::sys::uio::pwrite(fd, &buf, offset as i64)?;
+            nix::unistd::fsync(fd.as_raw_fd())?;
+            Ok::<_, DeviceError>(())
+        })
+        .await?;
+
+        metas.sequence = sequence;
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        self.inner.config.regions() * self.inner.config.region_size
+    }
+
+    fn regions(&self) -> usize {
+        self.inner.config.regions()
+    }
+
+    fn align(&self) -> usize {
+        self.inner.config.align
+    }
+
+    fn io_size(&self) -> usize {
+        self.inner.config.io_size
+    }
+
+    fn io_buffer_allocator(&self) -> &Self::IoBufferAllocator {
+        &self.inner.io_buffer_allocator
+    }
+
+    fn io_buffer(&self, len: usize, capacity: usize) -> VecA<u8, Self::IoBufferAllocator> {
+        assert!(len <= capacity);
+        let mut buf = VecA::with_capacity_in(capacity, self.inner.io_buffer_allocator);
+        unsafe { buf.set_len(len) };
+        buf
+    }
+}
+
+impl FileDevice {
+    pub async fn open(config: FileDeviceConfig) -> DeviceResult<Self> {
+        config.assert();
+
+        let path = config.path.clone();
+        let capacity = config.capacity;
+        let file = asyncify(move || {
+            #[cfg(target_os = "linux")]
+            use std::os::unix::prelude::OpenOptionsExt;
+
+            let is_block_device = path
+                .metadata()
+                .is_ok_and(|metadata| metadata.file_type().is_block_device());
+
+            let mut opts = OpenOptions::new();
+            opts.create(!is_block_device);
+            opts.write(true);
+            opts.read(true);
+            #[cfg(target_os = "linux")]
+            opts.custom_flags(libc::O_DIRECT);
+
+            let mut file = opts.open(&path)?;
+
+            if is_block_device {
+                let size = file.seek(SeekFrom::End(0))? as usize;
+                if size < capacity {
+                    return Err(DeviceError::Other(
+                        format!("block device size ({size}) is smaller than capacity ({capacity})").into(),
+                    ));
+                }
+            } else if (file.metadata()?.len() as usize) < capacity {
+                Self::preallocate(&file, capacity)?;
+            }
+
+            Ok::<_, DeviceError>(file)
+        })
+        .await?;
+
+        let io_buffer_allocator = AlignedAllocator::new(config.align);
+
+        let inner = FileDeviceInner {
+            config,
+            file,
+            metas: Mutex::new(Metas::default()),
+            io_buffer_allocator,
+        };
+        let device = Self { inner: Arc::new(inner) };
+
+        device.load_superblock().await?;
+        device.load_metas().await?;
+
+        Ok(device)
+    }
+
+    /// Validate the superblock, or initialize it if it does not exist.
+    async fn load_superblock(&self) -> DeviceResult<()> {
+        let config = &self.inner.config;
+        let superblock = Superblock {
+            align: config.align as u64,
+            region_size: config.region_size as u64,
+            regions: config.regions() as u64,
+            meta_size: config.meta_size as u64,
+        };
+
+        let buf = self.read_raw(0, config.align).await?;
+        match Superblock::read(&buf) {
+            Some(sb) if sb == superblock => Ok(()),
+            Some(sb) => Err(DeviceError::Other(
+                format!("superblock mismatch, superblock: {sb:?}, config: {superblock:?}").into(),
+            )),
+            None => {
+                let data = superblock.write();
+                let mut buf = self.io_buffer(config.align, config.align);
+                buf.fill(0);
+                buf[..data.len()].copy_from_slice(&data);
+                let (res, _) = self.write_raw(buf, 0).await;
+                res?;
+                self.flush().await
+            }
+        }
+    }
+
+    async fn load_metas(&self) -> DeviceResult<()> {
+        let config = &self.inner.config;
+        let buf = self.read_raw(config.align, 2 * config.meta_size).await?;
+        let (a, b) = buf.split_at(config.meta_size);
+        let metas = match (Metas::read(a), Metas::read(b)) {
+            (Some(a), Some(b)) => std::cmp::max_by_key(a, b, |metas| metas.sequence),
+            (Some(metas), None) | (None, Some(metas)) => metas,
+            (None, None) => Metas::default(),
+        };
+        *self.inner.metas.lock().await = metas;
+        Ok(())
+    }
+
+    async fn read_raw(&self, offset: usize, len: usize) -> DeviceResult<VecA<u8, AlignedAllocator>> {
+        let mut buf = self.io_buffer(len, len);
+        let fd = self.fd();
+        let (res, mut buf) = asyncify(move || {
+            let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+            let res = nix::sys::uio::pread(fd, &mut buf, offset as i64).map_err(DeviceError::from);
+            (res, buf)
+        })
+        .await;
+        // Bytes beyond the end of a sparse file are treated as zeros.
+        let read = res?;
+        buf[read..].fill(0);
+        Ok(buf)
+    }
+
+    async fn write_raw(
+        &self,
+        buf: VecA<u8, AlignedAllocator>,
+        offset: usize,
+    ) -> (DeviceResult<usize>, VecA<u8, AlignedAllocator>) {
+        let fd = self.fd();
+        asyncify(move || {
+            let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+            let res = nix::sys::uio::pwrite(fd, &buf, offset as i64).map_err(DeviceError::from);
+            (res, buf)
+        })
+        .await
+    }
+
+    fn fd(&self) -> RawFd {
+        self.inner.file.as_raw_fd()
+    }
+
+    fn offset(&self, region: RegionId, offset: usize) -> usize {
+        self.inner.config.data_offset() + region as usize * self.inner.config.region_size + offset
+    }
+
+    #[cfg(target_os = "linux")]
+    fn preallocate(file: &File, len: usize) -> DeviceResult<()> {
+        nix::fcntl::fallocate(
+            file.as_raw_fd(),
+            nix::fcntl::FallocateFlags::empty(),
+            0,
+            len as libc::off_t,
+        )?;
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn preallocate(file: &File, len: usize) -> DeviceResult<()> {
+        file.set_len(len as u64)?;
+        Ok(())
+    }
+
+    fn block_device_size(path: &Path) -> DeviceResult<usize> {
+        let mut file = File::open(path)?;
+        let size = file.seek(SeekFrom::End(0))?;
+        Ok(size as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BufMut;
+
+    use super::*;
+    use crate::device::DeviceExt;
+
+    const REGION_SIZE: usize = 16 * 1024; // 16 KiB
+    const META_SIZE: usize = 8 * 1024; // 8 KiB
+    const ALIGN: usize = 4 * 1024;
+    const CAPACITY: usize = ALIGN + 2 * META_SIZE + 4 * REGION_SIZE; // 84 KiB
+
+    fn config(path: impl AsRef<Path>) -> FileDeviceConfig {
+        FileDeviceConfig {
+            path: path.as_ref().into(),
+            capacity: CAPACITY,
+            region_size: REGION_SIZE,
+            meta_size: META_SIZE,
+            align: ALIGN,
+            io_size: ALIGN,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_device_simple() {
+        let dir = tempfile::tempdir().unwrap();
+        let dev = FileDevice::open(config(dir.path().join("device"))).await.unwrap();
+
+        assert_eq!(dev.regions(), 4);
+        assert_eq!(dev.region_size(), REGION_SIZE);
+        assert_eq!(
+            std::fs::metadata(dir.path().join("device")).unwrap().len() as usize,
+            CAPACITY
+        );
+
+        for region in 0..4 {
+            let mut buf = dev.io_buffer(ALIGN, ALIGN);
+            (&mut buf[..]).put_slice(&[region as u8 + 1; ALIGN]);
+            let (res, _) = dev.write(buf, .., region, REGION_SIZE - ALIGN).await;
+            res.unwrap();
+        }
+
+        for region in 0..4 {
+            let buf = dev.load(region, ..).await.unwrap();
+            assert!(buf[..REGION_SIZE - ALIGN].iter().all(|b| *b == 0));
+            assert!(buf[REGION_SIZE - ALIGN..].iter().all(|b| *b == region as u8 + 1));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_device_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("device");
+
+        let dev = FileDevice::open(config(&path)).await.unwrap();
+        let mut buf = dev.io_buffer(ALIGN, ALIGN);
+        (&mut buf[..]).put_slice(&[b'x'; ALIGN]);
+        let (res, _) = dev.write(buf, .., 3, 0).await;
+        res.unwrap();
+        assert!(dev.read_meta("test").await.unwrap().is_none());
+        dev.write_meta("test", vec![1; 42]).await.unwrap();
+        dev.write_meta("other", vec![2; 24]).await.unwrap();
+        dev.write_meta("test", vec![3; 4096]).await.unwrap();
+        assert!(dev.write_meta("large", vec![4; META_SIZE]).await.is_err());
+        drop(dev);
+
+        // The capacity of an existing file is detected.
+        let detected = FileDeviceConfigBuilder::new(&path)
+            .with_region_size(REGION_SIZE)
+            .with_meta_size(META_SIZE)
+            .with_align(ALIGN)
+            .with_io_size(ALIGN)
+            .build();
+        assert_eq!(detected.capacity, CAPACITY);
+
+        let dev = FileDevice::open(detected).await.unwrap();
+        let buf = dev.load(3, 0..ALIGN).await.unwrap();
+        assert!(buf.iter().all(|b| *b == b'x'));
+        assert_eq!(dev.read_meta("test").await.unwrap(), Some(vec![3; 4096]));
+        assert_eq!(dev.read_meta("other").await.unwrap(), Some(vec![2; 24]));
+        assert!(dev.read_meta("large").await.unwrap().is_none());
+        drop(dev);
+
+        // Reopen with a different layout is rejected.
+        let mut config = config(&path);
+        config.region_size = 2 * REGION_SIZE;
+        assert!(FileDevice::open(config).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_file_device_torn_meta() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("device");
+
+        let dev = FileDevice::open(config(&path)).await.unwrap();
+        dev.write_meta("test", vec![1; 42]).await.unwrap();
+        dev.write_meta("test", vec![2; 42]).await.unwrap();
+        drop(dev);
+
+        // Corrupt the slot with the latest metadata, metadata with sequence 2 is written to slot A.
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        nix::sys::uio::pwrite(&file, &[0; 64], ALIGN as i64).unwrap();
+        drop(file);
+
+        let dev = FileDevice::open(config(&path)).await.unwrap();
+        assert_eq!(dev.read_meta("test").await.unwrap(), Some(vec![1; 42]));
+    }
+
+    #[test]
+    fn test_config_builder() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = FileDeviceConfigBuilder::new(dir.path().join("device")).build();
+
+        println!("{config:?}");
+
+        config.assert();
+    }
+}
@@ -13,7 +13,8 @@
 //  limitations under the License.
 
 use std::{
-    fs::{create_dir_all, File, OpenOptions},
+    fs::{create_dir_all, rename, File, OpenOptions},
+    io::{ErrorKind, Read, Write},
     os::fd::{AsRawFd, BorrowedFd, RawFd},
     path::{Path, PathBuf},
     sync::Arc,
@@ -222,6 +223,35 @@ impl Device for FsDevice {
         Ok(())
     }
 
+    async fn read_meta(&self, name: &str) -> DeviceResult<Option<Vec<u8>>> {
+        let path = self.inner.config.dir.join(Self::meta_filename(name));
+        asyncify(move || {
+            let mut file = match File::open(path) {
+                Ok(file) => file,
+                Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+                Err(e) => return Err(e.into()),
+            };
+            let mut buf = vec![];
+            file.read_to_end(&mut buf)?;
+            Ok(Some(buf))
+        })
+        .await
+    }
+
+    async fn write_meta(&self, name: &str, buf: Vec<u8>) -> DeviceResult<()> {
+        let path = self.inner.config.dir.join(Self::meta_filename(name));
+        let tmp = self.inner.config.dir.join(format!("{}.tmp", Self::meta_filename(name)));
+        // Write to a temporary file and rename it to make the overwrite atomic.
+        asyncify(move || {
+            let mut file = OpenOptions::new().create(true).write(true).truncate(true).open(&tmp)?;
+            file.write_all(&buf)?;
+            file.sync_all()?;
+            rename(&tmp, &path)?;
+            Ok(())
+        })
+        .await
+    }
+
     fn capacity(&self) -> usize {
         self.inner.config.capacity
     }
@@ -299,13 +329,17 @@ impl FsDevice {
         Ok(Self { inner: Arc::new(inner) })
     }
 
-    fn fd(&self, region: RegionId) -> RawFd {
+    pub(super) fn fd(&self, region: RegionId) -> RawFd {
         self.inner.files[region as usize].as_raw_fd()
     }
 
     fn filename(region: RegionId) -> String {
         format!("foyer-cache-{:08}", region)
     }
+
+    fn meta_filename(name: &str) -> String {
+        format!("foyer-meta-{}", name)
+    }
 }
 
 #[cfg(test)]
@@ -350,6 +384,27 @@ mod tests {
         drop(rbuffer);
     }
 
+    #[tokio::test]
+    async fn test_fs_device_meta() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = FsDeviceConfig {
+            dir: PathBuf::from(dir.path()),
+            capacity: CAPACITY,
+            file_size: FILE_CAPACITY,
+            align: ALIGN,
+            io_size: ALIGN,
+        };
+        let dev = FsDevice::open(config.clone()).await.unwrap();
+
+        assert!(dev.read_meta("test").await.unwrap().is_none());
+        dev.write_meta("test", vec![1; 42]).await.unwrap();
+        dev.write_meta("test", vec![2; 24]).await.unwrap();
+        drop(dev);
+
+        let dev = FsDevice::open(config).await.unwrap();
+        assert_eq!(dev.read_meta("test").await.unwrap(), Some(vec![2; 24]));
+    }
+
     #[test]
     fn test_config_builder() {
         let dir = current_dir().unwrap();
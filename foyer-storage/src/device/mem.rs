@@ -0,0 +1,345 @@
+//  Copyright 2024 Foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! Device backed by aligned heap buffers.
+//!
+//! The backing memory is held by [`MemDeviceMemory`] in the config and shared by all devices opened with clones of
+//! the config, so the data survives `close` and `open` of the store as long as any clone of the config is alive.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, OnceLock},
+};
+
+use allocator_api2::vec::Vec as VecA;
+use foyer_common::range::RangeBoundsExt;
+use parking_lot::{Mutex, RwLock};
+
+use super::{allocator::AlignedAllocator, Device, DeviceError, DeviceResult, IoBuf, IoBufMut, IoRange};
+use crate::region::RegionId;
+
+#[derive(Debug)]
+pub struct MemDeviceConfigBuilder {
+    pub capacity: usize,
+    pub region_size: Option<usize>,
+    pub align: Option<usize>,
+    pub io_size: Option<usize>,
+}
+
+impl MemDeviceConfigBuilder {
+    const DEFAULT_ALIGN: usize = 4096;
+    const DEFAULT_IO_SIZE: usize = 16 * 1024;
+    const DEFAULT_REGION_SIZE: usize = 4 * 1024 * 1024;
+
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            region_size: None,
+            align: None,
+            io_size: None,
+        }
+    }
+
+    pub fn with_region_size(mut self, region_size: usize) -> Self {
+        self.region_size = Some(region_size);
+        self
+    }
+
+    pub fn with_align(mut self, align: usize) -> Self {
+        self.align = Some(align);
+        self
+    }
+
+    pub fn with_io_size(mut self, io_size: usize) -> Self {
+        self.io_size = Some(io_size);
+        self
+    }
+
+    pub fn build(self) -> MemDeviceConfig {
+        let align_v = |value: usize, align: usize| value - value % align;
+
+        let align = self.align.unwrap_or(Self::DEFAULT_ALIGN);
+
+        let capacity = align_v(self.capacity, align);
+
+        let region_size = self
+            .region_size
+            .unwrap_or(Self::DEFAULT_REGION_SIZE)
+            .clamp(align, capacity);
+        let region_size = align_v(region_size, align);
+
+        let capacity = align_v(capacity, region_size);
+
+        let io_size = self.io_size.unwrap_or(Self::DEFAULT_IO_SIZE).max(align);
+        let io_size = align_v(io_size, align);
+
+        MemDeviceConfig {
+            capacity,
+            region_size,
+            align,
+            io_size,
+            memory: MemDeviceMemory::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MemDeviceConfig {
+    /// must be multipliers of `align` and `region_size`
+    pub capacity: usize,
+
+    /// must be multipliers of `align`
+    pub region_size: usize,
+
+    /// io block alignment, must be pow of 2
+    pub align: usize,
+
+    /// recommended optimized io block size
+    pub io_size: usize,
+
+    /// backing memory, shared by devices opened with clones of the config
+    pub memory: MemDeviceMemory,
+}
+
+impl MemDeviceConfig {
+    pub fn assert(&self) {
+        assert!(self.align.is_power_of_two());
+        assert_eq!(self.region_size % self.align, 0);
+        assert_eq!(self.capacity % self.region_size, 0);
+    }
+}
+
+#[derive(Debug)]
+struct Memory {
+    region_size: usize,
+    align: usize,
+
+    regions: Vec<RwLock<VecA<u8, AlignedAllocator>>>,
+    metas: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+/// Handle of the backing memory of [`MemDevice`].
+///
+/// The memory is allocated when the first device is opened with it, and released after all handles are dropped.
+#[derive(Debug, Clone, Default)]
+pub struct MemDeviceMemory {
+    memory: Arc<OnceLock<Memory>>,
+}
+
+#[derive(Debug)]
+struct MemDeviceInner {
+    config: MemDeviceConfig,
+
+    io_buffer_allocator: AlignedAllocator,
+}
+
+#[derive(Debug, Clone)]
+pub struct MemDevice {
+    inner: Arc<MemDeviceInner>,
+}
+
+impl Device for MemDevice {
+    type Config = MemDeviceConfig;
+    type IoBufferAllocator = AlignedAllocator;
+
+    async fn open(config: MemDeviceConfig) -> DeviceResult<Self> {
+        Self::open(config).await
+    }
+
+    async fn write<B>(&self, buf: B, range: impl IoRange, region: RegionId, offset: usize) -> (DeviceResult<usize>, B)
+    where
+        B: IoBuf,
+    {
+        let region_size = self.inner.config.region_size;
+
+        let range = range.bounds(0..buf.as_ref().len());
+        let len = RangeBoundsExt::size(&range).unwrap();
+
+        assert!(
+            offset + len <= region_size,
+            "offset ({offset}) + len ({len}) <= region size ({region_size})"
+        );
+
+        self.memory().regions[region as usize].write()[offset..offset + len].copy_from_slice(&buf.as_ref()[range]);
+
+        (Ok(len), buf)
+    }
+
+    async fn read<B>(
+        &self,
+        mut buf: B,
+        range: impl IoRange,
+        region: RegionId,
+        offset: usize,
+    ) -> (DeviceResult<usize>, B)
+    where
+        B: IoBufMut,
+    {
+        let region_size = self.inner.config.region_size;
+
+        let range = range.bounds(0..buf.as_ref().len());
+        let len = RangeBoundsExt::size(&range).unwrap();
+
+        assert!(
+            offset + len <= region_size,
+            "offset ({offset}) + len ({len}) <= region size ({region_size})"
+        );
+
+        buf.as_mut()[range].copy_from_slice(&self.memory().regions[region as usize].read()[offset..offset + len]);
+
+        (Ok(len), buf)
+    }
+
+    async fn flush(&self) -> DeviceResult<()> {
+        Ok(())
+    }
+
+    async fn read_meta(&self, name: &str) -> DeviceResult<Option<Vec<u8>>> {
+        Ok(self.memory().metas.lock().get(name).cloned())
+    }
+
+    async fn write_meta(&self, name: &str, buf: Vec<u8>) -> DeviceResult<()> {
+        self.memory().metas.lock().insert(name.to_string(), buf);
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        self.inner.config.capacity
+    }
+
+    fn regions(&self) -> usize {
+        self.inner.config.capacity / self.inner.config.region_size
+    }
+
+    fn align(&self) -> usize {
+        self.inner.config.align
+    }
+
+    fn io_size(&self) -> usize {
+        self.inner.config.io_size
+    }
+
+    fn io_buffer_allocator(&self) -> &Self::IoBufferAllocator {
+        &self.inner.io_buffer_allocator
+    }
+
+    fn io_buffer(&self, len: usize, capacity: usize) -> VecA<u8, Self::IoBufferAllocator> {
+        assert!(len <= capacity);
+        let mut buf = VecA::with_capacity_in(capacity, self.inner.io_buffer_allocator);
+        unsafe { buf.set_len(len) };
+        buf
+    }
+}
+
+impl MemDevice {
+    pub async fn open(config: MemDeviceConfig) -> DeviceResult<Self> {
+        config.assert();
+
+        let regions = config.capacity / config.region_size;
+        let memory = config.memory.memory.get_or_init(|| Memory {
+            region_size: config.region_size,
+            align: config.align,
+            regions: (0..regions)
+                .map(|_| {
+                    let mut buf = VecA::with_capacity_in(config.region_size, AlignedAllocator::new(config.align));
+                    buf.resize(config.region_size, 0);
+                    RwLock::new(buf)
+                })
+                .collect(),
+            metas: Mutex::new(HashMap::new()),
+        });
+        if memory.region_size != config.region_size || memory.align != config.align || memory.regions.len() != regions {
+            return Err(DeviceError::Other(
+                format!(
+                    "memory layout mismatch, region size: {}, align: {}, regions: {}, config: {:?}",
+                    memory.region_size,
+                    memory.align,
+                    memory.regions.len(),
+                    config
+                )
+                .into(),
+            ));
+        }
+
+        let io_buffer_allocator = AlignedAllocator::new(config.align);
+
+        let inner = MemDeviceInner {
+            config,
+            io_buffer_allocator,
+        };
+
+        Ok(Self { inner: Arc::new(inner) })
+    }
+
+    fn memory(&self) -> &Memory {
+        self.inner.config.memory.memory.get().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BufMut;
+
+    use super::*;
+    use crate::device::DeviceExt;
+
+    const REGION_SIZE: usize = 16 * 1024; // 16 KiB
+    const CAPACITY: usize = 4 * REGION_SIZE; // 64 KiB
+    const ALIGN: usize = 4 * 1024;
+
+    #[tokio::test]
+    async fn test_mem_device() {
+        let config = MemDeviceConfigBuilder::new(CAPACITY)
+            .with_region_size(REGION_SIZE)
+            .with_align(ALIGN)
+            .with_io_size(ALIGN)
+            .build();
+
+        let dev = MemDevice::open(config.clone()).await.unwrap();
+        assert_eq!(dev.regions(), 4);
+
+        let mut buf = dev.io_buffer(ALIGN, ALIGN);
+        (&mut buf[..]).put_slice(&[b'x'; ALIGN]);
+        let (res, _) = dev.write(buf, .., 3, ALIGN).await;
+        assert_eq!(res.unwrap(), ALIGN);
+        dev.write_meta("test", vec![1; 42]).await.unwrap();
+        drop(dev);
+
+        // The memory is shared by devices opened with clones of the config.
+        let dev = MemDevice::open(config.clone()).await.unwrap();
+        let buf = dev.load(3, ..).await.unwrap();
+        assert!(buf[..ALIGN].iter().all(|b| *b == 0));
+        assert!(buf[ALIGN..2 * ALIGN].iter().all(|b| *b == b'x'));
+        assert!(buf[2 * ALIGN..].iter().all(|b| *b == 0));
+        assert_eq!(dev.read_meta("test").await.unwrap(), Some(vec![1; 42]));
+
+        // A new config has its own memory.
+        let dev = MemDevice::open(MemDeviceConfig {
+            memory: MemDeviceMemory::default(),
+            ..config.clone()
+        })
+        .await
+        .unwrap();
+        assert!(dev.load(3, ..).await.unwrap().iter().all(|b| *b == 0));
+        assert!(dev.read_meta("test").await.unwrap().is_none());
+
+        // Reopen with a different layout is rejected.
+        assert!(MemDevice::open(MemDeviceConfig {
+            region_size: 2 * REGION_SIZE,
+            ..config
+        })
+        .await
+        .is_err());
+    }
+}
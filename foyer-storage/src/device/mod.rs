@@ -13,7 +13,11 @@
 //  limitations under the License.
 
 pub mod allocator;
+pub mod file;
 pub mod fs;
+pub mod mem;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub mod uring;
 
 use std::fmt::Debug;
 
@@ -83,6 +87,14 @@ pub trait Device: Sized + Clone + Send + Sync + 'static + Debug {
     #[must_use]
     fn flush(&self) -> impl Future<Output = DeviceResult<()>> + Send;
 
+    /// Read the metadata blob named `name`, return `None` if it does not exist.
+    #[must_use]
+    fn read_meta(&self, name: &str) -> impl Future<Output = DeviceResult<Option<Vec<u8>>>> + Send;
+
+    /// Overwrite the metadata blob named `name` atomically.
+    #[must_use]
+    fn write_meta(&self, name: &str, buf: Vec<u8>) -> impl Future<Output = DeviceResult<()>> + Send;
+
     fn capacity(&self) -> usize;
 
     fn regions(&self) -> usize;
@@ -140,7 +152,7 @@ impl<D: Device> DeviceExt for D {}
 
 #[cfg(not(madsim))]
 #[tracing::instrument(level = "trace", skip(f))]
-async fn asyncify<F, T>(f: F) -> T
+pub async fn asyncify<F, T>(f: F) -> T
 where
     F: FnOnce() -> T + Send + 'static,
     T: Send + 'static,
@@ -150,7 +162,7 @@ where
 
 #[cfg(madsim)]
 #[tracing::instrument(level = "trace", skip(f))]
-async fn asyncify<F, T>(f: F) -> T
+pub async fn asyncify<F, T>(f: F) -> T
 where
     F: FnOnce() -> T + Send + 'static,
     T: Send + 'static,
@@ -209,6 +221,14 @@ pub mod tests {
             Ok(())
         }
 
+        async fn read_meta(&self, _name: &str) -> DeviceResult<Option<Vec<u8>>> {
+            Ok(None)
+        }
+
+        async fn write_meta(&self, _name: &str, _buf: Vec<u8>) -> DeviceResult<()> {
+            Ok(())
+        }
+
         fn capacity(&self) -> usize {
             usize::MAX
         }
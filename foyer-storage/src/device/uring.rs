@@ -0,0 +1,976 @@
+//  Copyright 2024 Foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! io_uring based device.
+//!
+//! [`UringDevice`] shares the file layout with [`FsDevice`], but submits reads and writes to an io_uring instance
+//! instead of calling `pread(2)`/`pwrite(2)` on the blocking thread pool.
+//!
+//! All submissions and completions are handled by a dedicated reactor thread, which submits all queued requests with
+//! a single `io_uring_enter(2)` call. The io buffers returned by [`Device::io_buffer`] are allocated from an aligned
+//! arena that is registered to the io_uring instance, so I/Os on them are issued with `IORING_OP_READ_FIXED` and
+//! `IORING_OP_WRITE_FIXED` to skip the page pinning of each I/O.
+
+use std::{
+    alloc::Layout,
+    any::Any,
+    collections::VecDeque,
+    ffi::c_void,
+    io,
+    ops::Range,
+    os::fd::{AsFd, OwnedFd, RawFd},
+    panic::AssertUnwindSafe,
+    path::{Path, PathBuf},
+    ptr::NonNull,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        mpsc, Arc,
+    },
+};
+
+use allocator_api2::{
+    alloc::{AllocError, Allocator},
+    vec::Vec as VecA,
+};
+use foyer_common::{bits, range::RangeBoundsExt};
+use parking_lot::Mutex;
+use rustix::io_uring::{
+    io_uring_cqe, io_uring_enter, io_uring_params, io_uring_ptr, io_uring_register, io_uring_setup, io_uring_sqe,
+    io_uring_user_data, iovec, IoringEnterFlags, IoringFeatureFlags, IoringOp, IoringRegisterOp, IORING_OFF_CQ_RING,
+    IORING_OFF_SQES, IORING_OFF_SQ_RING,
+};
+use tokio::sync::oneshot;
+
+use super::{
+    allocator::AlignedAllocator,
+    fs::{FsDevice, FsDeviceConfig, FsDeviceConfigBuilder},
+    Device, DeviceResult, IoBuf, IoBufMut, IoRange,
+};
+use crate::region::RegionId;
+
+#[derive(Debug)]
+pub struct UringDeviceConfigBuilder {
+    pub fs: FsDeviceConfigBuilder,
+    pub ring_entries: Option<u32>,
+    pub fixed_buffers: Option<usize>,
+    pub fixed_buffer_size: Option<usize>,
+}
+
+impl UringDeviceConfigBuilder {
+    const DEFAULT_RING_ENTRIES: u32 = 128;
+
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            fs: FsDeviceConfigBuilder::new(dir),
+            ring_entries: None,
+            fixed_buffers: None,
+            fixed_buffer_size: None,
+        }
+    }
+
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.fs = self.fs.with_capacity(capacity);
+        self
+    }
+
+    pub fn with_file_size(mut self, file_size: usize) -> Self {
+        self.fs = self.fs.with_file_size(file_size);
+        self
+    }
+
+    pub fn with_align(mut self, align: usize) -> Self {
+        self.fs = self.fs.with_align(align);
+        self
+    }
+
+    pub fn with_io_size(mut self, io_size: usize) -> Self {
+        self.fs = self.fs.with_io_size(io_size);
+        self
+    }
+
+    pub fn with_ring_entries(mut self, ring_entries: u32) -> Self {
+        self.ring_entries = Some(ring_entries);
+        self
+    }
+
+    pub fn with_fixed_buffers(mut self, fixed_buffers: usize) -> Self {
+        self.fixed_buffers = Some(fixed_buffers);
+        self
+    }
+
+    pub fn with_fixed_buffer_size(mut self, fixed_buffer_size: usize) -> Self {
+        self.fixed_buffer_size = Some(fixed_buffer_size);
+        self
+    }
+
+    pub fn build(self) -> UringDeviceConfig {
+        let FsDeviceConfig {
+            dir,
+            capacity,
+            file_size,
+            align,
+            io_size,
+        } = self.fs.build();
+
+        let ring_entries = self
+            .ring_entries
+            .unwrap_or(Self::DEFAULT_RING_ENTRIES)
+            .next_power_of_two();
+        let fixed_buffers = self.fixed_buffers.unwrap_or(ring_entries as usize);
+        // The flusher allocates io buffers with 1.5x io size, make sure they fit in a fixed buffer.
+        let fixed_buffer_size = bits::align_up(align, self.fixed_buffer_size.unwrap_or(io_size * 2).max(align));
+
+        UringDeviceConfig {
+            dir,
+            capacity,
+            file_size,
+            align,
+            io_size,
+            ring_entries,
+            fixed_buffers,
+            fixed_buffer_size,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UringDeviceConfig {
+    /// base dir path
+    pub dir: PathBuf,
+
+    /// must be multipliers of `align` and `file_capacity`
+    pub capacity: usize,
+
+    /// must be multipliers of `align`
+    pub file_size: usize,
+
+    /// io block alignment, must be pow of 2
+    pub align: usize,
+
+    /// recommended optimized io block size
+    pub io_size: usize,
+
+    /// submission queue entries of the io_uring instance, must be pow of 2
+    pub ring_entries: u32,
+
+    /// count of io buffers registered to the io_uring instance, `0` disables registered buffers
+    pub fixed_buffers: usize,
+
+    /// size of each registered io buffer, must be multipliers of `align`
+    pub fixed_buffer_size: usize,
+}
+
+impl UringDeviceConfig {
+    pub fn assert(&self) {
+        self.fs().assert();
+        assert!(self.ring_entries.is_power_of_two());
+        assert!(self.fixed_buffers <= u16::MAX as usize);
+        assert_eq!(self.fixed_buffer_size % self.align, 0);
+    }
+
+    fn fs(&self) -> FsDeviceConfig {
+        FsDeviceConfig {
+            dir: self.dir.clone(),
+            capacity: self.capacity,
+            file_size: self.file_size,
+            align: self.align,
+            io_size: self.io_size,
+        }
+    }
+}
+
+/// Aligned arena that is registered to the io_uring instance as fixed buffers.
+#[derive(Debug)]
+struct FixedBuffers {
+    ptr: NonNull<u8>,
+    size: usize,
+    count: usize,
+    align: usize,
+    free: Mutex<Vec<usize>>,
+}
+
+unsafe impl Send for FixedBuffers {}
+unsafe impl Sync for FixedBuffers {}
+
+impl FixedBuffers {
+    fn new(align: usize, size: usize, count: usize) -> Self {
+        let ptr = if count == 0 {
+            NonNull::dangling()
+        } else {
+            let layout = Layout::from_size_align(size * count, align).unwrap();
+            AlignedAllocator::new(align).allocate(layout).unwrap().cast::<u8>()
+        };
+        Self {
+            ptr,
+            size,
+            count,
+            align,
+            free: Mutex::new((0..count).rev().collect()),
+        }
+    }
+
+    fn iovecs(&self) -> Vec<iovec> {
+        (0..self.count)
+            .map(|i| iovec {
+                iov_base: unsafe { self.ptr.as_ptr().add(i * self.size) } as *mut c_void,
+                iov_len: self.size,
+            })
+            .collect()
+    }
+
+    /// Return the index of the fixed buffer that contains the whole range.
+    fn index(&self, ptr: *const u8, len: usize) -> Option<usize> {
+        let start = (ptr as usize).checked_sub(self.ptr.as_ptr() as usize)?;
+        let index = start / self.size;
+        if index >= self.count || start + len > (index + 1) * self.size {
+            return None;
+        }
+        Some(index)
+    }
+}
+
+impl Drop for FixedBuffers {
+    fn drop(&mut self) {
+        if self.count > 0 {
+            let layout = Layout::from_size_align(self.size * self.count, self.align).unwrap();
+            unsafe { AlignedAllocator::new(self.align).deallocate(self.ptr, layout) };
+        }
+    }
+}
+
+/// Io buffer allocator of [`UringDevice`].
+///
+/// Allocates from the registered fixed buffers if possible, otherwise falls back to [`AlignedAllocator`].
+#[derive(Debug, Clone)]
+pub struct UringAllocator {
+    fixed: Arc<FixedBuffers>,
+    /// `false` if the fixed buffers are not registered, the fixed buffers are never used then.
+    registered: bool,
+    fallback: AlignedAllocator,
+}
+
+unsafe impl Allocator for UringAllocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if self.registered && layout.size() <= self.fixed.size && layout.align() <= self.fixed.align {
+            if let Some(index) = self.fixed.free.lock().pop() {
+                let ptr = unsafe { NonNull::new_unchecked(self.fixed.ptr.as_ptr().add(index * self.fixed.size)) };
+                return Ok(NonNull::slice_from_raw_parts(ptr, layout.size()));
+            }
+        }
+        self.fallback.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        match self.fixed.index(ptr.as_ptr(), 0) {
+            Some(index) if self.registered => self.fixed.free.lock().push(index),
+            _ => self.fallback.deallocate(ptr, layout),
+        }
+    }
+}
+
+struct UringRequest {
+    opcode: IoringOp,
+    fd: RawFd,
+    ptr: *mut u8,
+    len: u32,
+    offset: u64,
+    buf_index: Option<u16>,
+    /// Keep the io buffer alive until the I/O completes, even if the caller is cancelled.
+    buf: Box<dyn Any + Send>,
+    tx: oneshot::Sender<UringResponse>,
+}
+
+type UringResponse = (io::Result<usize>, Box<dyn Any + Send>);
+
+// The raw pointer points to the owned `buf`.
+unsafe impl Send for UringRequest {}
+
+struct Mmap {
+    ptr: NonNull<u8>,
+    len: usize,
+}
+
+impl Mmap {
+    fn new(fd: &OwnedFd, len: usize, offset: u64) -> io::Result<Self> {
+        use std::os::fd::AsRawFd;
+
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED | libc::MAP_POPULATE,
+                fd.as_raw_fd(),
+                offset as libc::off_t,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self {
+            ptr: NonNull::new(ptr as *mut u8).unwrap(),
+            len,
+        })
+    }
+
+    unsafe fn at<T>(&self, offset: u32) -> *mut T {
+        self.ptr.as_ptr().add(offset as usize) as *mut T
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.ptr.as_ptr() as *mut c_void, self.len) };
+    }
+}
+
+/// Minimal io_uring instance that is only accessed by the reactor thread.
+struct Ring {
+    sq_head: *const AtomicU32,
+    sq_tail: *const AtomicU32,
+    sq_mask: u32,
+    sq_entries: u32,
+    sq_array: *mut u32,
+    sqes: *mut io_uring_sqe,
+
+    cq_head: *const AtomicU32,
+    cq_tail: *const AtomicU32,
+    cq_mask: u32,
+    cq_entries: u32,
+    cq_overflow: *const AtomicU32,
+    cqes: *const io_uring_cqe,
+
+    _mmaps: Vec<Mmap>,
+    fd: OwnedFd,
+}
+
+unsafe impl Send for Ring {}
+
+impl Ring {
+    fn new(entries: u32) -> io::Result<Self> {
+        let mut params = io_uring_params::default();
+        let fd = unsafe { io_uring_setup(entries, &mut params) }?;
+
+        let sq_len = params.sq_off.array as usize + params.sq_entries as usize * std::mem::size_of::<u32>();
+        let cq_len = params.cq_off.cqes as usize + params.cq_entries as usize * std::mem::size_of::<io_uring_cqe>();
+
+        let mut mmaps = vec![];
+        let (sq, cq) = if params.features.contains(IoringFeatureFlags::SINGLE_MMAP) {
+            mmaps.push(Mmap::new(&fd, sq_len.max(cq_len), IORING_OFF_SQ_RING)?);
+            (0, 0)
+        } else {
+            mmaps.push(Mmap::new(&fd, sq_len, IORING_OFF_SQ_RING)?);
+            mmaps.push(Mmap::new(&fd, cq_len, IORING_OFF_CQ_RING)?);
+            (0, 1)
+        };
+        let sqes = Mmap::new(
+            &fd,
+            params.sq_entries as usize * std::mem::size_of::<io_uring_sqe>(),
+            IORING_OFF_SQES,
+        )?;
+
+        let (sq, cq) = (&mmaps[sq], &mmaps[cq]);
+        let ring = unsafe {
+            Self {
+                sq_head: sq.at(params.sq_off.head),
+                sq_tail: sq.at(params.sq_off.tail),
+                sq_mask: *sq.at::<u32>(params.sq_off.ring_mask),
+                sq_entries: *sq.at::<u32>(params.sq_off.ring_entries),
+                sq_array: sq.at(params.sq_off.array),
+                sqes: sqes.at(0),
+
+                cq_head: cq.at(params.cq_off.head),
+                cq_tail: cq.at(params.cq_off.tail),
+                cq_mask: *cq.at::<u32>(params.cq_off.ring_mask),
+                cq_entries: *cq.at::<u32>(params.cq_off.ring_entries),
+                cq_overflow: cq.at(params.cq_off.overflow),
+                cqes: cq.at(params.cq_off.cqes),
+
+                _mmaps: {
+                    mmaps.push(sqes);
+                    mmaps
+                },
+                fd,
+            }
+        };
+        Ok(ring)
+    }
+
+    /// Count of queued but not submitted entries.
+    fn queued(&self) -> u32 {
+        unsafe { (*self.sq_tail).load(Ordering::Relaxed) - (*self.sq_head).load(Ordering::Acquire) }
+    }
+
+    /// Push an entry to the submission queue, return `false` if the submission queue is full.
+    fn push(&mut self, sqe: io_uring_sqe) -> bool {
+        if self.queued() == self.sq_entries {
+            return false;
+        }
+        unsafe {
+            let tail = (*self.sq_tail).load(Ordering::Relaxed);
+            let index = tail & self.sq_mask;
+            self.sqes.add(index as usize).write(sqe);
+            self.sq_array.add(index as usize).write(index);
+            (*self.sq_tail).store(tail.wrapping_add(1), Ordering::Release);
+        }
+        true
+    }
+
+    /// Submit all queued entries and wait for at least `wait` completions.
+    fn submit_and_wait(&self, wait: u32) -> io::Result<()> {
+        let flags = if wait > 0 {
+            IoringEnterFlags::GETEVENTS
+        } else {
+            IoringEnterFlags::empty()
+        };
+        unsafe { io_uring_enter(self.fd.as_fd(), self.queued(), wait, flags) }?;
+        Ok(())
+    }
+
+    /// Count of completions dropped because the completion queue was full.
+    fn overflow(&self) -> u32 {
+        unsafe { (*self.cq_overflow).load(Ordering::Acquire) }
+    }
+
+    /// Pop all completions as `(user_data, res)`.
+    fn complete(&mut self, mut f: impl FnMut(u64, i32)) {
+        unsafe {
+            let mut head = (*self.cq_head).load(Ordering::Relaxed);
+            let tail = (*self.cq_tail).load(Ordering::Acquire);
+            while head != tail {
+                let cqe = &*self.cqes.add((head & self.cq_mask) as usize);
+                f(cqe.user_data.u64_(), cqe.res);
+                head = head.wrapping_add(1);
+            }
+            (*self.cq_head).store(head, Ordering::Release);
+        }
+    }
+}
+
+struct Reactor {
+    ring: Ring,
+    rx: mpsc::Receiver<UringRequest>,
+    pending: VecDeque<UringRequest>,
+    /// Submitted requests indexed by `user_data`.
+    inflights: Vec<Option<UringRequest>>,
+    free: Vec<usize>,
+    count: usize,
+    // Keep the registered buffers alive until the ring is dropped.
+    _fixed: Arc<FixedBuffers>,
+}
+
+impl Reactor {
+    fn run(mut self) {
+        loop {
+            // Block for new requests only if there is nothing to wait for.
+            if self.count == 0 && self.pending.is_empty() {
+                match self.rx.recv() {
+                    Ok(request) => self.pending.push_back(request),
+                    Err(_) => return,
+                }
+            }
+            self.pending.extend(self.rx.try_iter());
+
+            // Never submit more I/Os than the completion queue can hold, otherwise completions are dropped on kernels
+            // without `IORING_FEAT_NODROP` and their requests never complete, or the submission fails with `EBUSY`.
+            while self.count < self.ring.cq_entries as usize {
+                let Some(request) = self.pending.pop_front() else {
+                    break;
+                };
+                if !self.ring.push(Self::sqe(
+                    &request,
+                    self.free.last().copied().unwrap_or(self.inflights.len()),
+                )) {
+                    self.pending.push_front(request);
+                    break;
+                }
+                match self.free.pop() {
+                    Some(index) => self.inflights[index] = Some(request),
+                    None => self.inflights.push(Some(request)),
+                }
+                self.count += 1;
+            }
+
+            match self.ring.submit_and_wait(1) {
+                Ok(()) => {}
+                Err(e)
+                    if e.kind() == io::ErrorKind::Interrupted
+                        || e.raw_os_error() == Some(libc::EAGAIN)
+                        || e.raw_os_error() == Some(libc::EBUSY) => {}
+                Err(e) => {
+                    tracing::error!("io_uring reactor exits with error: {}", e);
+                    if self.count > 0 {
+                        // The kernel may still access the buffers of the submitted requests, they can be neither
+                        // returned nor released.
+                        tracing::error!("io_uring reactor exits with {} inflight I/Os, abort", self.count);
+                        std::process::abort();
+                    }
+                    // Fail the following requests until the device is dropped, so every request is responded with its
+                    // io buffer.
+                    for request in self.pending.into_iter().chain(self.rx.iter()) {
+                        let _ = request.tx.send((Err(UringDevice::exited()), request.buf));
+                    }
+                    return;
+                }
+            }
+
+            if self.ring.overflow() > 0 {
+                // The requests of the dropped completions can never be responded, and their buffers may still be
+                // accessed by the kernel.
+                tracing::error!(
+                    "io_uring completions are dropped with {} inflight I/Os, abort",
+                    self.count
+                );
+                std::process::abort();
+            }
+
+            let (inflights, free, count) = (&mut self.inflights, &mut self.free, &mut self.count);
+            self.ring.complete(|user_data, res| {
+                let request = inflights[user_data as usize].take().unwrap();
+                free.push(user_data as usize);
+                *count -= 1;
+                let res = if res < 0 {
+                    Err(io::Error::from_raw_os_error(-res))
+                } else {
+                    Ok(res as usize)
+                };
+                let _ = request.tx.send((res, request.buf));
+            });
+        }
+    }
+
+    fn sqe(request: &UringRequest, user_data: usize) -> io_uring_sqe {
+        let mut sqe = io_uring_sqe {
+            opcode: request.opcode,
+            fd: request.fd,
+            ..Default::default()
+        };
+        if let Some(buf_index) = request.buf_index {
+            sqe.opcode = match request.opcode {
+                IoringOp::Read => IoringOp::ReadFixed,
+                IoringOp::Write => IoringOp::WriteFixed,
+                _ => unreachable!(),
+            };
+            sqe.buf.buf_index = buf_index;
+        }
+        sqe.off_or_addr2.off = request.offset;
+        sqe.addr_or_splice_off_in.addr = io_uring_ptr::new(request.ptr as *mut c_void);
+        sqe.len.len = request.len;
+        sqe.user_data = io_uring_user_data::from_u64(user_data as u64);
+        sqe
+    }
+}
+
+#[derive(Debug)]
+struct UringDeviceInner {
+    config: UringDeviceConfig,
+
+    fs: FsDevice,
+
+    tx: mpsc::Sender<UringRequest>,
+
+    io_buffer_allocator: UringAllocator,
+}
+
+#[derive(Debug, Clone)]
+pub struct UringDevice {
+    inner: Arc<UringDeviceInner>,
+}
+
+impl Device for UringDevice {
+    type Config = UringDeviceConfig;
+    type IoBufferAllocator = UringAllocator;
+
+    async fn open(config: UringDeviceConfig) -> DeviceResult<Self> {
+        Self::open(config).await
+    }
+
+    async fn write<B>(&self, buf: B, range: impl IoRange, region: RegionId, offset: usize) -> (DeviceResult<usize>, B)
+    where
+        B: IoBuf,
+    {
+        let file_capacity = self.inner.config.file_size;
+
+        let range = range.bounds(0..buf.as_ref().len());
+        let len = RangeBoundsExt::size(&range).unwrap();
+
+        assert!(
+            offset + len <= file_capacity,
+            "offset ({offset}) + len ({len}) <= file capacity ({file_capacity})"
+        );
+
+        self.io(IoringOp::Write, buf, range, region, offset, |buf| {
+            buf.as_ref().as_ptr() as *mut u8
+        })
+        .await
+    }
+
+    async fn read<B>(&self, buf: B, range: impl IoRange, region: RegionId, offset: usize) -> (DeviceResult<usize>, B)
+    where
+        B: IoBufMut,
+    {
+        let file_capacity = self.inner.config.file_size;
+
+        let range = range.bounds(0..buf.as_ref().len());
+        let len = RangeBoundsExt::size(&range).unwrap();
+
+        assert!(
+            offset + len <= file_capacity,
+            "offset ({offset}) + len ({len}) <= file capacity ({file_capacity})"
+        );
+
+        self.io(IoringOp::Read, buf, range, region, offset, |buf| {
+            buf.as_mut().as_mut_ptr()
+        })
+        .await
+    }
+
+    async fn flush(&self) -> DeviceResult<()> {
+        self.inner.fs.flush().await
+    }
+
+    async fn read_meta(&self, name: &str) -> DeviceResult<Option<Vec<u8>>> {
+        self.inner.fs.read_meta(name).await
+    }
+
+    async fn write_meta(&self, name: &str, buf: Vec<u8>) -> DeviceResult<()> {
+        self.inner.fs.write_meta(name, buf).await
+    }
+
+    fn capacity(&self) -> usize {
+        self.inner.config.capacity
+    }
+
+    fn regions(&self) -> usize {
+        self.inner.fs.regions()
+    }
+
+    fn align(&self) -> usize {
+        self.inner.config.align
+    }
+
+    fn io_size(&self) -> usize {
+        self.inner.config.io_size
+    }
+
+    fn io_buffer_allocator(&self) -> &Self::IoBufferAllocator {
+        &self.inner.io_buffer_allocator
+    }
+
+    fn io_buffer(&self, len: usize, capacity: usize) -> VecA<u8, Self::IoBufferAllocator> {
+        assert!(len <= capacity);
+        let mut buf = VecA::with_capacity_in(capacity, self.inner.io_buffer_allocator.clone());
+        unsafe { buf.set_len(len) };
+        buf
+    }
+}
+
+impl UringDevice {
+    pub async fn open(config: UringDeviceConfig) -> DeviceResult<Self> {
+        config.assert();
+
+        let fs = FsDevice::open(config.fs()).await?;
+
+        let ring = Ring::new(config.ring_entries)?;
+
+        let fixed = Arc::new(FixedBuffers::new(
+            config.align,
+            config.fixed_buffer_size,
+            config.fixed_buffers,
+        ));
+        let registered = config.fixed_buffers > 0 && {
+            let iovecs = fixed.iovecs();
+            // Registering buffers may fail with `RLIMIT_MEMLOCK` on old kernels, fall back to unregistered buffers.
+            match unsafe {
+                io_uring_register(
+                    ring.fd.as_fd(),
+                    IoringRegisterOp::RegisterBuffers,
+                    iovecs.as_ptr() as *const c_void,
+                    iovecs.len() as u32,
+                )
+            } {
+                Ok(_) => true,
+                Err(e) => {
+                    tracing::warn!(
+                        "register io_uring fixed buffers error, fall back to unregistered buffers: {}",
+                        e
+                    );
+                    false
+                }
+            }
+        };
+
+        let (tx, rx) = mpsc::channel();
+        let reactor = Reactor {
+            ring,
+            rx,
+            pending: VecDeque::new(),
+            inflights: vec![],
+            free: vec![],
+            count: 0,
+            _fixed: fixed.clone(),
+        };
+        std::thread::Builder::new()
+            .name("foyer-uring".to_string())
+            .spawn(move || {
+                // Releasing the buffers of the inflight I/Os on unwinding is unsound, abort instead.
+                if std::panic::catch_unwind(AssertUnwindSafe(|| reactor.run())).is_err() {
+                    tracing::error!("io_uring reactor panicked, abort");
+                    std::process::abort();
+                }
+            })?;
+
+        let io_buffer_allocator = UringAllocator {
+            fixed,
+            registered,
+            fallback: AlignedAllocator::new(config.align),
+        };
+
+        let inner = UringDeviceInner {
+            config,
+            fs,
+            tx,
+            io_buffer_allocator,
+        };
+
+        Ok(Self { inner: Arc::new(inner) })
+    }
+
+    /// Submit the I/O on `range` of `buf`, and resubmit the rest of it on short reads and writes.
+    ///
+    /// Return the bytes transferred, which is less than the range only if a read reaches the end of the file.
+    async fn io<B>(
+        &self,
+        opcode: IoringOp,
+        mut buf: B,
+        range: Range<usize>,
+        region: RegionId,
+        offset: usize,
+        ptr: impl Fn(&mut B) -> *mut u8,
+    ) -> (DeviceResult<usize>, B)
+    where
+        B: Send + 'static,
+    {
+        let len = range.end - range.start;
+        let mut done = 0;
+
+        while done < len {
+            let rx = {
+                let mut buf = Box::new(buf);
+                let ptr = unsafe { ptr(&mut buf).add(range.start + done) };
+                self.submit(opcode, region, ptr, len - done, offset + done, buf)
+            };
+            let (res, b) = Self::wait(rx).await;
+            buf = b;
+            match res {
+                Ok(0) if opcode == IoringOp::Read => break,
+                Ok(0) => return (Err(io::Error::from(io::ErrorKind::WriteZero).into()), buf),
+                Ok(bytes) => done += bytes,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return (Err(e.into()), buf),
+            }
+        }
+
+        (Ok(done), buf)
+    }
+
+    fn submit<B>(
+        &self,
+        opcode: IoringOp,
+        region: RegionId,
+        ptr: *mut u8,
+        len: usize,
+        offset: usize,
+        buf: Box<B>,
+    ) -> oneshot::Receiver<UringResponse>
+    where
+        B: Send + 'static,
+    {
+        let allocator = &self.inner.io_buffer_allocator;
+        let buf_index = match allocator.fixed.index(ptr, len) {
+            Some(index) if allocator.registered => Some(index as u16),
+            _ => None,
+        };
+
+        let (tx, rx) = oneshot::channel();
+        let request = UringRequest {
+            opcode,
+            fd: self.inner.fs.fd(region),
+            ptr,
+            len: len as u32,
+            offset: offset as u64,
+            buf_index,
+            buf,
+            tx,
+        };
+
+        if let Err(mpsc::SendError(request)) = self.inner.tx.send(request) {
+            let _ = request.tx.send((Err(Self::exited()), request.buf));
+        }
+        rx
+    }
+
+    async fn wait<B>(rx: oneshot::Receiver<UringResponse>) -> (io::Result<usize>, B)
+    where
+        B: Send + 'static,
+    {
+        let Ok((res, buf)) = rx.await else {
+            unreachable!("the io_uring reactor always responds with the io buffer or aborts");
+        };
+        let buf = *buf.downcast::<B>().unwrap();
+        (res, buf)
+    }
+
+    fn exited() -> io::Error {
+        io::Error::new(io::ErrorKind::BrokenPipe, "io_uring reactor exited")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BufMut;
+
+    use super::*;
+    use crate::device::DeviceExt;
+
+    const FILES: usize = 8;
+    const FILE_CAPACITY: usize = 64 * 1024; // 64 KiB
+    const CAPACITY: usize = FILES * FILE_CAPACITY; // 512 KiB
+    const ALIGN: usize = 4 * 1024;
+
+    fn config(dir: impl AsRef<Path>) -> UringDeviceConfig {
+        UringDeviceConfig {
+            dir: dir.as_ref().into(),
+            capacity: CAPACITY,
+            file_size: FILE_CAPACITY,
+            align: ALIGN,
+            io_size: ALIGN,
+            ring_entries: 4,
+            fixed_buffers: 4,
+            fixed_buffer_size: 2 * ALIGN,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_uring_device_simple() {
+        let dir = tempfile::tempdir().unwrap();
+        let dev = UringDevice::open(config(dir.path())).await.unwrap();
+
+        let mut wbuffer = dev.io_buffer(ALIGN, ALIGN);
+        (&mut wbuffer[..]).put_slice(&[b'x'; ALIGN]);
+        let mut rbuffer = dev.io_buffer(ALIGN, ALIGN);
+        (&mut rbuffer[..]).put_slice(&[0; ALIGN]);
+
+        // Io buffers are allocated from the registered fixed buffers.
+        let fixed = &dev.io_buffer_allocator().fixed;
+        assert!(fixed.index(wbuffer.as_ptr(), ALIGN).is_some());
+        assert!(fixed.index(rbuffer.as_ptr(), ALIGN).is_some());
+
+        let (res, wbuffer) = dev.write(wbuffer, .., 0, 0).await;
+        assert_eq!(res.unwrap(), ALIGN);
+        let (res, rbuffer) = dev.read(rbuffer, .., 0, 0).await;
+        assert_eq!(res.unwrap(), ALIGN);
+
+        assert_eq!(&wbuffer, &rbuffer);
+    }
+
+    #[tokio::test]
+    async fn test_uring_device_read_eof() {
+        let dir = tempfile::tempdir().unwrap();
+        let dev = UringDevice::open(config(dir.path())).await.unwrap();
+
+        let mut wbuffer = dev.io_buffer(2 * ALIGN, 2 * ALIGN);
+        (&mut wbuffer[..]).put_slice(&[b'x'; 2 * ALIGN]);
+        let (res, _) = dev.write(wbuffer, .., 0, 0).await;
+        assert_eq!(res.unwrap(), 2 * ALIGN);
+
+        // The read stops at the end of the file instead of failing.
+        std::fs::File::options()
+            .write(true)
+            .open(dir.path().join("foyer-cache-00000000"))
+            .unwrap()
+            .set_len(ALIGN as u64)
+            .unwrap();
+        let rbuffer = dev.io_buffer(2 * ALIGN, 2 * ALIGN);
+        let (res, rbuffer) = dev.read(rbuffer, .., 0, 0).await;
+        assert_eq!(res.unwrap(), ALIGN);
+        assert_eq!(&rbuffer[..ALIGN], &[b'x'; ALIGN]);
+    }
+
+    #[tokio::test]
+    async fn test_uring_device_batch() {
+        let dir = tempfile::tempdir().unwrap();
+        let dev = UringDevice::open(config(dir.path())).await.unwrap();
+
+        // More concurrent I/Os than ring entries, completion queue entries and fixed buffers.
+        let futures = (0..FILES as RegionId)
+            .flat_map(|region| (0..FILE_CAPACITY / ALIGN).map(move |i| (region, i)))
+            .map(|(region, i)| {
+                let dev = dev.clone();
+                async move {
+                    let mut buf = dev.io_buffer(ALIGN, ALIGN);
+                    (&mut buf[..]).put_slice(&[(region as usize + i) as u8; ALIGN]);
+                    let (res, _) = dev.write(buf, .., region, i * ALIGN).await;
+                    assert_eq!(res.unwrap(), ALIGN);
+                }
+            });
+        futures::future::join_all(futures).await;
+
+        for region in 0..FILES as RegionId {
+            let buf = dev.load(region, ..).await.unwrap();
+            assert_eq!(buf.len(), FILE_CAPACITY);
+            for (i, chunk) in buf.chunks(ALIGN).enumerate() {
+                assert!(chunk.iter().all(|b| *b == (region as usize + i) as u8));
+            }
+        }
+
+        // Unregistered buffers are supported.
+        let mut buf = VecA::with_capacity_in(ALIGN * 4, AlignedAllocator::new(ALIGN));
+        buf.extend_from_slice(&[b'x'; ALIGN * 4]);
+        let (res, _) = dev.write(buf, .., 0, 0).await;
+        assert_eq!(res.unwrap(), ALIGN * 4);
+        let buf = dev.load(0, 0..ALIGN * 4).await.unwrap();
+        assert!(buf.iter().all(|b| *b == b'x'));
+    }
+
+    #[tokio::test]
+    async fn test_uring_device_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let dev = UringDevice::open(config(dir.path())).await.unwrap();
+
+        let mut buf = dev.io_buffer(ALIGN, ALIGN);
+        (&mut buf[..]).put_slice(&[b'x'; ALIGN]);
+        let (res, _) = dev.write(buf, .., 1, ALIGN).await;
+        res.unwrap();
+        dev.write_meta("test", vec![1; 42]).await.unwrap();
+        dev.flush().await.unwrap();
+        drop(dev);
+
+        let dev = UringDevice::open(config(dir.path())).await.unwrap();
+        let buf = dev.load(1, ALIGN..2 * ALIGN).await.unwrap();
+        assert!(buf.iter().all(|b| *b == b'x'));
+        assert_eq!(dev.read_meta("test").await.unwrap(), Some(vec![1; 42]));
+    }
+
+    #[test]
+    fn test_config_builder() {
+        let dir = std::env::current_dir().unwrap();
+        let config = UringDeviceConfigBuilder::new(dir).with_ring_entries(100).build();
+
+        println!("{config:?}");
+
+        assert_eq!(config.ring_entries, 128);
+        config.assert();
+    }
+}
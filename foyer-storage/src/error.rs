@@ -21,6 +21,8 @@ pub enum Error {
     Device(#[from] DeviceError),
     #[error("buffer error: {0}")]
     Buffer(#[from] BufferError),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
     #[error("other error: {0}")]
     Other(#[from] anyhow::Error),
 }
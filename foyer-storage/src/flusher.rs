@@ -12,7 +12,7 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 
-use std::{fmt::Debug, sync::Arc};
+use std::{fmt::Debug, sync::Arc, time::SystemTime};
 
 use either::Either;
 use foyer_common::code::{StorageKey, StorageValue};
@@ -38,6 +38,7 @@ where
     pub value: V,
     pub sequence: Sequence,
     pub compression: Compression,
+    pub expire_at: Option<SystemTime>,
 }
 
 impl<K, V> Debug for Entry<K, V>
@@ -49,6 +50,7 @@ where
         f.debug_struct("Entry")
             .field("sequence", &self.sequence)
             .field("compression", &self.compression)
+            .field("expire_at", &self.expire_at)
             .finish()
     }
 }
@@ -64,6 +66,7 @@ where
             value: self.value.clone(),
             sequence: self.sequence,
             compression: self.compression,
+            expire_at: self.expire_at,
         }
     }
 }
@@ -187,7 +190,13 @@ where
 
         let timer = self.metrics.inner_op_duration_update_catalog.start_timer();
         for PositionedEntry {
-            entry: Entry { key, sequence, .. },
+            entry:
+                Entry {
+                    key,
+                    sequence,
+                    expire_at,
+                    ..
+                },
             region,
             offset,
             len,
@@ -197,7 +206,7 @@ where
             let index = Index::Region {
                 view: self.region_manager.region(&region).view(offset as u32, len as u32),
             };
-            let item = Item::new(sequence, index);
+            let item = Item::new(sequence, index, expire_at);
             self.catalog.insert(key, item);
         }
         drop(timer);
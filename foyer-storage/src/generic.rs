@@ -19,9 +19,9 @@ use std::{
     marker::PhantomData,
     sync::{
         atomic::{AtomicU64, Ordering},
-        Arc,
+        Arc, OnceLock,
     },
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::anyhow;
@@ -53,14 +53,20 @@ use crate::{
     judge::Judges,
     metrics::{Metrics, METRICS},
     reclaimer::Reclaimer,
-    region::{Region, RegionHeader, RegionId},
-    region_manager::RegionManager,
+    region::{Region, RegionHeader, RegionId, Version},
+    region_manager::{EvictionCheckpoint, RegionManager},
     reinsertion::{ReinsertionContext, ReinsertionPolicy},
     storage::{Storage, StorageWriter},
+    tombstone::{Tombstone, TombstoneLog, TombstoneLogConfig, Tombstones},
 };
 
 const DEFAULT_BROADCAST_CAPACITY: usize = 4096;
 
+/// Name of the device metadata that holds the clear watermark.
+///
+/// Entries with a sequence lower than the watermark are cleared and will not be recovered.
+const CLEAR_WATERMARK_META: &str = "clear";
+
 pub struct GenericStoreConfig<K, V, D>
 where
     K: StorageKey,
@@ -103,6 +109,11 @@ where
 
     /// Compression algorithm.
     pub compression: Compression,
+
+    /// Tombstone log configurations.
+    ///
+    /// Removed entries may be recovered after restart if the tombstone log is disabled.
+    pub tombstone_log_config: Option<TombstoneLogConfig>,
 }
 
 impl<K, V, D> Debug for GenericStoreConfig<K, V, D>
@@ -123,6 +134,7 @@ where
             .field("clean_region_threshold", &self.clean_region_threshold)
             .field("recover_concurrency", &self.recover_concurrency)
             .field("compression", &self.compression)
+            .field("tombstone_log_config", &self.tombstone_log_config)
             .finish()
     }
 }
@@ -146,6 +158,7 @@ where
             clean_region_threshold: self.clean_region_threshold,
             recover_concurrency: self.recover_concurrency,
             compression: self.compression,
+            tombstone_log_config: self.tombstone_log_config.clone(),
         }
     }
 }
@@ -201,6 +214,10 @@ where
 
     compression: Compression,
 
+    tombstone_log_config: Option<TombstoneLogConfig>,
+    /// Opened after recovery if configured.
+    tombstone_log: OnceLock<TombstoneLog>,
+
     _marker: PhantomData<V>,
 }
 
@@ -219,6 +236,7 @@ where
         assert!(device.regions() >= config.flushers * 2);
 
         let region_manager = Arc::new(RegionManager::new(
+            &config.name,
             device.regions(),
             config.eviction_config,
             device.clone(),
@@ -254,6 +272,8 @@ where
             reclaimers_stop_tx,
             metrics: metrics.clone(),
             compression: config.compression,
+            tombstone_log_config: config.tombstone_log_config,
+            tombstone_log: OnceLock::new(),
             _marker: PhantomData,
         };
         let store = Self { inner: Arc::new(inner) };
@@ -339,6 +359,17 @@ where
             handle.await.unwrap();
         }
 
+        if let Some(tombstone_log) = self.inner.tombstone_log.get() {
+            tombstone_log.close().await?;
+        }
+
+        // Checkpoint the region eviction state after flushers and reclaimers are stopped.
+        let checkpoint = self.inner.region_manager.eviction_checkpoint();
+        self.inner
+            .device
+            .write_meta(EvictionCheckpoint::META, checkpoint.write())
+            .await?;
+
         Ok(())
     }
 
@@ -354,7 +385,12 @@ where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
     {
-        Ok(self.inner.catalog.lookup(key).is_some())
+        Ok(self
+            .inner
+            .catalog
+            .lookup(key)
+            .map(|item| !item.is_expired())
+            .unwrap_or_default())
     }
 
     #[tracing::instrument(skip_all)]
@@ -365,7 +401,16 @@ where
     {
         let now = Instant::now();
 
-        let (_sequence, index) = match self.inner.catalog.lookup(key) {
+        let (sequence, index) = match self.inner.catalog.lookup(key) {
+            Some(item) if item.is_expired() => {
+                // Remove index if the entry is expired, unless a newer entry is inserted since the lookup.
+                self.inner.catalog.remove_if_sequence(key, *item.sequence());
+                self.inner
+                    .metrics
+                    .op_duration_lookup_miss
+                    .observe(now.elapsed().as_secs_f64());
+                return Ok(None);
+            }
             Some(item) => item.consume(),
             None => {
                 self.inner
@@ -398,7 +443,7 @@ where
                     Some(buf) => buf,
                     None => {
                         // Remove index if the storage layer fails to lookup it (because of region version mismatch).
-                        self.inner.catalog.remove(key);
+                        self.inner.catalog.remove_if_sequence(key, sequence);
                         self.inner
                             .metrics
                             .op_duration_lookup_miss
@@ -414,7 +459,7 @@ where
                     }
                     Err(e) => {
                         // Remove index if the storage layer fails to lookup it (because of entry magic mismatch).
-                        self.inner.catalog.remove(key);
+                        self.inner.catalog.remove_if_sequence(key, sequence);
                         Err(e)
                     }
                 };
@@ -437,20 +482,71 @@ where
     {
         let _timer = self.inner.metrics.op_duration_remove.start_timer();
 
-        let res = self.inner.catalog.remove(key).is_some();
+        let sequence = self.inner.sequence.fetch_add(1, Ordering::Relaxed);
+        let res = self.inner.catalog.remove(key, sequence).is_some();
+
+        // Log the removal even if the key is not indexed, because the entry may be taken by the reclaimer and
+        // reinserted with its original sequence, or be flushed after the removal.
+        if let Some(tombstone_log) = self.inner.tombstone_log.get() {
+            tombstone_log.append(Tombstone {
+                hash: self.inner.catalog.hash(key),
+                sequence,
+            });
+        }
 
         Ok(res)
     }
 
     #[tracing::instrument(skip(self))]
-    fn clear(&self) -> Result<()> {
+    async fn flush_removes(&self) -> Result<()> {
+        match self.inner.tombstone_log.get() {
+            Some(tombstone_log) => tombstone_log.flush().await,
+            None => Ok(()),
+        }
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn clear(&self) -> Result<()> {
+        // Wait for the regions being reclaimed and block the following reclamation.
+        let _guard = self.inner.region_manager.reclaim_lock().write().await;
+
+        // Persist the watermark before wiping regions, so the cleared entries that are not wiped (e.g. entries in the
+        // region being flushed) will not be recovered either.
+        let watermark = self.inner.sequence.load(Ordering::Relaxed);
+        let mut buf = Vec::with_capacity(16);
+        buf.put_u64(watermark);
+        buf.put_u64(checksum(&buf));
+        self.inner.device.write_meta(CLEAR_WATERMARK_META, buf).await?;
+
+        self.inner.catalog.set_watermark(watermark);
         self.inner.catalog.clear();
 
-        // TODO(MrCroxx): set all regions as clean?
+        while let Some(region_id) = self.inner.region_manager.eviction_pop() {
+            let region = self.inner.region_manager.region(&region_id);
+
+            // wait unfinished readers
+            while region.refs().load(Ordering::SeqCst) > 0 {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+
+            region.wipe().await?;
+            self.inner.region_manager.clean_regions().release(region_id);
+            self.inner.metrics.total_bytes.sub(region.device().region_size() as u64);
+        }
 
         Ok(())
     }
 
+    async fn clear_watermark(&self) -> Result<Sequence> {
+        let Some(buf) = self.inner.device.read_meta(CLEAR_WATERMARK_META).await? else {
+            return Ok(0);
+        };
+        if buf.len() != 16 || checksum(&buf[..8]) != (&buf[8..]).get_u64() {
+            return Err(anyhow!("invalid clear watermark, len: {}", buf.len()).into());
+        }
+        Ok((&buf[..8]).get_u64())
+    }
+
     pub(crate) fn catalog(&self) -> &Arc<Catalog<K, V>> {
         &self.inner.catalog
     }
@@ -463,6 +559,15 @@ where
     async fn recover(&self, concurrency: usize) -> Result<Sequence> {
         tracing::info!("start store recovery");
 
+        let watermark = self.clear_watermark().await?;
+        self.inner.catalog.set_watermark(watermark);
+
+        let tombstones = match &self.inner.tombstone_log_config {
+            Some(config) => Tombstones::new(TombstoneLog::load(config).await?),
+            None => Tombstones::default(),
+        };
+        let tombstones = Arc::new(tombstones);
+
         let semaphore = Arc::new(Semaphore::new(concurrency));
 
         let mut handles = vec![];
@@ -470,16 +575,17 @@ where
             let semaphore = semaphore.clone();
             let region_manager = self.inner.region_manager.clone();
             let indices = self.inner.catalog.clone();
+            let tombstones = tombstones.clone();
             let handle = tokio::spawn(async move {
                 let permit = semaphore.acquire().await;
-                let res = Self::recover_region(region_id, region_manager, indices).await;
+                let res = Self::recover_region(region_id, region_manager, indices, tombstones).await;
                 drop(permit);
                 res
             });
             handles.push(handle);
         }
 
-        let mut recovered = 0;
+        let mut recovered = vec![];
         let mut sequence = 0;
 
         let results = try_join_all(handles).await.map_err(anyhow::Error::from)?;
@@ -487,11 +593,28 @@ where
         for (region_id, result) in results.into_iter().enumerate() {
             if let Some(seq) = result? {
                 tracing::debug!("region {} is recovered", region_id);
-                recovered += 1;
+                recovered.push(region_id as RegionId);
                 sequence = std::cmp::max(sequence, seq);
             }
         }
 
+        let checkpoint = match self.inner.device.read_meta(EvictionCheckpoint::META).await? {
+            Some(buf) if !buf.is_empty() => {
+                // The checkpoint only matches the state at the last close, invalidate it in case the store is not
+                // closed gracefully next time.
+                self.inner.device.write_meta(EvictionCheckpoint::META, vec![]).await?;
+                EvictionCheckpoint::read(&buf).unwrap_or_else(|e| {
+                    tracing::warn!("ignore invalid eviction checkpoint: {}", e);
+                    EvictionCheckpoint::default()
+                })
+            }
+            _ => EvictionCheckpoint::default(),
+        };
+        self.inner
+            .region_manager
+            .eviction_restore(&checkpoint, recovered.iter().copied());
+        let recovered = recovered.len();
+
         tracing::info!("finish store recovery, {} region recovered", recovered);
         self.inner
             .metrics
@@ -503,23 +626,35 @@ where
             self.inner.region_manager.clean_regions().flash();
         }
 
-        Ok(sequence)
+        // Rewrite the tombstone log with the tombstones that are still in use.
+        if let Some(config) = self.inner.tombstone_log_config.clone() {
+            let tombstone_log = TombstoneLog::open(config, tombstones.used()).await?;
+            self.inner.tombstone_log.set(tombstone_log).unwrap();
+        }
+
+        // The following sequences must not fall behind the clear watermark and the tombstones.
+        Ok([sequence, watermark, tombstones.sequence()].into_iter().max().unwrap())
     }
 
-    /// Return `Some(max sequence)` if region is valid, otherwise `None`
+    /// Return `Some(max sequence)` if region is valid, otherwise `None`.
+    ///
+    /// Valid regions are pushed into the eviction container by the caller after all regions are recovered.
     async fn recover_region(
         region_id: RegionId,
         region_manager: Arc<RegionManager<D>>,
         catalog: Arc<Catalog<K, V>>,
+        tombstones: Arc<Tombstones>,
     ) -> Result<Option<Sequence>> {
         let region = region_manager.region(&region_id).clone();
         let mut sequence = 0;
         let res = if let Some(mut iter) = RegionEntryIter::<K, V, D>::open(region).await? {
             while let Some((key, item)) = iter.next().await? {
                 sequence = std::cmp::max(sequence, *item.sequence());
+                if item.is_expired() || tombstones.is_removed(catalog.hash(&key), *item.sequence()) {
+                    continue;
+                }
                 catalog.insert(key, item);
             }
-            region_manager.eviction_push(region_id);
             Some(sequence)
         } else {
             region_manager.clean_regions().release(region_id);
@@ -577,6 +712,7 @@ where
                     key: key.clone(),
                     value: value.clone(),
                 },
+                writer.expire_at,
             ),
         );
 
@@ -587,6 +723,7 @@ where
                 key,
                 value,
                 compression: writer.compression,
+                expire_at: writer.expire_at,
             })
             .unwrap();
 
@@ -621,6 +758,7 @@ where
     is_inserted: bool,
     is_skippable: bool,
     compression: Compression,
+    expire_at: Option<SystemTime>,
 }
 
 impl<K, V, D> GenericStoreWriter<K, V, D>
@@ -642,6 +780,7 @@ where
             is_inserted: false,
             is_skippable: false,
             compression,
+            expire_at: None,
         }
     }
 
@@ -684,6 +823,14 @@ where
     pub fn set_compression(&mut self, compression: Compression) {
         self.compression = compression
     }
+
+    pub fn set_ttl(&mut self, ttl: Duration) {
+        self.expire_at = Some(SystemTime::now() + ttl);
+    }
+
+    pub fn set_expire_at(&mut self, expire_at: Option<SystemTime>) {
+        self.expire_at = expire_at;
+    }
 }
 
 impl<K, V, D> Debug for GenericStoreWriter<K, V, D>
@@ -749,17 +896,24 @@ pub struct EntryHeader {
     pub sequence: Sequence,
     pub checksum: u64,
     pub compression: Compression,
+    /// Encoded as milliseconds since unix epoch, `0` means never expire.
+    pub expire_at: Option<SystemTime>,
 }
 
 impl EntryHeader {
     pub const fn serialized_len() -> usize {
-        4 + 4 + 8 + 8 + 4 /* magic & compression */
+        4 + 4 + 8 + 8 + 8 + 4 /* magic & compression */
     }
 
     pub fn write(&self, mut buf: &mut [u8]) {
         buf.put_u32(self.key_len);
         buf.put_u32(self.value_len);
         buf.put_u64(self.sequence);
+        buf.put_u64(
+            self.expire_at
+                .map(|expire_at| expire_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64)
+                .unwrap_or_default(),
+        );
         buf.put_u64(self.checksum);
 
         let v = ENTRY_MAGIC | self.compression.to_u8() as u32;
@@ -770,6 +924,10 @@ impl EntryHeader {
         let key_len = buf.get_u32();
         let value_len = buf.get_u32();
         let sequence = buf.get_u64();
+        let expire_at = match buf.get_u64() {
+            0 => None,
+            millis => Some(UNIX_EPOCH + Duration::from_millis(millis)),
+        };
         let checksum = buf.get_u64();
 
         let v = buf.get_u32();
@@ -785,6 +943,7 @@ impl EntryHeader {
             sequence,
             compression,
             checksum,
+            expire_at,
         })
     }
 }
@@ -794,7 +953,7 @@ impl EntryHeader {
 /// # Safety
 ///
 /// `buf.len()` must exactly fit entry size
-fn read_entry<K, V>(buf: &[u8]) -> Result<(K, V)>
+pub fn read_entry<K, V>(buf: &[u8]) -> Result<(K, V)>
 where
     K: StorageKey,
     V: StorageValue,
@@ -866,9 +1025,12 @@ where
             None => return Ok(None),
         };
 
-        let Ok(_) = RegionHeader::read(slice.as_ref()) else {
+        let Ok(header) = RegionHeader::read(slice.as_ref()) else {
             return Ok(None);
         };
+        if header.version != Version::latest() {
+            return Ok(None);
+        }
 
         Ok(Some(Self {
             region,
@@ -939,37 +1101,13 @@ where
             Index::Region {
                 view: self.region.view(self.cursor as u32, entry_len as u32),
             },
+            header.expire_at,
         );
 
         self.cursor += entry_len;
 
         Ok(Some((key, info)))
     }
-
-    pub async fn next_kv(&mut self) -> Result<Option<(K, V, usize)>> {
-        let (_, item) = match self.next().await {
-            Ok(Some(res)) => res,
-            Ok(None) => return Ok(None),
-            Err(e) => return Err(e),
-        };
-
-        let Index::Region { view } = item.index() else {
-            unreachable!("kv loaded from region must have index of region")
-        };
-
-        // TODO(MrCroxx): Optimize if all key, value and footer are in the same read block.
-        let start = *view.offset() as usize;
-        let end = start + *view.len() as usize;
-        let Some(slice) = self.region.load_range(start..end).await? else {
-            return Ok(None);
-        };
-        let res = read_entry::<K, V>(slice.as_ref())
-            .ok()
-            .map(|(k, v)| (k, v, slice.len()));
-        drop(slice);
-
-        Ok(res)
-    }
 }
 
 impl<K, V, D> StorageWriter<K, V> for GenericStoreWriter<K, V, D>
@@ -1001,6 +1139,14 @@ where
     fn set_compression(&mut self, compression: Compression) {
         self.set_compression(compression)
     }
+
+    fn set_ttl(&mut self, ttl: Duration) {
+        self.set_ttl(ttl)
+    }
+
+    fn set_sequence(&mut self, sequence: Sequence) {
+        self.set_sequence(sequence)
+    }
 }
 
 impl<K, V, D> Storage<K, V> for GenericStore<K, V, D>
@@ -1052,8 +1198,16 @@ where
         self.remove(key)
     }
 
-    fn clear(&self) -> Result<()> {
-        self.clear()
+    fn sequence(&self) -> Sequence {
+        self.inner.sequence.fetch_add(1, Ordering::Relaxed)
+    }
+
+    async fn flush_removes(&self) -> Result<()> {
+        self.flush_removes().await
+    }
+
+    async fn clear(&self) -> Result<()> {
+        self.clear().await
     }
 }
 
@@ -1061,11 +1215,14 @@ where
 mod tests {
     use std::path::PathBuf;
 
-    use foyer_memory::FifoConfig;
+    use foyer_memory::{FifoConfig, LruConfig};
 
     use super::*;
     use crate::{
-        device::fs::{FsDevice, FsDeviceConfig},
+        device::{
+            fs::{FsDevice, FsDeviceConfig},
+            mem::{MemDevice, MemDeviceConfigBuilder},
+        },
         storage::StorageExt,
         test_utils::JudgeRecorder,
     };
@@ -1104,6 +1261,7 @@ mod tests {
             recover_concurrency: 2,
             clean_region_threshold: 1,
             compression: Compression::None,
+            tombstone_log_config: None,
         };
 
         let store = TestStore::open(config).await.unwrap();
@@ -1150,6 +1308,7 @@ mod tests {
             recover_concurrency: 2,
             clean_region_threshold: 1,
             compression: Compression::None,
+            tombstone_log_config: None,
         };
         let store = TestStore::open(config).await.unwrap();
 
@@ -1165,4 +1324,376 @@ mod tests {
 
         drop(store);
     }
+
+    #[tokio::test]
+    // TODO(MrCroxx): use `expect` after `lint_reasons` is stable.
+    #[allow(clippy::identity_op)]
+    async fn test_mem_device_recovery() {
+        const KB: usize = 1024;
+        const MB: usize = 1024 * 1024;
+
+        let recorder = Arc::new(JudgeRecorder::default());
+        let admissions: Vec<Arc<dyn AdmissionPolicy<Key = u64, Value = Vec<u8>>>> = vec![recorder.clone()];
+        let reinsertions: Vec<Arc<dyn ReinsertionPolicy<Key = u64, Value = Vec<u8>>>> = vec![recorder.clone()];
+
+        let config = GenericStoreConfig::<u64, Vec<u8>, MemDevice> {
+            name: "".to_string(),
+            eviction_config: EvictionConfig::Fifo(FifoConfig {}),
+            device_config: MemDeviceConfigBuilder::new(16 * MB)
+                .with_region_size(4 * MB)
+                .with_align(4 * KB)
+                .with_io_size(4 * KB)
+                .build(),
+            catalog_bits: 1,
+            admissions,
+            reinsertions,
+            flushers: 1,
+            reclaimers: 1,
+            recover_concurrency: 2,
+            clean_region_threshold: 1,
+            compression: Compression::None,
+            tombstone_log_config: None,
+        };
+
+        let store = GenericStore::open(config.clone()).await.unwrap();
+        for i in 0..21 {
+            store.insert(i, vec![i as u8; 1 * MB]).await.unwrap();
+        }
+        store.close().await.unwrap();
+        drop(store);
+
+        let remains = recorder.remains();
+
+        // Reopen with a clone of the config to recover from the same memory.
+        let store = GenericStore::open(config).await.unwrap();
+        for i in 0..21 {
+            if remains.contains(&i) {
+                assert_eq!(store.lookup(&i).await.unwrap().unwrap(), vec![i as u8; 1 * MB],);
+            } else {
+                assert!(store.lookup(&i).await.unwrap().is_none());
+            }
+        }
+        store.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_ttl() {
+        const KB: usize = 1024;
+        const MB: usize = 1024 * 1024;
+
+        let tempdir = tempfile::tempdir().unwrap();
+
+        let config = || TestStoreConfig {
+            name: "".to_string(),
+            eviction_config: EvictionConfig::Fifo(FifoConfig {}),
+            device_config: FsDeviceConfig {
+                dir: PathBuf::from(tempdir.path()),
+                capacity: 16 * MB,
+                file_size: 4 * MB,
+                align: 4 * KB,
+                io_size: 4 * KB,
+            },
+            catalog_bits: 1,
+            admissions: vec![],
+            reinsertions: vec![],
+            flushers: 1,
+            reclaimers: 0,
+            recover_concurrency: 2,
+            clean_region_threshold: 1,
+            compression: Compression::None,
+            tombstone_log_config: None,
+        };
+
+        let store = TestStore::open(config()).await.unwrap();
+
+        store.insert(1, vec![1; KB]).await.unwrap();
+        store
+            .insert_with_ttl(2, vec![2; KB], Duration::from_millis(100))
+            .await
+            .unwrap();
+        store
+            .insert_with_ttl(3, vec![3; KB], Duration::from_secs(3600))
+            .await
+            .unwrap();
+
+        assert_eq!(store.lookup(&2).await.unwrap(), Some(vec![2; KB]));
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        assert_eq!(store.lookup(&1).await.unwrap(), Some(vec![1; KB]));
+        assert!(!store.exists(&2).unwrap());
+        assert!(store.lookup(&2).await.unwrap().is_none());
+        assert_eq!(store.lookup(&3).await.unwrap(), Some(vec![3; KB]));
+
+        store.close().await.unwrap();
+        drop(store);
+
+        // Expired entries are not recovered.
+        let store = TestStore::open(config()).await.unwrap();
+
+        assert_eq!(store.lookup(&1).await.unwrap(), Some(vec![1; KB]));
+        assert!(store.lookup(&2).await.unwrap().is_none());
+        assert_eq!(store.lookup(&3).await.unwrap(), Some(vec![3; KB]));
+
+        store.close().await.unwrap();
+        drop(store);
+    }
+
+    #[tokio::test]
+    // TODO(MrCroxx): use `expect` after `lint_reasons` is stable.
+    #[allow(clippy::identity_op)]
+    async fn test_eviction_checkpoint() {
+        const KB: usize = 1024;
+        const MB: usize = 1024 * 1024;
+
+        let tempdir = tempfile::tempdir().unwrap();
+
+        let config = || TestStoreConfig {
+            name: "".to_string(),
+            eviction_config: EvictionConfig::Lru(LruConfig {
+                high_priority_pool_ratio: 0.0,
+            }),
+            device_config: FsDeviceConfig {
+                dir: PathBuf::from(tempdir.path()),
+                capacity: 16 * MB,
+                file_size: 4 * MB,
+                align: 4 * KB,
+                io_size: 4 * KB,
+            },
+            catalog_bits: 1,
+            admissions: vec![],
+            reinsertions: vec![],
+            flushers: 1,
+            reclaimers: 0,
+            recover_concurrency: 2,
+            clean_region_threshold: 1,
+            compression: Compression::None,
+            tombstone_log_config: None,
+        };
+
+        // regions:
+        // [0, 1, 2]
+        // [3, 4, 5]
+        // [6, 7, 8]
+        let store = TestStore::open(config()).await.unwrap();
+        for i in 0..9 {
+            store.insert(i, vec![i as u8; 1 * MB]).await.unwrap();
+        }
+        store.close().await.unwrap();
+        drop(store);
+
+        let store = TestStore::open(config()).await.unwrap();
+        assert_eq!(
+            store.inner.region_manager.eviction_checkpoint().regions,
+            vec![(0, 0), (1, 0), (2, 0)]
+        );
+        // Access region 0.
+        assert_eq!(store.lookup(&0).await.unwrap(), Some(vec![0; 1 * MB]));
+        store.close().await.unwrap();
+        drop(store);
+
+        let store = TestStore::open(config()).await.unwrap();
+        assert_eq!(
+            store.inner.region_manager.eviction_checkpoint().regions,
+            vec![(1, 0), (2, 0), (0, 1)]
+        );
+        store.close().await.unwrap();
+        drop(store);
+    }
+
+    #[tokio::test]
+    // TODO(MrCroxx): use `expect` after `lint_reasons` is stable.
+    #[allow(clippy::identity_op)]
+    async fn test_clear() {
+        const KB: usize = 1024;
+        const MB: usize = 1024 * 1024;
+
+        let tempdir = tempfile::tempdir().unwrap();
+
+        let config = || TestStoreConfig {
+            name: "".to_string(),
+            eviction_config: EvictionConfig::Fifo(FifoConfig {}),
+            device_config: FsDeviceConfig {
+                dir: PathBuf::from(tempdir.path()),
+                capacity: 16 * MB,
+                file_size: 4 * MB,
+                align: 4 * KB,
+                io_size: 4 * KB,
+            },
+            catalog_bits: 1,
+            admissions: vec![],
+            reinsertions: vec![],
+            flushers: 1,
+            reclaimers: 0,
+            recover_concurrency: 2,
+            clean_region_threshold: 1,
+            compression: Compression::None,
+            tombstone_log_config: None,
+        };
+
+        // regions:
+        // [0, 1, 2]
+        // [3, 4, 5]
+        // [6, 7, 8]
+        let store = TestStore::open(config()).await.unwrap();
+        for i in 0..9 {
+            store.insert(i, vec![i as u8; 1 * MB]).await.unwrap();
+        }
+
+        store.clear().await.unwrap();
+
+        for i in 0..9 {
+            assert!(!store.exists(&i).unwrap());
+            assert!(store.lookup(&i).await.unwrap().is_none());
+        }
+
+        store.insert(9, vec![9; 1 * MB]).await.unwrap();
+        assert_eq!(store.lookup(&9).await.unwrap(), Some(vec![9; 1 * MB]));
+
+        store.close().await.unwrap();
+        drop(store);
+
+        // Cleared entries are not recovered, including those in the region being flushed while clearing.
+        let store = TestStore::open(config()).await.unwrap();
+        for i in 0..9 {
+            assert!(store.lookup(&i).await.unwrap().is_none());
+        }
+        assert_eq!(store.lookup(&9).await.unwrap(), Some(vec![9; 1 * MB]));
+
+        store.close().await.unwrap();
+        drop(store);
+    }
+
+    #[tokio::test]
+    // TODO(MrCroxx): use `expect` after `lint_reasons` is stable.
+    #[allow(clippy::identity_op)]
+    async fn test_durable_remove() {
+        const KB: usize = 1024;
+        const MB: usize = 1024 * 1024;
+
+        let tempdir = tempfile::tempdir().unwrap();
+
+        let config = || TestStoreConfig {
+            name: "".to_string(),
+            eviction_config: EvictionConfig::Fifo(FifoConfig {}),
+            device_config: FsDeviceConfig {
+                dir: PathBuf::from(tempdir.path()),
+                capacity: 16 * MB,
+                file_size: 4 * MB,
+                align: 4 * KB,
+                io_size: 4 * KB,
+            },
+            catalog_bits: 1,
+            admissions: vec![],
+            reinsertions: vec![],
+            flushers: 1,
+            reclaimers: 0,
+            recover_concurrency: 2,
+            clean_region_threshold: 1,
+            compression: Compression::None,
+            tombstone_log_config: Some(TombstoneLogConfig {
+                dir: PathBuf::from(tempdir.path()),
+            }),
+        };
+
+        let store = TestStore::open(config()).await.unwrap();
+        for i in 0..9 {
+            store.insert(i, vec![i as u8; 1 * MB]).await.unwrap();
+        }
+        assert!(store.remove(&1).unwrap());
+        assert!(store.remove(&4).unwrap());
+        // The removes are persisted without closing the store.
+        store.flush_removes().await.unwrap();
+        assert_eq!(
+            TombstoneLog::load(store.inner.tombstone_log_config.as_ref().unwrap())
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        store.close().await.unwrap();
+        drop(store);
+
+        let store = TestStore::open(config()).await.unwrap();
+        for i in 0..9 {
+            if i == 1 || i == 4 {
+                assert!(store.lookup(&i).await.unwrap().is_none());
+            } else {
+                assert_eq!(store.lookup(&i).await.unwrap(), Some(vec![i as u8; 1 * MB]));
+            }
+        }
+        // Entries inserted after remove are not filtered by the tombstone.
+        store.insert(1, vec![42; 1 * MB]).await.unwrap();
+        store.close().await.unwrap();
+        drop(store);
+
+        let store = TestStore::open(config()).await.unwrap();
+        assert_eq!(store.lookup(&1).await.unwrap(), Some(vec![42; 1 * MB]));
+        assert!(store.lookup(&4).await.unwrap().is_none());
+        store.close().await.unwrap();
+        drop(store);
+    }
+
+    #[tokio::test]
+    // TODO(MrCroxx): use `expect` after `lint_reasons` is stable.
+    #[allow(clippy::identity_op)]
+    async fn test_remove_during_reclaim() {
+        const KB: usize = 1024;
+        const MB: usize = 1024 * 1024;
+
+        let tempdir = tempfile::tempdir().unwrap();
+
+        let config = || TestStoreConfig {
+            name: "".to_string(),
+            eviction_config: EvictionConfig::Fifo(FifoConfig {}),
+            device_config: FsDeviceConfig {
+                dir: PathBuf::from(tempdir.path()),
+                capacity: 16 * MB,
+                file_size: 4 * MB,
+                align: 4 * KB,
+                io_size: 4 * KB,
+            },
+            catalog_bits: 1,
+            admissions: vec![],
+            reinsertions: vec![],
+            flushers: 1,
+            reclaimers: 0,
+            recover_concurrency: 2,
+            clean_region_threshold: 1,
+            compression: Compression::None,
+            tombstone_log_config: Some(TombstoneLogConfig {
+                dir: PathBuf::from(tempdir.path()),
+            }),
+        };
+
+        let store = TestStore::open(config()).await.unwrap();
+        store.insert(1, vec![1; 1 * MB]).await.unwrap();
+        let region = loop {
+            match store.catalog().lookup(&1).unwrap().index() {
+                Index::Region { view } => break *view.id(),
+                Index::Inflight { .. } => tokio::time::sleep(Duration::from_millis(1)).await,
+            }
+        };
+
+        // The reclaimer takes the entry, then the entry is removed before the reclaimer reinserts it.
+        let items = store.catalog().take_region(&region);
+        assert_eq!(items.len(), 1);
+        assert!(!store.remove(&1).unwrap());
+
+        let mut writer = store.writer(1);
+        writer.set_skippable();
+        writer.set_sequence(*items[0].1.sequence());
+        writer.finish(vec![1; 1 * MB]).await.unwrap();
+        assert!(store.lookup(&1).await.unwrap().is_none());
+
+        store.flush_removes().await.unwrap();
+        store.close().await.unwrap();
+        drop(store);
+
+        // The reinserted entry is not recovered either.
+        let store = TestStore::open(config()).await.unwrap();
+        assert!(store.lookup(&1).await.unwrap().is_none());
+        store.close().await.unwrap();
+        drop(store);
+    }
 }
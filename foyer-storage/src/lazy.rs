@@ -16,12 +16,14 @@ use std::{
     borrow::Borrow,
     hash::Hash,
     sync::{Arc, OnceLock},
+    time::Duration,
 };
 
 use foyer_common::code::{StorageKey, StorageValue};
 use tokio::task::JoinHandle;
 
 use crate::{
+    catalog::Sequence,
     compress::Compression,
     error::Result,
     none::{NoneStore, NoneStoreWriter},
@@ -86,6 +88,20 @@ where
             LazyStoreWriter::None { writer } => writer.set_compression(compression),
         }
     }
+
+    fn set_ttl(&mut self, ttl: Duration) {
+        match self {
+            LazyStoreWriter::Store { writer } => writer.set_ttl(ttl),
+            LazyStoreWriter::None { writer } => writer.set_ttl(ttl),
+        }
+    }
+
+    fn set_sequence(&mut self, sequence: Sequence) {
+        match self {
+            LazyStoreWriter::Store { writer } => writer.set_sequence(sequence),
+            LazyStoreWriter::None { writer } => writer.set_sequence(sequence),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -216,10 +232,24 @@ where
         }
     }
 
-    fn clear(&self) -> Result<()> {
+    fn sequence(&self) -> Sequence {
+        match self.once.get() {
+            Some(store) => store.sequence(),
+            None => self.none.sequence(),
+        }
+    }
+
+    async fn flush_removes(&self) -> Result<()> {
+        match self.once.get() {
+            Some(store) => store.flush_removes().await,
+            None => self.none.flush_removes().await,
+        }
+    }
+
+    async fn clear(&self) -> Result<()> {
         match self.once.get() {
-            Some(store) => store.clear(),
-            None => self.none.clear(),
+            Some(store) => store.clear().await,
+            None => self.none.clear().await,
         }
     }
 }
@@ -262,6 +292,7 @@ mod tests {
             recover_concurrency: 2,
             clean_region_threshold: 1,
             compression: crate::compress::Compression::None,
+            tombstone_log_config: None,
         };
 
         let (store, handle) = Lazy::<u64, u64, FsStore<_, _>>::with_handle(config);
@@ -294,6 +325,7 @@ mod tests {
             recover_concurrency: 2,
             clean_region_threshold: 1,
             compression: crate::compress::Compression::None,
+            tombstone_log_config: None,
         };
 
         let (store, handle) = Lazy::<u64, u64, FsStore<_, _>>::with_handle(config);
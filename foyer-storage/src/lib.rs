@@ -31,6 +31,7 @@ mod reinsertion;
 mod runtime;
 mod storage;
 mod store;
+mod tombstone;
 
 mod prelude;
 pub use prelude::*;
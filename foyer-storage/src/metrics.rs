@@ -12,8 +12,7 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 
-use std::sync::OnceLock;
-
+pub use foyer_common::metrics::{get_metrics_registry, set_metrics_registry};
 use prometheus::{
     core::{AtomicU64, GenericGauge, GenericGaugeVec},
     exponential_buckets, opts, register_histogram_vec_with_registry, register_int_counter_vec_with_registry,
@@ -39,21 +38,6 @@ macro_rules! register_uint_gauge_vec_with_registry {
     }};
 }
 
-static REGISTRY: OnceLock<Registry> = OnceLock::new();
-
-/// Set metrics registry for `foyer`.
-///
-/// Metrics registry must be set before `open`.
-///
-/// Return `true` if set succeeds.
-pub fn set_metrics_registry(registry: Registry) -> bool {
-    REGISTRY.set(registry).is_ok()
-}
-
-pub fn get_metrics_registry() -> &'static Registry {
-    REGISTRY.get_or_init(|| prometheus::default_registry().clone())
-}
-
 // TODO(MrCroxx): Use `LazyLock` after `lazy_cell` is stable.
 // /// Multiple foyer instance will share the same global metrics with different label `foyer` name.
 // pub static METRICS: LazyLock<GlobalMetrics> = LazyLock::new(GlobalMetrics::default);
@@ -72,6 +56,7 @@ pub struct GlobalMetrics {
     entry_bytes: HistogramVec,
 
     inner_op_duration: HistogramVec,
+    inner_op_count: IntCounterVec,
     _inner_bytes: IntGaugeVec,
 }
 
@@ -135,6 +120,14 @@ impl GlobalMetrics {
         )
         .unwrap();
 
+        let inner_op_count = register_int_counter_vec_with_registry!(
+            "foyer_storage_inner_op_count",
+            "foyer storage inner op count",
+            &["foyer", "op", "extra"],
+            registry,
+        )
+        .unwrap();
+
         let inner_bytes = register_int_gauge_vec_with_registry!(
             "foyer_storage_inner_bytes",
             "foyer storage inner bytes",
@@ -152,6 +145,7 @@ impl GlobalMetrics {
             entry_bytes,
 
             inner_op_duration,
+            inner_op_count,
             _inner_bytes: inner_bytes,
         }
     }
@@ -187,6 +181,9 @@ pub struct Metrics {
     pub inner_op_duration_update_catalog: Histogram,
     pub inner_op_duration_entry_flush: Histogram,
     pub inner_op_duration_flusher_handle: Histogram,
+
+    pub inner_op_count_insert_catalog_stale: IntCounter,
+    pub inner_op_count_insert_catalog_cleared: IntCounter,
 }
 
 impl Metrics {
@@ -231,6 +228,15 @@ impl Metrics {
                 .inner_op_duration
                 .with_label_values(&[foyer, "flusher_handle", ""]);
 
+        let inner_op_count_insert_catalog_stale =
+            global
+                .inner_op_count
+                .with_label_values(&[foyer, "insert_catalog", "stale"]);
+        let inner_op_count_insert_catalog_cleared =
+            global
+                .inner_op_count
+                .with_label_values(&[foyer, "insert_catalog", "cleared"]);
+
         Self {
             op_duration_insert_inserted,
             op_duration_insert_filtered,
@@ -256,6 +262,9 @@ impl Metrics {
             inner_op_duration_update_catalog,
             inner_op_duration_entry_flush,
             inner_op_duration_flusher_handle,
+
+            inner_op_count_insert_catalog_stale,
+            inner_op_count_insert_catalog_cleared,
         }
     }
 }
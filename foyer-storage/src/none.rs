@@ -12,11 +12,12 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 
-use std::{borrow::Borrow, hash::Hash, marker::PhantomData};
+use std::{borrow::Borrow, hash::Hash, marker::PhantomData, time::Duration};
 
 use foyer_common::code::{StorageKey, StorageValue};
 
 use crate::{
+    catalog::Sequence,
     compress::Compression,
     error::Result,
     storage::{Storage, StorageWriter},
@@ -69,6 +70,10 @@ where
     }
 
     fn set_compression(&mut self, _: Compression) {}
+
+    fn set_ttl(&mut self, _: Duration) {}
+
+    fn set_sequence(&mut self, _: Sequence) {}
 }
 
 #[derive(Debug)]
@@ -130,7 +135,15 @@ impl<K: StorageKey, V: StorageValue> Storage<K, V> for NoneStore<K, V> {
         Ok(false)
     }
 
-    fn clear(&self) -> Result<()> {
+    fn sequence(&self) -> Sequence {
+        0
+    }
+
+    async fn flush_removes(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<()> {
         Ok(())
     }
 }
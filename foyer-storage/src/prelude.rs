@@ -14,12 +14,24 @@
 
 pub use crate::{
     admission::{rated_ticket::RatedTicketAdmissionPolicy, AdmissionContext, AdmissionPolicy},
+    catalog::Sequence,
     compress::Compression,
-    device::fs::{FsDeviceConfig, FsDeviceConfigBuilder},
+    device::{
+        file::{FileDeviceConfig, FileDeviceConfigBuilder},
+        fs::{FsDeviceConfig, FsDeviceConfigBuilder},
+        mem::{MemDeviceConfig, MemDeviceConfigBuilder, MemDeviceMemory},
+    },
     error::{Error, Result},
     metrics::{get_metrics_registry, set_metrics_registry},
     reinsertion::{rated_ticket::RatedTicketReinsertionPolicy, ReinsertionContext, ReinsertionPolicy},
     runtime::{RuntimeConfig, RuntimeStoreConfig},
     storage::{AsyncStorageExt, ForceStorageExt, Storage, StorageExt, StorageWriter},
-    store::{FsStoreConfig, Store, StoreConfig, StoreWriter},
+    store::{FileStoreConfig, FsStoreConfig, MemStoreConfig, Store, StoreConfig, StoreWriter},
+    tombstone::TombstoneLogConfig,
+};
+
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub use crate::{
+    device::uring::{UringDeviceConfig, UringDeviceConfigBuilder},
+    store::UringStoreConfig,
 };
@@ -17,15 +17,15 @@ use std::{
     time::Duration,
 };
 
-use bytes::BufMut;
 use foyer_common::code::{StorageKey, StorageValue};
 
 use tokio::sync::broadcast;
 
 use crate::{
+    catalog::{Index, Item},
     device::Device,
-    error::Result,
-    generic::{GenericStore, RegionEntryIter},
+    error::{Error, Result},
+    generic::{read_entry, GenericStore},
     judge::Judges,
     metrics::Metrics,
     region_manager::RegionManager,
@@ -94,10 +94,15 @@ where
         }
 
         // TODO(MrCroxx): subscribe evictable region changes.
-        let region_id = loop {
+        let (region_id, _guard) = loop {
+            // Hold the reclaim lock until the region is reclaimed, so `clear` cannot run in between.
+            let guard = self.region_manager.reclaim_lock().read().await;
             match self.region_manager.eviction_pop() {
-                Some(id) => break id,
-                None => tokio::time::sleep(Duration::from_millis(100)).await,
+                Some(id) => break (id, guard),
+                None => {
+                    drop(guard);
+                    tokio::time::sleep(Duration::from_millis(100)).await
+                }
             }
         };
 
@@ -120,7 +125,7 @@ where
         }
 
         // step 2: do reinsertion
-        let reinsert = || {
+        let reinsert = |indices: Vec<(K, Item<K, V>)>| {
             let region = region.clone();
             let metrics = self.metrics.clone();
             let reinsertions = self.store.reinsertions().clone();
@@ -128,13 +133,26 @@ where
             tracing::info!("[reclaimer] begin reinsertion, region: {}", region_id);
 
             async move {
-                let mut iter = match RegionEntryIter::<K, V, D>::open(region).await {
-                    Ok(Some(iter)) => iter,
-                    Ok(None) => return Ok(true),
-                    Err(e) => return Err(e),
-                };
+                // Only the entries still indexed by the catalog are reinserted, so removed, overwritten or cleared
+                // entries are never brought back.
+                for (key, item) in indices {
+                    if item.is_expired() {
+                        continue;
+                    }
+                    let expire_at = item.expire_at();
+                    let (sequence, index) = item.consume();
+                    let Index::Region { view } = index else {
+                        unreachable!("items taken from region must have index of region")
+                    };
+                    let Some(buf) = region.load(view).await? else {
+                        continue;
+                    };
+                    let len = buf.len();
+                    let Ok((_, value)) = read_entry::<K, V>(buf.as_ref()) else {
+                        continue;
+                    };
+                    drop(buf);
 
-                while let Some((key, value, len)) = iter.next_kv().await? {
                     let mut judges = Judges::new(reinsertions.len());
                     for (index, reinsertion) in reinsertions.iter().enumerate() {
                         let judge = reinsertion.judge(&key);
@@ -150,6 +168,9 @@ where
 
                     let mut writer = self.store.writer(key.clone());
                     writer.set_skippable();
+                    writer.set_expire_at(expire_at);
+                    // Keep the original sequence, so the reinserted entry never overrides a newer one.
+                    writer.set_sequence(sequence);
 
                     if !writer.judge() {
                         continue;
@@ -176,12 +197,12 @@ where
 
                 tracing::info!("[reclaimer] finish reinsertion, region: {}", region_id);
 
-                Ok(true)
+                Ok::<_, Error>(true)
             }
         };
 
         if !self.store.reinsertions().is_empty() {
-            match reinsert().await {
+            match reinsert(indices).await {
                 Ok(true) => {
                     tracing::info!("[reclaimer] reinsertion finish, region: {}", region_id)
                 }
@@ -193,11 +214,7 @@ where
         }
 
         // step 3: wipe region header
-        let align = region.device().align();
-        let mut buf = region.device().io_buffer(align, align);
-        (&mut buf[..]).put_slice(&vec![0; align]);
-        let (res, _buf) = region.device().write(buf, .., region_id, 0).await;
-        res?;
+        region.wipe().await?;
 
         // step 4: send clean region
         self.region_manager.clean_regions().release(region_id);
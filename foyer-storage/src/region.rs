@@ -37,28 +37,29 @@ pub type RegionId = u32;
 
 pub const REGION_MAGIC: u64 = 0x19970327;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Version {
     V1,
+    /// Entry header with expire timestamp.
+    V2,
 }
 
 impl Version {
     pub fn latest() -> Self {
-        Self::V1
+        Self::V2
     }
 
-    pub fn to_u64(&self) -> u64 {
+    pub fn to_u64(self) -> u64 {
         match self {
             Version::V1 => 1,
+            Version::V2 => 2,
         }
     }
 }
 
 impl From<Version> for u64 {
     fn from(value: Version) -> Self {
-        match value {
-            Version::V1 => 1,
-        }
+        value.to_u64()
     }
 }
 
@@ -68,6 +69,7 @@ impl TryFrom<u64> for Version {
     fn try_from(value: u64) -> std::result::Result<Self, Self::Error> {
         match value {
             1 => Ok(Self::V1),
+            2 => Ok(Self::V2),
             v => Err(anyhow::anyhow!("invalid region format version: {}", v)),
         }
     }
@@ -231,6 +233,16 @@ where
         &self.device
     }
 
+    /// Invalidate the region by wiping its header, so the entries in it will not be recovered.
+    pub async fn wipe(&self) -> Result<()> {
+        let align = self.device.align();
+        let mut buf = self.device.io_buffer(align, align);
+        (&mut buf[..]).put_slice(&vec![0; align]);
+        let (res, _buf) = self.device.write(buf, .., self.id, 0).await;
+        res?;
+        Ok(())
+    }
+
     /// Cleanup waits.
     fn cleanup(&self, start: usize, end: usize) -> Result<()> {
         if let Some(txs) = self.inner.lock().waits.remove(&(start, end)) {
@@ -12,16 +12,100 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::anyhow;
+use bytes::{Buf, BufMut};
 use foyer_common::async_queue::AsyncQueue;
 use foyer_memory::{Cache, CacheBuilder, EvictionConfig};
 
 use itertools::Itertools;
+use tokio::sync::RwLock;
 
 use crate::{
     device::Device,
+    error::Result,
+    generic::checksum,
     region::{Region, RegionId},
 };
 
+pub const EVICTION_CHECKPOINT_MAGIC: u64 = 0x20240404;
+
+/// Access counters replayed into the eviction container are capped to bound the restore cost.
+const MAX_REPLAYED_ACCESSES: u64 = 64;
+
+/// Persisted region eviction state.
+///
+/// Format:
+///
+/// | magic (8B) | count (4B) | [ region id (4B) | accesses (8B) ] * count | checksum (8B) |
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EvictionCheckpoint {
+    /// `(region id, access count)` in eviction order, the first region will be evicted first.
+    pub regions: Vec<(RegionId, u64)>,
+}
+
+impl EvictionCheckpoint {
+    /// Name of the device metadata that holds the checkpoint.
+    pub const META: &'static str = "eviction";
+
+    pub fn serialized_len(&self) -> usize {
+        8 + 4 + (4 + 8) * self.regions.len() + 8
+    }
+
+    pub fn write(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.serialized_len());
+        buf.put_u64(EVICTION_CHECKPOINT_MAGIC);
+        buf.put_u32(self.regions.len() as u32);
+        for (id, accesses) in self.regions.iter() {
+            buf.put_u32(*id);
+            buf.put_u64(*accesses);
+        }
+        buf.put_u64(checksum(&buf));
+        buf
+    }
+
+    pub fn read(buf: &[u8]) -> Result<Self> {
+        if buf.len() < 8 + 4 + 8 {
+            return Err(anyhow!("eviction checkpoint too short, len: {}", buf.len()).into());
+        }
+        let (data, mut tail) = buf.split_at(buf.len() - 8);
+        let expected = tail.get_u64();
+        let checksum = checksum(data);
+        if checksum != expected {
+            return Err(anyhow!(
+                "eviction checkpoint checksum mismatch, checksum: {}, expected: {}",
+                checksum,
+                expected
+            )
+            .into());
+        }
+
+        let mut data = data;
+        let magic = data.get_u64();
+        if magic != EVICTION_CHECKPOINT_MAGIC {
+            return Err(anyhow!(
+                "eviction checkpoint magic mismatch, magic: {}, expected: {}",
+                magic,
+                EVICTION_CHECKPOINT_MAGIC
+            )
+            .into());
+        }
+        let count = data.get_u32() as usize;
+        if data.len() != count * (4 + 8) {
+            return Err(anyhow!(
+                "eviction checkpoint length mismatch, count: {}, len: {}",
+                count,
+                data.len()
+            )
+            .into());
+        }
+        let regions = (0..count).map(|_| (data.get_u32(), data.get_u64())).collect_vec();
+
+        Ok(Self { regions })
+    }
+}
+
 #[derive(Debug)]
 pub struct RegionManager<D>
 where
@@ -33,16 +117,25 @@ where
     regions: Vec<Region<D>>,
 
     eviction: Cache<RegionId, ()>,
+
+    /// Access counters of the regions since they are pushed into the eviction container.
+    accesses: Vec<AtomicU64>,
+
+    /// Reclaimers hold the read lock while reclaiming a region, `clear` holds the write lock.
+    reclaim_lock: RwLock<()>,
 }
 
 impl<D> RegionManager<D>
 where
     D: Device,
 {
-    pub fn new(region_count: usize, eviction_config: EvictionConfig, device: D) -> Self {
+    /// The region eviction container exports its metrics with the label `foyer = {{ name }}-region-eviction`, to keep
+    /// them apart from the in-memory caches.
+    pub fn new(name: &str, region_count: usize, eviction_config: EvictionConfig, device: D) -> Self {
         let clean_regions = AsyncQueue::new();
 
         let eviction = CacheBuilder::new(region_count)
+            .with_name(format!("{name}-region-eviction"))
             .with_object_pool_capacity(region_count)
             .with_eviction_config(eviction_config)
             .build();
@@ -51,10 +144,14 @@ where
             .map(|id| Region::new(id, device.clone()))
             .collect_vec();
 
+        let accesses = (0..region_count).map(|_| AtomicU64::new(0)).collect_vec();
+
         Self {
             clean_regions,
             regions,
             eviction,
+            accesses,
+            reclaim_lock: RwLock::new(()),
         }
     }
 
@@ -64,7 +161,12 @@ where
 
     #[tracing::instrument(skip(self))]
     pub fn record_access(&self, id: &RegionId) {
-        self.eviction.touch(id);
+        self.accesses[*id as usize].fetch_add(1, Ordering::Relaxed);
+        self.touch(id);
+    }
+
+    pub fn reclaim_lock(&self) -> &RwLock<()> {
+        &self.reclaim_lock
     }
 
     pub fn clean_regions(&self) -> &AsyncQueue<RegionId> {
@@ -72,10 +174,111 @@ where
     }
 
     pub fn eviction_push(&self, region_id: RegionId) {
-        self.eviction.insert(region_id, (), 1);
+        self.accesses[region_id as usize].store(0, Ordering::Relaxed);
+        self.eviction.insert(region_id, ());
     }
 
     pub fn eviction_pop(&self) -> Option<RegionId> {
         self.eviction.pop().map(|entry| *entry.key())
     }
+
+    /// Take a checkpoint of the eviction order and the access counters of the regions in the eviction container.
+    ///
+    /// The eviction container is drained and restored from the checkpoint, so it must not be modified concurrently.
+    pub fn eviction_checkpoint(&self) -> EvictionCheckpoint {
+        let mut regions = vec![];
+        while let Some(id) = self.eviction_pop() {
+            regions.push((id, self.accesses[id as usize].load(Ordering::Relaxed)));
+        }
+        let checkpoint = EvictionCheckpoint { regions };
+        self.eviction_restore(&checkpoint, checkpoint.regions.iter().map(|(id, _)| *id));
+        checkpoint
+    }
+
+    /// Push `regions` into the eviction container in the order of the checkpoint and replay their access counters.
+    ///
+    /// Regions absent from the checkpoint are pushed afterwards in the given order. Regions in the checkpoint but not
+    /// in `regions` are ignored.
+    pub fn eviction_restore(&self, checkpoint: &EvictionCheckpoint, regions: impl IntoIterator<Item = RegionId>) {
+        let regions = regions.into_iter().collect_vec();
+
+        let mut given = vec![false; self.regions.len()];
+        for id in regions.iter() {
+            given[*id as usize] = true;
+        }
+
+        let mut restored = vec![false; self.regions.len()];
+        for (id, accesses) in checkpoint.regions.iter() {
+            let index = *id as usize;
+            if index >= self.regions.len() || !given[index] || restored[index] {
+                continue;
+            }
+            self.eviction_push(*id);
+            for _ in 0..std::cmp::min(*accesses, MAX_REPLAYED_ACCESSES) {
+                self.touch(id);
+            }
+            self.accesses[index].store(*accesses, Ordering::Relaxed);
+            restored[index] = true;
+        }
+
+        for id in regions {
+            if !restored[id as usize] {
+                self.eviction_push(id);
+            }
+        }
+    }
+
+    fn touch(&self, id: &RegionId) {
+        // Acquire and release the entry, so the eviction container updates both the order and the frequency.
+        drop(self.eviction.get(id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use foyer_memory::LruConfig;
+
+    use super::*;
+    use crate::device::tests::NullDevice;
+
+    fn manager(regions: usize) -> RegionManager<NullDevice> {
+        RegionManager::new(
+            "test",
+            regions,
+            EvictionConfig::Lru(LruConfig {
+                high_priority_pool_ratio: 0.0,
+            }),
+            NullDevice::new(4096),
+        )
+    }
+
+    #[test]
+    fn test_eviction_checkpoint() {
+        let rm = manager(8);
+        for id in 0..4 {
+            rm.eviction_push(id);
+        }
+        rm.record_access(&0);
+        rm.record_access(&0);
+        rm.record_access(&2);
+
+        let checkpoint = rm.eviction_checkpoint();
+        assert_eq!(checkpoint.regions, vec![(1, 0), (3, 0), (0, 2), (2, 1)]);
+        // Taking a checkpoint does not change the eviction state.
+        assert_eq!(rm.eviction_checkpoint(), checkpoint);
+
+        let buf = checkpoint.write();
+        assert_eq!(buf.len(), checkpoint.serialized_len());
+        assert_eq!(EvictionCheckpoint::read(&buf).unwrap(), checkpoint);
+
+        let mut corrupted = buf.clone();
+        corrupted[10] ^= 1;
+        assert!(EvictionCheckpoint::read(&corrupted).is_err());
+
+        // Region 1 is not recovered, region 5 is absent from the checkpoint.
+        let rm = manager(8);
+        rm.eviction_restore(&checkpoint, [0, 2, 3, 5]);
+        let order = std::iter::from_fn(|| rm.eviction_pop()).collect_vec();
+        assert_eq!(order, vec![3, 0, 2, 5]);
+    }
 }
@@ -12,7 +12,7 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 
-use std::{borrow::Borrow, hash::Hash, marker::PhantomData, sync::Arc};
+use std::{borrow::Borrow, hash::Hash, marker::PhantomData, sync::Arc, time::Duration};
 
 use foyer_common::{
     code::{StorageKey, StorageValue},
@@ -20,6 +20,7 @@ use foyer_common::{
 };
 
 use crate::{
+    catalog::Sequence,
     compress::Compression,
     error::Result,
     storage::{Storage, StorageWriter},
@@ -85,6 +86,14 @@ where
     fn set_compression(&mut self, compression: Compression) {
         self.writer.set_compression(compression)
     }
+
+    fn set_ttl(&mut self, ttl: Duration) {
+        self.writer.set_ttl(ttl)
+    }
+
+    fn set_sequence(&mut self, sequence: Sequence) {
+        self.writer.set_sequence(sequence)
+    }
 }
 
 #[derive(Debug)]
@@ -191,7 +200,20 @@ where
         self.store.remove(key)
     }
 
-    fn clear(&self) -> crate::error::Result<()> {
-        self.store.clear()
+    fn sequence(&self) -> Sequence {
+        self.store.sequence()
+    }
+
+    async fn flush_removes(&self) -> Result<()> {
+        let store = self.store.clone();
+        self.runtime
+            .spawn(async move { store.flush_removes().await })
+            .await
+            .unwrap()
+    }
+
+    async fn clear(&self) -> Result<()> {
+        let store = self.store.clone();
+        self.runtime.spawn(async move { store.clear().await }).await.unwrap()
     }
 }
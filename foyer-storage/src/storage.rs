@@ -12,12 +12,12 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 
-use std::{borrow::Borrow, fmt::Debug, hash::Hash};
+use std::{borrow::Borrow, fmt::Debug, hash::Hash, time::Duration};
 
 use foyer_common::code::{StorageKey, StorageValue};
 use futures::Future;
 
-use crate::{compress::Compression, error::Result};
+use crate::{catalog::Sequence, compress::Compression, error::Result};
 
 // TODO(MrCroxx): Use `trait_alias` after stable.
 // pub trait FetchValueFuture<V> = Future<Output = anyhow::Result<V>> + Send + 'static;
@@ -40,6 +40,12 @@ where
 
     fn set_compression(&mut self, compression: Compression);
 
+    /// Set the time-to-live of the entry. The entry is treated as a miss after it expires.
+    fn set_ttl(&mut self, ttl: Duration);
+
+    /// Insert the entry with the sequence taken by [`Storage::sequence`] instead of a new one.
+    fn set_sequence(&mut self, sequence: Sequence);
+
     fn finish(self, value: V) -> impl Future<Output = Result<bool>> + Send;
 }
 
@@ -72,12 +78,28 @@ where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized + Send + Sync + Clone + 'static;
 
+    /// Remove the entry of the key.
+    ///
+    /// The remove is persisted in the background, the removed entry may be recovered after a crash until
+    /// [`Storage::flush_removes`] returns.
     fn remove<Q>(&self, key: &Q) -> Result<bool>
     where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized;
 
-    fn clear(&self) -> Result<()>;
+    /// Take a new sequence, which is greater than the sequences of the inserts and removes issued before.
+    ///
+    /// An insert with the sequence never overwrites the newer entry of the key, and is dropped if the key is removed
+    /// after the sequence is taken.
+    fn sequence(&self) -> Sequence;
+
+    /// Wait until all removes issued before are persisted.
+    #[must_use]
+    fn flush_removes(&self) -> impl Future<Output = Result<()>> + Send;
+
+    /// Remove all entries and reclaim the device space. The cleared entries will not be recovered after restart.
+    #[must_use]
+    fn clear(&self) -> impl Future<Output = Result<()>> + Send;
 }
 
 pub trait StorageExt<K, V>: Storage<K, V>
@@ -91,6 +113,23 @@ where
         self.writer(key).finish(value)
     }
 
+    #[must_use]
+    #[tracing::instrument(skip(self, value))]
+    fn insert_with_ttl(&self, key: K, value: V, ttl: Duration) -> impl Future<Output = Result<bool>> + Send {
+        let mut writer = self.writer(key);
+        writer.set_ttl(ttl);
+        writer.finish(value)
+    }
+
+    /// Insert the entry with the sequence taken by [`Storage::sequence`], see [`StorageWriter::set_sequence`].
+    #[must_use]
+    #[tracing::instrument(skip(self, value))]
+    fn insert_with_sequence(&self, key: K, value: V, sequence: Sequence) -> impl Future<Output = Result<bool>> + Send {
+        let mut writer = self.writer(key);
+        writer.set_sequence(sequence);
+        writer.finish(value)
+    }
+
     #[must_use]
     #[tracing::instrument(skip(self, value))]
     fn insert_if_not_exists(&self, key: K, value: V) -> impl Future<Output = Result<bool>> + Send {
@@ -214,6 +253,16 @@ where
         });
     }
 
+    #[tracing::instrument(skip(self, value))]
+    fn insert_with_sequence_async(&self, key: K, value: V, sequence: Sequence) {
+        let store = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = store.insert_with_sequence(key, value, sequence).await {
+                tracing::warn!("async storage insert error: {}", e);
+            }
+        });
+    }
+
     #[tracing::instrument(skip(self, value))]
     fn insert_if_not_exists_async(&self, key: K, value: V) {
         let store = self.clone();
@@ -376,6 +425,7 @@ mod tests {
             clean_region_threshold: 1,
             recover_concurrency: 2,
             compression: Compression::None,
+            tombstone_log_config: None,
         }
     }
 
@@ -405,7 +455,7 @@ mod tests {
         assert!(!storage.exists(&1).unwrap());
         assert!(!storage.remove(&1).unwrap());
 
-        storage.clear().unwrap();
+        storage.clear().await.unwrap();
         storage.close().await.unwrap();
     }
 
@@ -13,11 +13,12 @@
 //  limitations under the License.
 
 use foyer_common::code::{StorageKey, StorageValue};
-use std::{borrow::Borrow, fmt::Debug, hash::Hash};
+use std::{borrow::Borrow, fmt::Debug, hash::Hash, time::Duration};
 
 use crate::{
+    catalog::Sequence,
     compress::Compression,
-    device::fs::FsDevice,
+    device::{file::FileDevice, fs::FsDevice, mem::MemDevice},
     error::Result,
     generic::{GenericStore, GenericStoreConfig, GenericStoreWriter},
     lazy::{Lazy, LazyStoreWriter},
@@ -26,10 +27,28 @@ use crate::{
     storage::{Storage, StorageWriter},
 };
 
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+use crate::device::uring::UringDevice;
+
 pub type FsStore<K, V> = GenericStore<K, V, FsDevice>;
 pub type FsStoreConfig<K, V> = GenericStoreConfig<K, V, FsDevice>;
 pub type FsStoreWriter<K, V> = GenericStoreWriter<K, V, FsDevice>;
 
+pub type FileStore<K, V> = GenericStore<K, V, FileDevice>;
+pub type FileStoreConfig<K, V> = GenericStoreConfig<K, V, FileDevice>;
+pub type FileStoreWriter<K, V> = GenericStoreWriter<K, V, FileDevice>;
+
+pub type MemStore<K, V> = GenericStore<K, V, MemDevice>;
+pub type MemStoreConfig<K, V> = GenericStoreConfig<K, V, MemDevice>;
+pub type MemStoreWriter<K, V> = GenericStoreWriter<K, V, MemDevice>;
+
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub type UringStore<K, V> = GenericStore<K, V, UringDevice>;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub type UringStoreConfig<K, V> = GenericStoreConfig<K, V, UringDevice>;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub type UringStoreWriter<K, V> = GenericStoreWriter<K, V, UringDevice>;
+
 pub enum StoreConfig<K, V>
 where
     K: StorageKey,
@@ -41,6 +60,22 @@ where
     LazyFs(FsStoreConfig<K, V>),
     RuntimeFs(RuntimeStoreConfig<K, V, FsStore<K, V>>),
     RuntimeLazyFs(RuntimeStoreConfig<K, V, Lazy<K, V, FsStore<K, V>>>),
+    File(FileStoreConfig<K, V>),
+    LazyFile(FileStoreConfig<K, V>),
+    RuntimeFile(RuntimeStoreConfig<K, V, FileStore<K, V>>),
+    RuntimeLazyFile(RuntimeStoreConfig<K, V, Lazy<K, V, FileStore<K, V>>>),
+    Mem(MemStoreConfig<K, V>),
+    LazyMem(MemStoreConfig<K, V>),
+    RuntimeMem(RuntimeStoreConfig<K, V, MemStore<K, V>>),
+    RuntimeLazyMem(RuntimeStoreConfig<K, V, Lazy<K, V, MemStore<K, V>>>),
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    Uring(UringStoreConfig<K, V>),
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    LazyUring(UringStoreConfig<K, V>),
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    RuntimeUring(RuntimeStoreConfig<K, V, UringStore<K, V>>),
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    RuntimeLazyUring(RuntimeStoreConfig<K, V, Lazy<K, V, UringStore<K, V>>>),
 }
 
 impl<K, V> Debug for StoreConfig<K, V>
@@ -55,6 +90,22 @@ where
             Self::LazyFs(config) => f.debug_tuple("LazyFs").field(config).finish(),
             Self::RuntimeFs(config) => f.debug_tuple("RuntimeFs").field(config).finish(),
             Self::RuntimeLazyFs(config) => f.debug_tuple("RuntimeLazyFs").field(config).finish(),
+            Self::File(config) => f.debug_tuple("File").field(config).finish(),
+            Self::LazyFile(config) => f.debug_tuple("LazyFile").field(config).finish(),
+            Self::RuntimeFile(config) => f.debug_tuple("RuntimeFile").field(config).finish(),
+            Self::RuntimeLazyFile(config) => f.debug_tuple("RuntimeLazyFile").field(config).finish(),
+            Self::Mem(config) => f.debug_tuple("Mem").field(config).finish(),
+            Self::LazyMem(config) => f.debug_tuple("LazyMem").field(config).finish(),
+            Self::RuntimeMem(config) => f.debug_tuple("RuntimeMem").field(config).finish(),
+            Self::RuntimeLazyMem(config) => f.debug_tuple("RuntimeLazyMem").field(config).finish(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Self::Uring(config) => f.debug_tuple("Uring").field(config).finish(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Self::LazyUring(config) => f.debug_tuple("LazyUring").field(config).finish(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Self::RuntimeUring(config) => f.debug_tuple("RuntimeUring").field(config).finish(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Self::RuntimeLazyUring(config) => f.debug_tuple("RuntimeLazyUring").field(config).finish(),
         }
     }
 }
@@ -71,6 +122,22 @@ where
             StoreConfig::LazyFs(config) => StoreConfig::LazyFs(config.clone()),
             StoreConfig::RuntimeFs(config) => StoreConfig::RuntimeFs(config.clone()),
             StoreConfig::RuntimeLazyFs(config) => StoreConfig::RuntimeLazyFs(config.clone()),
+            StoreConfig::File(config) => StoreConfig::File(config.clone()),
+            StoreConfig::LazyFile(config) => StoreConfig::LazyFile(config.clone()),
+            StoreConfig::RuntimeFile(config) => StoreConfig::RuntimeFile(config.clone()),
+            StoreConfig::RuntimeLazyFile(config) => StoreConfig::RuntimeLazyFile(config.clone()),
+            StoreConfig::Mem(config) => StoreConfig::Mem(config.clone()),
+            StoreConfig::LazyMem(config) => StoreConfig::LazyMem(config.clone()),
+            StoreConfig::RuntimeMem(config) => StoreConfig::RuntimeMem(config.clone()),
+            StoreConfig::RuntimeLazyMem(config) => StoreConfig::RuntimeLazyMem(config.clone()),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreConfig::Uring(config) => StoreConfig::Uring(config.clone()),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreConfig::LazyUring(config) => StoreConfig::LazyUring(config.clone()),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreConfig::RuntimeUring(config) => StoreConfig::RuntimeUring(config.clone()),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreConfig::RuntimeLazyUring(config) => StoreConfig::RuntimeLazyUring(config.clone()),
         }
     }
 }
@@ -86,6 +153,22 @@ where
     LazyFs(LazyStoreWriter<K, V, FsStore<K, V>>),
     RuntimeFs(RuntimeStoreWriter<K, V, FsStore<K, V>>),
     RuntimeLazyFs(RuntimeStoreWriter<K, V, Lazy<K, V, FsStore<K, V>>>),
+    File(FileStoreWriter<K, V>),
+    LazyFile(LazyStoreWriter<K, V, FileStore<K, V>>),
+    RuntimeFile(RuntimeStoreWriter<K, V, FileStore<K, V>>),
+    RuntimeLazyFile(RuntimeStoreWriter<K, V, Lazy<K, V, FileStore<K, V>>>),
+    Mem(MemStoreWriter<K, V>),
+    LazyMem(LazyStoreWriter<K, V, MemStore<K, V>>),
+    RuntimeMem(RuntimeStoreWriter<K, V, MemStore<K, V>>),
+    RuntimeLazyMem(RuntimeStoreWriter<K, V, Lazy<K, V, MemStore<K, V>>>),
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    Uring(UringStoreWriter<K, V>),
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    LazyUring(LazyStoreWriter<K, V, UringStore<K, V>>),
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    RuntimeUring(RuntimeStoreWriter<K, V, UringStore<K, V>>),
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    RuntimeLazyUring(RuntimeStoreWriter<K, V, Lazy<K, V, UringStore<K, V>>>),
 }
 
 impl<K, V> Debug for StoreWriter<K, V>
@@ -100,6 +183,22 @@ where
             Self::LazyFs(writer) => f.debug_tuple("LazyFs").field(writer).finish(),
             Self::RuntimeFs(writer) => f.debug_tuple("RuntimeFs").field(writer).finish(),
             Self::RuntimeLazyFs(writer) => f.debug_tuple("RuntimeLazyFs").field(writer).finish(),
+            Self::File(writer) => f.debug_tuple("File").field(writer).finish(),
+            Self::LazyFile(writer) => f.debug_tuple("LazyFile").field(writer).finish(),
+            Self::RuntimeFile(writer) => f.debug_tuple("RuntimeFile").field(writer).finish(),
+            Self::RuntimeLazyFile(writer) => f.debug_tuple("RuntimeLazyFile").field(writer).finish(),
+            Self::Mem(writer) => f.debug_tuple("Mem").field(writer).finish(),
+            Self::LazyMem(writer) => f.debug_tuple("LazyMem").field(writer).finish(),
+            Self::RuntimeMem(writer) => f.debug_tuple("RuntimeMem").field(writer).finish(),
+            Self::RuntimeLazyMem(writer) => f.debug_tuple("RuntimeLazyMem").field(writer).finish(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Self::Uring(writer) => f.debug_tuple("Uring").field(writer).finish(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Self::LazyUring(writer) => f.debug_tuple("LazyUring").field(writer).finish(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Self::RuntimeUring(writer) => f.debug_tuple("RuntimeUring").field(writer).finish(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Self::RuntimeLazyUring(writer) => f.debug_tuple("RuntimeLazyUring").field(writer).finish(),
         }
     }
 }
@@ -116,6 +215,22 @@ where
     LazyFs(Lazy<K, V, FsStore<K, V>>),
     RuntimeFs(Runtime<K, V, FsStore<K, V>>),
     RuntimeLazyFs(Runtime<K, V, Lazy<K, V, FsStore<K, V>>>),
+    File(FileStore<K, V>),
+    LazyFile(Lazy<K, V, FileStore<K, V>>),
+    RuntimeFile(Runtime<K, V, FileStore<K, V>>),
+    RuntimeLazyFile(Runtime<K, V, Lazy<K, V, FileStore<K, V>>>),
+    Mem(MemStore<K, V>),
+    LazyMem(Lazy<K, V, MemStore<K, V>>),
+    RuntimeMem(Runtime<K, V, MemStore<K, V>>),
+    RuntimeLazyMem(Runtime<K, V, Lazy<K, V, MemStore<K, V>>>),
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    Uring(UringStore<K, V>),
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    LazyUring(Lazy<K, V, UringStore<K, V>>),
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    RuntimeUring(Runtime<K, V, UringStore<K, V>>),
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    RuntimeLazyUring(Runtime<K, V, Lazy<K, V, UringStore<K, V>>>),
 }
 
 impl<K, V> Debug for Store<K, V>
@@ -130,6 +245,22 @@ where
             Self::LazyFs(store) => f.debug_tuple("LazyFs").field(store).finish(),
             Self::RuntimeFs(store) => f.debug_tuple("RuntimeFs").field(store).finish(),
             Self::RuntimeLazyFs(store) => f.debug_tuple("RuntimeLazyFs").field(store).finish(),
+            Self::File(store) => f.debug_tuple("File").field(store).finish(),
+            Self::LazyFile(store) => f.debug_tuple("LazyFile").field(store).finish(),
+            Self::RuntimeFile(store) => f.debug_tuple("RuntimeFile").field(store).finish(),
+            Self::RuntimeLazyFile(store) => f.debug_tuple("RuntimeLazyFile").field(store).finish(),
+            Self::Mem(store) => f.debug_tuple("Mem").field(store).finish(),
+            Self::LazyMem(store) => f.debug_tuple("LazyMem").field(store).finish(),
+            Self::RuntimeMem(store) => f.debug_tuple("RuntimeMem").field(store).finish(),
+            Self::RuntimeLazyMem(store) => f.debug_tuple("RuntimeLazyMem").field(store).finish(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Self::Uring(store) => f.debug_tuple("Uring").field(store).finish(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Self::LazyUring(store) => f.debug_tuple("LazyUring").field(store).finish(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Self::RuntimeUring(store) => f.debug_tuple("RuntimeUring").field(store).finish(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Self::RuntimeLazyUring(store) => f.debug_tuple("RuntimeLazyUring").field(store).finish(),
         }
     }
 }
@@ -146,6 +277,22 @@ where
             StoreWriter::LazyFs(writer) => writer.key(),
             StoreWriter::RuntimeFs(writer) => writer.key(),
             StoreWriter::RuntimeLazyFs(writer) => writer.key(),
+            StoreWriter::File(writer) => writer.key(),
+            StoreWriter::LazyFile(writer) => writer.key(),
+            StoreWriter::RuntimeFile(writer) => writer.key(),
+            StoreWriter::RuntimeLazyFile(writer) => writer.key(),
+            StoreWriter::Mem(writer) => writer.key(),
+            StoreWriter::LazyMem(writer) => writer.key(),
+            StoreWriter::RuntimeMem(writer) => writer.key(),
+            StoreWriter::RuntimeLazyMem(writer) => writer.key(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::Uring(writer) => writer.key(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::LazyUring(writer) => writer.key(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::RuntimeUring(writer) => writer.key(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::RuntimeLazyUring(writer) => writer.key(),
         }
     }
 
@@ -156,6 +303,22 @@ where
             StoreWriter::LazyFs(writer) => writer.judge(),
             StoreWriter::RuntimeFs(writer) => writer.judge(),
             StoreWriter::RuntimeLazyFs(writer) => writer.judge(),
+            StoreWriter::File(writer) => writer.judge(),
+            StoreWriter::LazyFile(writer) => writer.judge(),
+            StoreWriter::RuntimeFile(writer) => writer.judge(),
+            StoreWriter::RuntimeLazyFile(writer) => writer.judge(),
+            StoreWriter::Mem(writer) => writer.judge(),
+            StoreWriter::LazyMem(writer) => writer.judge(),
+            StoreWriter::RuntimeMem(writer) => writer.judge(),
+            StoreWriter::RuntimeLazyMem(writer) => writer.judge(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::Uring(writer) => writer.judge(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::LazyUring(writer) => writer.judge(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::RuntimeUring(writer) => writer.judge(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::RuntimeLazyUring(writer) => writer.judge(),
         }
     }
 
@@ -166,6 +329,22 @@ where
             StoreWriter::LazyFs(writer) => writer.force(),
             StoreWriter::RuntimeFs(writer) => writer.force(),
             StoreWriter::RuntimeLazyFs(writer) => writer.force(),
+            StoreWriter::File(writer) => writer.force(),
+            StoreWriter::LazyFile(writer) => writer.force(),
+            StoreWriter::RuntimeFile(writer) => writer.force(),
+            StoreWriter::RuntimeLazyFile(writer) => writer.force(),
+            StoreWriter::Mem(writer) => writer.force(),
+            StoreWriter::LazyMem(writer) => writer.force(),
+            StoreWriter::RuntimeMem(writer) => writer.force(),
+            StoreWriter::RuntimeLazyMem(writer) => writer.force(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::Uring(writer) => writer.force(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::LazyUring(writer) => writer.force(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::RuntimeUring(writer) => writer.force(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::RuntimeLazyUring(writer) => writer.force(),
         }
     }
 
@@ -176,6 +355,22 @@ where
             StoreWriter::LazyFs(writer) => writer.compression(),
             StoreWriter::RuntimeFs(writer) => writer.compression(),
             StoreWriter::RuntimeLazyFs(writer) => writer.compression(),
+            StoreWriter::File(writer) => writer.compression(),
+            StoreWriter::LazyFile(writer) => writer.compression(),
+            StoreWriter::RuntimeFile(writer) => writer.compression(),
+            StoreWriter::RuntimeLazyFile(writer) => writer.compression(),
+            StoreWriter::Mem(writer) => writer.compression(),
+            StoreWriter::LazyMem(writer) => writer.compression(),
+            StoreWriter::RuntimeMem(writer) => writer.compression(),
+            StoreWriter::RuntimeLazyMem(writer) => writer.compression(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::Uring(writer) => writer.compression(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::LazyUring(writer) => writer.compression(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::RuntimeUring(writer) => writer.compression(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::RuntimeLazyUring(writer) => writer.compression(),
         }
     }
 
@@ -186,6 +381,74 @@ where
             StoreWriter::LazyFs(writer) => writer.set_compression(compression),
             StoreWriter::RuntimeFs(writer) => writer.set_compression(compression),
             StoreWriter::RuntimeLazyFs(writer) => writer.set_compression(compression),
+            StoreWriter::File(writer) => writer.set_compression(compression),
+            StoreWriter::LazyFile(writer) => writer.set_compression(compression),
+            StoreWriter::RuntimeFile(writer) => writer.set_compression(compression),
+            StoreWriter::RuntimeLazyFile(writer) => writer.set_compression(compression),
+            StoreWriter::Mem(writer) => writer.set_compression(compression),
+            StoreWriter::LazyMem(writer) => writer.set_compression(compression),
+            StoreWriter::RuntimeMem(writer) => writer.set_compression(compression),
+            StoreWriter::RuntimeLazyMem(writer) => writer.set_compression(compression),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::Uring(writer) => writer.set_compression(compression),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::LazyUring(writer) => writer.set_compression(compression),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::RuntimeUring(writer) => writer.set_compression(compression),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::RuntimeLazyUring(writer) => writer.set_compression(compression),
+        }
+    }
+
+    fn set_ttl(&mut self, ttl: Duration) {
+        match self {
+            StoreWriter::None(writer) => writer.set_ttl(ttl),
+            StoreWriter::Fs(writer) => writer.set_ttl(ttl),
+            StoreWriter::LazyFs(writer) => writer.set_ttl(ttl),
+            StoreWriter::RuntimeFs(writer) => writer.set_ttl(ttl),
+            StoreWriter::RuntimeLazyFs(writer) => writer.set_ttl(ttl),
+            StoreWriter::File(writer) => writer.set_ttl(ttl),
+            StoreWriter::LazyFile(writer) => writer.set_ttl(ttl),
+            StoreWriter::RuntimeFile(writer) => writer.set_ttl(ttl),
+            StoreWriter::RuntimeLazyFile(writer) => writer.set_ttl(ttl),
+            StoreWriter::Mem(writer) => writer.set_ttl(ttl),
+            StoreWriter::LazyMem(writer) => writer.set_ttl(ttl),
+            StoreWriter::RuntimeMem(writer) => writer.set_ttl(ttl),
+            StoreWriter::RuntimeLazyMem(writer) => writer.set_ttl(ttl),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::Uring(writer) => writer.set_ttl(ttl),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::LazyUring(writer) => writer.set_ttl(ttl),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::RuntimeUring(writer) => writer.set_ttl(ttl),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::RuntimeLazyUring(writer) => writer.set_ttl(ttl),
+        }
+    }
+
+    fn set_sequence(&mut self, sequence: Sequence) {
+        match self {
+            StoreWriter::None(writer) => writer.set_sequence(sequence),
+            StoreWriter::Fs(writer) => writer.set_sequence(sequence),
+            StoreWriter::LazyFs(writer) => writer.set_sequence(sequence),
+            StoreWriter::RuntimeFs(writer) => writer.set_sequence(sequence),
+            StoreWriter::RuntimeLazyFs(writer) => writer.set_sequence(sequence),
+            StoreWriter::File(writer) => writer.set_sequence(sequence),
+            StoreWriter::LazyFile(writer) => writer.set_sequence(sequence),
+            StoreWriter::RuntimeFile(writer) => writer.set_sequence(sequence),
+            StoreWriter::RuntimeLazyFile(writer) => writer.set_sequence(sequence),
+            StoreWriter::Mem(writer) => writer.set_sequence(sequence),
+            StoreWriter::LazyMem(writer) => writer.set_sequence(sequence),
+            StoreWriter::RuntimeMem(writer) => writer.set_sequence(sequence),
+            StoreWriter::RuntimeLazyMem(writer) => writer.set_sequence(sequence),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::Uring(writer) => writer.set_sequence(sequence),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::LazyUring(writer) => writer.set_sequence(sequence),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::RuntimeUring(writer) => writer.set_sequence(sequence),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::RuntimeLazyUring(writer) => writer.set_sequence(sequence),
         }
     }
 
@@ -196,6 +459,22 @@ where
             StoreWriter::LazyFs(writer) => writer.finish(value).await,
             StoreWriter::RuntimeFs(writer) => writer.finish(value).await,
             StoreWriter::RuntimeLazyFs(writer) => writer.finish(value).await,
+            StoreWriter::File(writer) => writer.finish(value).await,
+            StoreWriter::LazyFile(writer) => writer.finish(value).await,
+            StoreWriter::RuntimeFile(writer) => writer.finish(value).await,
+            StoreWriter::RuntimeLazyFile(writer) => writer.finish(value).await,
+            StoreWriter::Mem(writer) => writer.finish(value).await,
+            StoreWriter::LazyMem(writer) => writer.finish(value).await,
+            StoreWriter::RuntimeMem(writer) => writer.finish(value).await,
+            StoreWriter::RuntimeLazyMem(writer) => writer.finish(value).await,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::Uring(writer) => writer.finish(value).await,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::LazyUring(writer) => writer.finish(value).await,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::RuntimeUring(writer) => writer.finish(value).await,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreWriter::RuntimeLazyUring(writer) => writer.finish(value).await,
         }
     }
 }
@@ -215,6 +494,22 @@ where
             StoreConfig::LazyFs(config) => Self::LazyFs(Lazy::open(config).await?),
             StoreConfig::RuntimeFs(config) => Self::RuntimeFs(Runtime::open(config).await?),
             StoreConfig::RuntimeLazyFs(config) => Self::RuntimeLazyFs(Runtime::open(config).await?),
+            StoreConfig::File(config) => Self::File(FileStore::open(config).await?),
+            StoreConfig::LazyFile(config) => Self::LazyFile(Lazy::open(config).await?),
+            StoreConfig::RuntimeFile(config) => Self::RuntimeFile(Runtime::open(config).await?),
+            StoreConfig::RuntimeLazyFile(config) => Self::RuntimeLazyFile(Runtime::open(config).await?),
+            StoreConfig::Mem(config) => Self::Mem(MemStore::open(config).await?),
+            StoreConfig::LazyMem(config) => Self::LazyMem(Lazy::open(config).await?),
+            StoreConfig::RuntimeMem(config) => Self::RuntimeMem(Runtime::open(config).await?),
+            StoreConfig::RuntimeLazyMem(config) => Self::RuntimeLazyMem(Runtime::open(config).await?),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreConfig::Uring(config) => Self::Uring(UringStore::open(config).await?),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreConfig::LazyUring(config) => Self::LazyUring(Lazy::open(config).await?),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreConfig::RuntimeUring(config) => Self::RuntimeUring(Runtime::open(config).await?),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            StoreConfig::RuntimeLazyUring(config) => Self::RuntimeLazyUring(Runtime::open(config).await?),
         };
         Ok(store)
     }
@@ -226,6 +521,22 @@ where
             Store::LazyFs(store) => store.is_ready(),
             Store::RuntimeFs(store) => store.is_ready(),
             Store::RuntimeLazyFs(store) => store.is_ready(),
+            Store::File(store) => store.is_ready(),
+            Store::LazyFile(store) => store.is_ready(),
+            Store::RuntimeFile(store) => store.is_ready(),
+            Store::RuntimeLazyFile(store) => store.is_ready(),
+            Store::Mem(store) => store.is_ready(),
+            Store::LazyMem(store) => store.is_ready(),
+            Store::RuntimeMem(store) => store.is_ready(),
+            Store::RuntimeLazyMem(store) => store.is_ready(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::Uring(store) => store.is_ready(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::LazyUring(store) => store.is_ready(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::RuntimeUring(store) => store.is_ready(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::RuntimeLazyUring(store) => store.is_ready(),
         }
     }
 
@@ -236,6 +547,22 @@ where
             Store::LazyFs(store) => store.close().await,
             Store::RuntimeFs(store) => store.close().await,
             Store::RuntimeLazyFs(store) => store.close().await,
+            Store::File(store) => store.close().await,
+            Store::LazyFile(store) => store.close().await,
+            Store::RuntimeFile(store) => store.close().await,
+            Store::RuntimeLazyFile(store) => store.close().await,
+            Store::Mem(store) => store.close().await,
+            Store::LazyMem(store) => store.close().await,
+            Store::RuntimeMem(store) => store.close().await,
+            Store::RuntimeLazyMem(store) => store.close().await,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::Uring(store) => store.close().await,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::LazyUring(store) => store.close().await,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::RuntimeUring(store) => store.close().await,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::RuntimeLazyUring(store) => store.close().await,
         }
     }
 
@@ -246,6 +573,22 @@ where
             Store::LazyFs(store) => StoreWriter::LazyFs(store.writer(key)),
             Store::RuntimeFs(store) => StoreWriter::RuntimeFs(store.writer(key)),
             Store::RuntimeLazyFs(store) => StoreWriter::RuntimeLazyFs(store.writer(key)),
+            Store::File(store) => StoreWriter::File(store.writer(key)),
+            Store::LazyFile(store) => StoreWriter::LazyFile(store.writer(key)),
+            Store::RuntimeFile(store) => StoreWriter::RuntimeFile(store.writer(key)),
+            Store::RuntimeLazyFile(store) => StoreWriter::RuntimeLazyFile(store.writer(key)),
+            Store::Mem(store) => StoreWriter::Mem(store.writer(key)),
+            Store::LazyMem(store) => StoreWriter::LazyMem(store.writer(key)),
+            Store::RuntimeMem(store) => StoreWriter::RuntimeMem(store.writer(key)),
+            Store::RuntimeLazyMem(store) => StoreWriter::RuntimeLazyMem(store.writer(key)),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::Uring(store) => StoreWriter::Uring(store.writer(key)),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::LazyUring(store) => StoreWriter::LazyUring(store.writer(key)),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::RuntimeUring(store) => StoreWriter::RuntimeUring(store.writer(key)),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::RuntimeLazyUring(store) => StoreWriter::RuntimeLazyUring(store.writer(key)),
         }
     }
 
@@ -260,6 +603,22 @@ where
             Store::LazyFs(store) => store.exists(key),
             Store::RuntimeFs(store) => store.exists(key),
             Store::RuntimeLazyFs(store) => store.exists(key),
+            Store::File(store) => store.exists(key),
+            Store::LazyFile(store) => store.exists(key),
+            Store::RuntimeFile(store) => store.exists(key),
+            Store::RuntimeLazyFile(store) => store.exists(key),
+            Store::Mem(store) => store.exists(key),
+            Store::LazyMem(store) => store.exists(key),
+            Store::RuntimeMem(store) => store.exists(key),
+            Store::RuntimeLazyMem(store) => store.exists(key),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::Uring(store) => store.exists(key),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::LazyUring(store) => store.exists(key),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::RuntimeUring(store) => store.exists(key),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::RuntimeLazyUring(store) => store.exists(key),
         }
     }
 
@@ -274,6 +633,22 @@ where
             Store::LazyFs(store) => store.lookup(key).await,
             Store::RuntimeFs(store) => store.lookup(key).await,
             Store::RuntimeLazyFs(store) => store.lookup(key).await,
+            Store::File(store) => store.lookup(key).await,
+            Store::LazyFile(store) => store.lookup(key).await,
+            Store::RuntimeFile(store) => store.lookup(key).await,
+            Store::RuntimeLazyFile(store) => store.lookup(key).await,
+            Store::Mem(store) => store.lookup(key).await,
+            Store::LazyMem(store) => store.lookup(key).await,
+            Store::RuntimeMem(store) => store.lookup(key).await,
+            Store::RuntimeLazyMem(store) => store.lookup(key).await,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::Uring(store) => store.lookup(key).await,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::LazyUring(store) => store.lookup(key).await,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::RuntimeUring(store) => store.lookup(key).await,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::RuntimeLazyUring(store) => store.lookup(key).await,
         }
     }
 
@@ -288,16 +663,100 @@ where
             Store::LazyFs(store) => store.remove(key),
             Store::RuntimeFs(store) => store.remove(key),
             Store::RuntimeLazyFs(store) => store.remove(key),
+            Store::File(store) => store.remove(key),
+            Store::LazyFile(store) => store.remove(key),
+            Store::RuntimeFile(store) => store.remove(key),
+            Store::RuntimeLazyFile(store) => store.remove(key),
+            Store::Mem(store) => store.remove(key),
+            Store::LazyMem(store) => store.remove(key),
+            Store::RuntimeMem(store) => store.remove(key),
+            Store::RuntimeLazyMem(store) => store.remove(key),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::Uring(store) => store.remove(key),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::LazyUring(store) => store.remove(key),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::RuntimeUring(store) => store.remove(key),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::RuntimeLazyUring(store) => store.remove(key),
+        }
+    }
+
+    fn sequence(&self) -> Sequence {
+        match self {
+            Store::None(store) => store.sequence(),
+            Store::Fs(store) => store.sequence(),
+            Store::LazyFs(store) => store.sequence(),
+            Store::RuntimeFs(store) => store.sequence(),
+            Store::RuntimeLazyFs(store) => store.sequence(),
+            Store::File(store) => store.sequence(),
+            Store::LazyFile(store) => store.sequence(),
+            Store::RuntimeFile(store) => store.sequence(),
+            Store::RuntimeLazyFile(store) => store.sequence(),
+            Store::Mem(store) => store.sequence(),
+            Store::LazyMem(store) => store.sequence(),
+            Store::RuntimeMem(store) => store.sequence(),
+            Store::RuntimeLazyMem(store) => store.sequence(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::Uring(store) => store.sequence(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::LazyUring(store) => store.sequence(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::RuntimeUring(store) => store.sequence(),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::RuntimeLazyUring(store) => store.sequence(),
+        }
+    }
+
+    async fn flush_removes(&self) -> Result<()> {
+        match self {
+            Store::None(store) => store.flush_removes().await,
+            Store::Fs(store) => store.flush_removes().await,
+            Store::LazyFs(store) => store.flush_removes().await,
+            Store::RuntimeFs(store) => store.flush_removes().await,
+            Store::RuntimeLazyFs(store) => store.flush_removes().await,
+            Store::File(store) => store.flush_removes().await,
+            Store::LazyFile(store) => store.flush_removes().await,
+            Store::RuntimeFile(store) => store.flush_removes().await,
+            Store::RuntimeLazyFile(store) => store.flush_removes().await,
+            Store::Mem(store) => store.flush_removes().await,
+            Store::LazyMem(store) => store.flush_removes().await,
+            Store::RuntimeMem(store) => store.flush_removes().await,
+            Store::RuntimeLazyMem(store) => store.flush_removes().await,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::Uring(store) => store.flush_removes().await,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::LazyUring(store) => store.flush_removes().await,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::RuntimeUring(store) => store.flush_removes().await,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::RuntimeLazyUring(store) => store.flush_removes().await,
         }
     }
 
-    fn clear(&self) -> Result<()> {
+    async fn clear(&self) -> Result<()> {
         match self {
-            Store::None(store) => store.clear(),
-            Store::Fs(store) => store.clear(),
-            Store::LazyFs(store) => store.clear(),
-            Store::RuntimeFs(store) => store.clear(),
-            Store::RuntimeLazyFs(store) => store.clear(),
+            Store::None(store) => store.clear().await,
+            Store::Fs(store) => store.clear().await,
+            Store::LazyFs(store) => store.clear().await,
+            Store::RuntimeFs(store) => store.clear().await,
+            Store::RuntimeLazyFs(store) => store.clear().await,
+            Store::File(store) => store.clear().await,
+            Store::LazyFile(store) => store.clear().await,
+            Store::RuntimeFile(store) => store.clear().await,
+            Store::RuntimeLazyFile(store) => store.clear().await,
+            Store::Mem(store) => store.clear().await,
+            Store::LazyMem(store) => store.clear().await,
+            Store::RuntimeMem(store) => store.clear().await,
+            Store::RuntimeLazyMem(store) => store.clear().await,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::Uring(store) => store.clear().await,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::LazyUring(store) => store.clear().await,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::RuntimeUring(store) => store.clear().await,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            Store::RuntimeLazyUring(store) => store.clear().await,
         }
     }
 }
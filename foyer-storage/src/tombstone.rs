@@ -0,0 +1,347 @@
+//  Copyright 2024 Foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! Tombstone log that makes removes durable.
+//!
+//! The design is ported from the experimental `TombstoneLog` of `foyer-experimental`. Tombstones are appended to an
+//! in-memory queue and written to the log file by a dedicated flusher thread with group commit. Each tombstone records
+//! the hash of the removed key and the sequence of the remove, entries of the same key hash with a lower sequence are
+//! filtered out during recovery.
+//!
+//! Appending a tombstone does not wait for the group commit, [`TombstoneLog::flush`] waits until all tombstones
+//! appended before are persisted.
+
+use std::{
+    collections::HashMap,
+    fs::{create_dir_all, rename, File, OpenOptions},
+    io::{ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+};
+
+use bytes::{Buf, BufMut};
+use itertools::Itertools;
+use parking_lot::{Condvar, Mutex};
+use tokio::sync::oneshot;
+
+use crate::{catalog::Sequence, device::asyncify, error::Result};
+
+const TOMBSTONE_LOG_FILENAME: &str = "foyer-tombstone-log";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tombstone {
+    pub hash: u64,
+    pub sequence: Sequence,
+}
+
+impl Tombstone {
+    pub const fn serialized_len() -> usize {
+        8 + 8
+    }
+
+    pub fn write(&self, mut buf: impl BufMut) {
+        buf.put_u64(self.hash);
+        buf.put_u64(self.sequence);
+    }
+
+    pub fn read(mut buf: impl Buf) -> Self {
+        let hash = buf.get_u64();
+        let sequence = buf.get_u64();
+        Self { hash, sequence }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TombstoneLogConfig {
+    /// Directory of the tombstone log file.
+    pub dir: PathBuf,
+}
+
+/// Tombstones loaded from the tombstone log for recovery.
+#[derive(Debug, Default)]
+pub struct Tombstones {
+    /// key hash -> (sequence, is used to filter any entry)
+    tombstones: HashMap<u64, (Sequence, AtomicBool)>,
+}
+
+impl Tombstones {
+    pub fn new(tombstones: impl IntoIterator<Item = Tombstone>) -> Self {
+        let mut map: HashMap<u64, (Sequence, AtomicBool)> = HashMap::new();
+        for tombstone in tombstones {
+            let (sequence, _) = map
+                .entry(tombstone.hash)
+                .or_insert_with(|| (tombstone.sequence, AtomicBool::new(false)));
+            *sequence = std::cmp::max(*sequence, tombstone.sequence);
+        }
+        Self { tombstones: map }
+    }
+
+    /// Return `true` if the entry with the key hash and the sequence is removed.
+    pub fn is_removed(&self, hash: u64, sequence: Sequence) -> bool {
+        match self.tombstones.get(&hash) {
+            Some((removed, used)) if sequence < *removed => {
+                used.store(true, Ordering::Relaxed);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Max sequence of the tombstones.
+    pub fn sequence(&self) -> Sequence {
+        self.tombstones
+            .values()
+            .map(|(sequence, _)| *sequence)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Tombstones that still filter entries on the device.
+    ///
+    /// The others can be dropped because all entries inserted afterwards have larger sequences.
+    pub fn used(&self) -> Vec<Tombstone> {
+        self.tombstones
+            .iter()
+            .filter(|(_, (_, used))| used.load(Ordering::Relaxed))
+            .map(|(hash, (sequence, _))| Tombstone {
+                hash: *hash,
+                sequence: *sequence,
+            })
+            .collect_vec()
+    }
+}
+
+#[derive(Debug, Default)]
+struct Inflights {
+    tombstones: Vec<Tombstone>,
+    /// Notified after the tombstones appended before are persisted.
+    waiters: Vec<oneshot::Sender<std::io::Result<()>>>,
+}
+
+impl Inflights {
+    fn is_empty(&self) -> bool {
+        self.tombstones.is_empty() && self.waiters.is_empty()
+    }
+}
+
+#[derive(Debug, Default)]
+struct TombstoneLogInner {
+    inflights: Mutex<Inflights>,
+    condvar: Condvar,
+    stopped: AtomicBool,
+}
+
+#[derive(Debug, Clone)]
+pub struct TombstoneLog {
+    inner: Arc<TombstoneLogInner>,
+    handle: Arc<Mutex<Option<JoinHandle<()>>>>,
+}
+
+impl TombstoneLog {
+    /// Load all tombstones from the tombstone log.
+    pub async fn load(config: &TombstoneLogConfig) -> Result<Vec<Tombstone>> {
+        let path = config.dir.join(TOMBSTONE_LOG_FILENAME);
+        asyncify(move || {
+            let mut file = match File::open(path) {
+                Ok(file) => file,
+                Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+                Err(e) => return Err(e.into()),
+            };
+            let mut buf = vec![];
+            file.read_to_end(&mut buf)?;
+            // Ignore the torn tail.
+            let tombstones = buf
+                .chunks_exact(Tombstone::serialized_len())
+                .map(Tombstone::read)
+                .collect_vec();
+            Ok(tombstones)
+        })
+        .await
+    }
+
+    /// Open the tombstone log and rewrite it with the given `tombstones`.
+    pub async fn open(config: TombstoneLogConfig, tombstones: Vec<Tombstone>) -> Result<Self> {
+        let dir = config.dir;
+        let file = asyncify(move || Self::rewrite(&dir, &tombstones)).await?;
+
+        let inner = Arc::new(TombstoneLogInner::default());
+        let flusher = TombstoneLogFlusher {
+            file,
+            inner: inner.clone(),
+        };
+        let handle = std::thread::spawn(move || flusher.run());
+
+        Ok(Self {
+            inner,
+            handle: Arc::new(Mutex::new(Some(handle))),
+        })
+    }
+
+    /// Append a tombstone to the log.
+    ///
+    /// The tombstone is persisted by the flusher in the background with group commit. Use [`TombstoneLog::flush`] to
+    /// wait for it.
+    pub fn append(&self, tombstone: Tombstone) {
+        self.inner.inflights.lock().tombstones.push(tombstone);
+        self.inner.condvar.notify_one();
+    }
+
+    /// Wait until all appended tombstones are persisted.
+    pub async fn flush(&self) -> Result<()> {
+        let rx = {
+            let mut inflights = self.inner.inflights.lock();
+            // All tombstones are persisted by `close`.
+            if self.inner.stopped.load(Ordering::Acquire) {
+                return Ok(());
+            }
+            let (tx, rx) = oneshot::channel();
+            inflights.waiters.push(tx);
+            rx
+        };
+        self.inner.condvar.notify_one();
+        match rx.await {
+            Ok(res) => res.map_err(Into::into),
+            // The flusher is stopped after persisting all tombstones.
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// Persist all appended tombstones and stop the flusher.
+    pub async fn close(&self) -> Result<()> {
+        let handle = self.handle.lock().take();
+        if let Some(handle) = handle {
+            {
+                let _guard = self.inner.inflights.lock();
+                self.inner.stopped.store(true, Ordering::Release);
+            }
+            self.inner.condvar.notify_one();
+            asyncify(move || handle.join().unwrap()).await;
+        }
+        Ok(())
+    }
+
+    fn rewrite(dir: &Path, tombstones: &[Tombstone]) -> Result<File> {
+        create_dir_all(dir)?;
+        let path = dir.join(TOMBSTONE_LOG_FILENAME);
+        let tmp = dir.join(format!("{}.tmp", TOMBSTONE_LOG_FILENAME));
+
+        let mut buf = Vec::with_capacity(Tombstone::serialized_len() * tombstones.len());
+        for tombstone in tombstones {
+            tombstone.write(&mut buf);
+        }
+
+        let mut file = OpenOptions::new().create(true).write(true).truncate(true).open(&tmp)?;
+        file.write_all(&buf)?;
+        file.sync_all()?;
+        rename(&tmp, &path)?;
+
+        let file = OpenOptions::new().append(true).open(&path)?;
+        Ok(file)
+    }
+}
+
+#[derive(Debug)]
+struct TombstoneLogFlusher {
+    file: File,
+    inner: Arc<TombstoneLogInner>,
+}
+
+impl TombstoneLogFlusher {
+    fn run(mut self) {
+        loop {
+            let inflights = {
+                let mut inflights = self.inner.inflights.lock();
+                self.inner.condvar.wait_while(&mut inflights, |inflights| {
+                    inflights.is_empty() && !self.inner.stopped.load(Ordering::Acquire)
+                });
+                std::mem::take(&mut *inflights)
+            };
+
+            // Drain all inflight tombstones before exit.
+            if inflights.is_empty() {
+                return;
+            }
+
+            let mut buf = Vec::with_capacity(Tombstone::serialized_len() * inflights.tombstones.len());
+            for tombstone in inflights.tombstones.iter() {
+                tombstone.write(&mut buf);
+            }
+
+            let res = self.file.write_all(&buf).and_then(|_| self.file.sync_data());
+            if let Err(e) = &res {
+                tracing::warn!("write tombstone log error: {}", e);
+            }
+            for waiter in inflights.waiters {
+                let res = res
+                    .as_ref()
+                    .map(|_| ())
+                    .map_err(|e| std::io::Error::new(e.kind(), e.to_string()));
+                let _ = waiter.send(res);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tombstone_log() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let config = TombstoneLogConfig {
+            dir: tempdir.path().into(),
+        };
+
+        assert!(TombstoneLog::load(&config).await.unwrap().is_empty());
+
+        let log = TombstoneLog::open(config.clone(), vec![]).await.unwrap();
+        for i in 0..100 {
+            log.append(Tombstone { hash: i, sequence: i });
+        }
+
+        // Flushed tombstones are persisted before close.
+        log.flush().await.unwrap();
+        assert_eq!(TombstoneLog::load(&config).await.unwrap().len(), 100);
+        log.close().await.unwrap();
+        log.flush().await.unwrap();
+
+        let tombstones = TombstoneLog::load(&config).await.unwrap();
+        assert_eq!(tombstones.len(), 100);
+
+        let tombstones = Tombstones::new(tombstones);
+        assert_eq!(tombstones.sequence(), 99);
+        assert!(tombstones.is_removed(42, 41));
+        assert!(!tombstones.is_removed(42, 42));
+        assert!(!tombstones.is_removed(100, 0));
+        assert_eq!(tombstones.used(), vec![Tombstone { hash: 42, sequence: 42 }]);
+
+        // Only used tombstones are kept after rewrite.
+        let log = TombstoneLog::open(config.clone(), tombstones.used()).await.unwrap();
+        log.append(Tombstone { hash: 1, sequence: 100 });
+        log.close().await.unwrap();
+
+        assert_eq!(
+            TombstoneLog::load(&config).await.unwrap(),
+            vec![
+                Tombstone { hash: 42, sequence: 42 },
+                Tombstone { hash: 1, sequence: 100 }
+            ]
+        );
+    }
+}
@@ -19,8 +19,8 @@ use std::{path::PathBuf, sync::Arc, time::Duration};
 
 use foyer_memory::{EvictionConfig, FifoConfig};
 use foyer_storage::{
-    test_utils::JudgeRecorder, Compression, FsDeviceConfig, FsStoreConfig, RuntimeConfig, RuntimeStoreConfig, Storage,
-    StorageExt, Store, StoreConfig,
+    test_utils::JudgeRecorder, Compression, FileDeviceConfig, FileStoreConfig, FsDeviceConfig, FsStoreConfig,
+    RuntimeConfig, RuntimeStoreConfig, Storage, StorageExt, Store, StoreConfig,
 };
 
 const KB: usize = 1024;
@@ -115,6 +115,7 @@ async fn test_fs_store() {
         clean_region_threshold: 1,
         recover_concurrency: 2,
         compression: Compression::None,
+        tombstone_log_config: None,
     });
 
     test_store(config, recorder).await;
@@ -142,6 +143,7 @@ async fn test_fs_store_zstd() {
         clean_region_threshold: 1,
         recover_concurrency: 2,
         compression: Compression::Zstd,
+        tombstone_log_config: None,
     });
 
     test_store(config, recorder).await;
@@ -169,6 +171,7 @@ async fn test_fs_store_lz4() {
         clean_region_threshold: 1,
         recover_concurrency: 2,
         compression: Compression::Lz4,
+        tombstone_log_config: None,
     });
 
     test_store(config, recorder).await;
@@ -196,6 +199,7 @@ async fn test_lazy_fs_store() {
         clean_region_threshold: 1,
         recover_concurrency: 2,
         compression: Compression::None,
+        tombstone_log_config: None,
     });
 
     test_store(config, recorder).await;
@@ -224,6 +228,7 @@ async fn test_runtime_fs_store() {
             clean_region_threshold: 1,
             recover_concurrency: 2,
             compression: Compression::None,
+            tombstone_log_config: None,
         },
         runtime: RuntimeConfig {
             worker_threads: None,
@@ -257,6 +262,7 @@ async fn test_runtime_lazy_fs_store() {
             clean_region_threshold: 1,
             recover_concurrency: 2,
             compression: Compression::None,
+            tombstone_log_config: None,
         },
         runtime: RuntimeConfig {
             worker_threads: None,
@@ -266,3 +272,66 @@ async fn test_runtime_lazy_fs_store() {
 
     test_store(config, recorder).await;
 }
+
+#[tokio::test]
+async fn test_file_store() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let recorder = Arc::new(JudgeRecorder::default());
+    let config = StoreConfig::File(FileStoreConfig {
+        name: "".to_string(),
+        eviction_config: EvictionConfig::Fifo(FifoConfig {}),
+        device_config: FileDeviceConfig {
+            path: tempdir.path().join("device"),
+            capacity: 4 * MB + 4 * KB + 2 * 64 * KB,
+            region_size: 1 * MB,
+            meta_size: 64 * KB,
+            align: 4 * KB,
+            io_size: 4 * KB,
+        },
+        catalog_bits: 1,
+        admissions: vec![recorder.clone()],
+        reinsertions: vec![recorder.clone()],
+        flushers: 1,
+        reclaimers: 1,
+        clean_region_threshold: 1,
+        recover_concurrency: 2,
+        compression: Compression::None,
+        tombstone_log_config: None,
+    });
+
+    test_store(config, recorder).await;
+}
+
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+#[tokio::test]
+async fn test_uring_store() {
+    use foyer_storage::{UringDeviceConfig, UringStoreConfig};
+
+    let tempdir = tempfile::tempdir().unwrap();
+    let recorder = Arc::new(JudgeRecorder::default());
+    let config = StoreConfig::Uring(UringStoreConfig {
+        name: "".to_string(),
+        eviction_config: EvictionConfig::Fifo(FifoConfig {}),
+        device_config: UringDeviceConfig {
+            dir: PathBuf::from(tempdir.path()),
+            capacity: 4 * MB,
+            file_size: 1 * MB,
+            align: 4 * KB,
+            io_size: 4 * KB,
+            ring_entries: 16,
+            fixed_buffers: 16,
+            fixed_buffer_size: 8 * KB,
+        },
+        catalog_bits: 1,
+        admissions: vec![recorder.clone()],
+        reinsertions: vec![recorder.clone()],
+        flushers: 1,
+        reclaimers: 1,
+        clean_region_threshold: 1,
+        recover_concurrency: 2,
+        compression: Compression::None,
+        tombstone_log_config: None,
+    });
+
+    test_store(config, recorder).await;
+}
@@ -0,0 +1,626 @@
+//  Copyright 2024 Foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! A hybrid cache that combines the in-memory cache of `foyer-memory` and the disk cache of `foyer-storage`.
+//!
+//! Entries are always inserted into the in-memory cache first. When an entry is evicted by the in-memory cache, it
+//! will be written to the disk cache by the event listener, unless the key is inserted or removed again after it. A
+//! miss of the in-memory cache falls back to the disk cache, and the entry found on disk will be refilled into the
+//! in-memory cache.
+
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    fmt::Debug,
+    future::Future,
+    hash::{BuildHasher, Hash},
+    ops::Deref,
+    sync::Arc,
+};
+
+use foyer_common::code::{StorageKey, StorageValue};
+use foyer_memory::{
+    Cache, CacheBuilder, CacheContext, CacheEntry, CacheEventListener, DefaultCacheEventListener, Entry,
+    EvictionConfig, RandomState, RemovalCause,
+};
+use foyer_storage::{AsyncStorageExt, Result, Sequence, Storage, Store, StoreConfig};
+use parking_lot::Mutex;
+
+pub type HybridCacheEntry<K, V, S = RandomState> = CacheEntry<K, HybridValue<V>, HybridCacheEventListener<K, V>, S>;
+pub type HybridEntry<K, V, ER, S = RandomState> = Entry<K, HybridValue<V>, ER, HybridCacheEventListener<K, V>, S>;
+
+/// The value of an entry of the in-memory cache, which dereferences to the value inserted or refilled.
+#[derive(Debug)]
+pub struct HybridValue<V> {
+    value: V,
+    /// The disk cache sequence taken when the value is inserted, `None` if the value is refilled from the disk cache.
+    sequence: Option<Sequence>,
+}
+
+impl<V> HybridValue<V> {
+    pub fn into_inner(self) -> V {
+        self.value
+    }
+}
+
+impl<V> Deref for HybridValue<V> {
+    type Target = V;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+/// The sequences of the latest values of the keys that are inserted into the in-memory cache and not written yet.
+///
+/// An evicted entry is written to the disk cache only if its value is still the latest one of the key, so the stale
+/// value never overwrites the disk cache state after the key is inserted or removed again.
+struct Versions<K>(Arc<Mutex<HashMap<K, Sequence>>>);
+
+impl<K> Clone for Versions<K> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<K> Versions<K>
+where
+    K: StorageKey,
+{
+    fn new() -> Self {
+        Self(Arc::new(Mutex::new(HashMap::new())))
+    }
+
+    /// Take the sequence of the new value of the key, then remove the outdated disk cache entry and drop the pending
+    /// writes of the previous values of the key.
+    fn insert<V>(&self, storage: &Store<K, V>, key: &K) -> Result<Sequence>
+    where
+        V: StorageValue,
+    {
+        let sequence = {
+            let mut versions = self.0.lock();
+            let sequence = storage.sequence();
+            versions.insert(key.clone(), sequence);
+            sequence
+        };
+        storage.remove(key)?;
+        Ok(sequence)
+    }
+
+    /// Remove the disk cache entry and drop the pending writes of the previous values of the key.
+    fn remove<Q, V>(&self, storage: &Store<K, V>, key: &Q) -> Result<bool>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        V: StorageValue,
+    {
+        self.0.lock().remove(key);
+        storage.remove(key)
+    }
+
+    fn clear(&self) {
+        self.0.lock().clear();
+    }
+}
+
+/// The error of refilling the in-memory cache from the disk cache.
+#[derive(Debug, thiserror::Error)]
+enum RefillError {
+    #[error("not found")]
+    NotFound,
+    #[error("storage error: {0}")]
+    Storage(#[from] foyer_storage::Error),
+    #[error("recv error: {0}")]
+    Recv(#[from] tokio::sync::oneshot::error::RecvError),
+}
+
+/// The event listener that writes the entries released by the in-memory cache to the disk cache.
+pub struct HybridCacheEventListener<K, V>
+where
+    K: StorageKey,
+    V: StorageValue,
+{
+    storage: Store<K, V>,
+    versions: Versions<K>,
+}
+
+impl<K, V> Debug for HybridCacheEventListener<K, V>
+where
+    K: StorageKey,
+    V: StorageValue,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HybridCacheEventListener")
+            .field("storage", &self.storage)
+            .finish()
+    }
+}
+
+impl<K, V> CacheEventListener<K, HybridValue<V>> for HybridCacheEventListener<K, V>
+where
+    K: StorageKey,
+    V: StorageValue,
+{
+    fn on_release(&self, _: K, _: HybridValue<V>, _: CacheContext, _: usize) {
+        // All released entries are handled by `on_release_with_cause`.
+    }
+
+    fn on_release_with_cause(&self, key: K, value: HybridValue<V>, _: CacheContext, _: usize, cause: RemovalCause) {
+        let mut versions = self.versions.0.lock();
+        // Refilled values are already on disk, and superseded values would overwrite the newer disk cache state.
+        match value.sequence {
+            Some(sequence) if versions.get(&key) == Some(&sequence) => {
+                versions.remove(&key);
+            }
+            _ => return,
+        }
+        // Only evicted entries are written to the disk cache. Removed, replaced, cleared and expired entries are
+        // dropped.
+        if cause != RemovalCause::Evicted {
+            return;
+        }
+        // The sequence is taken with the lock held, so the write is dropped by the disk cache if the key is inserted
+        // or removed again before it is applied.
+        let sequence = self.storage.sequence();
+        drop(versions);
+        self.storage.insert_with_sequence_async(key, value.value, sequence);
+    }
+}
+
+pub struct HybridCacheBuilder<K, V, S = RandomState>
+where
+    K: StorageKey,
+    V: StorageValue,
+    S: BuildHasher + Send + Sync + 'static,
+{
+    memory: CacheBuilder<K, HybridValue<V>, DefaultCacheEventListener<K, HybridValue<V>>, S>,
+    storage_config: StoreConfig<K, V>,
+}
+
+impl<K, V> HybridCacheBuilder<K, V, RandomState>
+where
+    K: StorageKey,
+    V: StorageValue,
+{
+    /// Create a hybrid cache builder with the capacity of the in-memory cache.
+    ///
+    /// The disk cache is disabled (`StoreConfig::None`) by default.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            memory: CacheBuilder::new(capacity),
+            storage_config: StoreConfig::None,
+        }
+    }
+}
+
+impl<K, V, S> HybridCacheBuilder<K, V, S>
+where
+    K: StorageKey,
+    V: StorageValue,
+    S: BuildHasher + Send + Sync + 'static,
+{
+    /// Set in-memory cache name, which is used as the label `foyer` of its exported metrics.
+    ///
+    /// The disk cache is labelled by the name in its own config.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.memory = self.memory.with_name(name);
+        self
+    }
+
+    /// Set in-memory cache sharding count. Entries will be distributed to different shards based on their hash.
+    /// Operations on different shard can be parallelized.
+    pub fn with_shards(mut self, shards: usize) -> Self {
+        self.memory = self.memory.with_shards(shards);
+        self
+    }
+
+    /// Set in-memory cache eviction algorithm.
+    pub fn with_eviction_config(mut self, eviction_config: impl Into<EvictionConfig>) -> Self {
+        self.memory = self.memory.with_eviction_config(eviction_config);
+        self
+    }
+
+    /// Set object pool for handles of the in-memory cache.
+    pub fn with_object_pool_capacity(mut self, object_pool_capacity: usize) -> Self {
+        self.memory = self.memory.with_object_pool_capacity(object_pool_capacity);
+        self
+    }
+
+    /// Set in-memory cache hash builder.
+    pub fn with_hash_builder<OS>(self, hash_builder: OS) -> HybridCacheBuilder<K, V, OS>
+    where
+        OS: BuildHasher + Send + Sync + 'static,
+    {
+        HybridCacheBuilder {
+            memory: self.memory.with_hash_builder(hash_builder),
+            storage_config: self.storage_config,
+        }
+    }
+
+    /// Set the weigher that calculates the charge of an entry in the in-memory cache.
+    ///
+    /// The default weigher charges `1` for each entry.
+    pub fn with_weigher(mut self, weigher: impl Fn(&K, &V) -> usize + Send + Sync + 'static) -> Self {
+        self.memory = self
+            .memory
+            .with_weigher(move |key, value: &HybridValue<V>| weigher(key, &value.value));
+        self
+    }
+
+    /// Set the disk cache config.
+    pub fn with_storage_config(mut self, storage_config: StoreConfig<K, V>) -> Self {
+        self.storage_config = storage_config;
+        self
+    }
+
+    /// Open the disk cache and build the hybrid cache.
+    pub async fn build(self) -> Result<HybridCache<K, V, S>> {
+        let storage = Store::open(self.storage_config).await?;
+        let versions = Versions::new();
+        let memory = self
+            .memory
+            .with_event_listener(HybridCacheEventListener {
+                storage: storage.clone(),
+                versions: versions.clone(),
+            })
+            .build();
+        Ok(HybridCache {
+            memory,
+            storage,
+            versions,
+        })
+    }
+}
+
+pub struct HybridCache<K, V, S = RandomState>
+where
+    K: StorageKey,
+    V: StorageValue,
+    S: BuildHasher + Send + Sync + 'static,
+{
+    memory: Cache<K, HybridValue<V>, HybridCacheEventListener<K, V>, S>,
+    storage: Store<K, V>,
+    versions: Versions<K>,
+}
+
+impl<K, V, S> Debug for HybridCache<K, V, S>
+where
+    K: StorageKey,
+    V: StorageValue,
+    S: BuildHasher + Send + Sync + 'static,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HybridCache")
+            .field("memory", &self.memory)
+            .field("storage", &self.storage)
+            .finish()
+    }
+}
+
+impl<K, V, S> Clone for HybridCache<K, V, S>
+where
+    K: StorageKey,
+    V: StorageValue,
+    S: BuildHasher + Send + Sync + 'static,
+{
+    fn clone(&self) -> Self {
+        Self {
+            memory: self.memory.clone(),
+            storage: self.storage.clone(),
+            versions: self.versions.clone(),
+        }
+    }
+}
+
+impl<K, V, S> HybridCache<K, V, S>
+where
+    K: StorageKey,
+    V: StorageValue,
+    S: BuildHasher + Send + Sync + 'static,
+{
+    /// Insert an entry into the in-memory cache with the charge calculated by the weigher.
+    ///
+    /// The outdated entry on disk with the same key will be removed.
+    pub fn insert(&self, key: K, value: V) -> Result<HybridCacheEntry<K, V, S>> {
+        self.insert_with_context(key, value, CacheContext::default())
+    }
+
+    /// Insert an entry with the given context into the in-memory cache with the charge calculated by the weigher.
+    ///
+    /// The outdated entry on disk with the same key will be removed.
+    pub fn insert_with_context(&self, key: K, value: V, context: CacheContext) -> Result<HybridCacheEntry<K, V, S>> {
+        let sequence = self.versions.insert(&self.storage, &key)?;
+        let value = HybridValue {
+            value,
+            sequence: Some(sequence),
+        };
+        let charge = (self.memory.weigher())(&key, &value);
+        Ok(self.memory.insert_with_context(key, value, charge, context))
+    }
+
+    /// Get the entry from the in-memory cache first, then the disk cache.
+    ///
+    /// The entry found on disk will be refilled into the in-memory cache. The refill is deduplicated with the other
+    /// fetches of the same key, and never overwrites an entry inserted during the disk lookup.
+    pub async fn get(&self, key: &K) -> Result<Option<HybridCacheEntry<K, V, S>>> {
+        if let Some(entry) = self.memory.get(key) {
+            return Ok(Some(entry));
+        }
+        let storage = self.storage.clone();
+        let k = key.clone();
+        let entry = self.memory.entry(key.clone(), move || async move {
+            match storage.lookup(&k).await? {
+                Some(value) => Ok((HybridValue { value, sequence: None }, CacheContext::default())),
+                None => Err(RefillError::NotFound),
+            }
+        });
+        match entry.await {
+            Ok(entry) => Ok(Some(entry)),
+            Err(RefillError::Storage(e)) => Err(e),
+            // The concurrent fetch of the key has failed.
+            Err(RefillError::NotFound | RefillError::Recv(_)) => Ok(None),
+        }
+    }
+
+    /// Check if the key exists in the in-memory cache or the disk cache.
+    pub fn contains<Q>(&self, key: &Q) -> Result<bool>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.memory.contains(key) {
+            return Ok(true);
+        }
+        self.storage.exists(key)
+    }
+
+    /// Remove the entry from both the in-memory cache and the disk cache.
+    ///
+    /// Returns `true` if the entry existed in any of them.
+    pub fn remove<Q>(&self, key: &Q) -> Result<bool>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let memory = self.memory.remove(key).is_some();
+        let storage = self.versions.remove(&self.storage, key)?;
+        Ok(memory || storage)
+    }
+
+    /// Get the entry from the in-memory cache or the disk cache, or fetch it with `f` on miss.
+    ///
+    /// Concurrent calls with the same key will be deduplicated by the in-memory cache. The charge of the entry is
+    /// calculated by the weigher.
+    pub fn entry<F, FU, ER>(&self, key: K, f: F) -> HybridEntry<K, V, ER, S>
+    where
+        F: FnOnce() -> FU + Send + 'static,
+        FU: Future<Output = std::result::Result<(V, CacheContext), ER>> + Send + 'static,
+        ER: std::error::Error + Send + 'static + From<foyer_storage::Error>,
+    {
+        let storage = self.storage.clone();
+        let versions = self.versions.clone();
+        self.memory.entry(key.clone(), move || async move {
+            if let Some(value) = storage.lookup(&key).await? {
+                return Ok((HybridValue { value, sequence: None }, CacheContext::default()));
+            }
+            let (value, context) = f().await?;
+            let sequence = versions.insert(&storage, &key)?;
+            let value = HybridValue {
+                value,
+                sequence: Some(sequence),
+            };
+            Ok((value, context))
+        })
+    }
+
+    /// Clear both the in-memory cache and the disk cache.
+    pub async fn clear(&self) -> Result<()> {
+        self.versions.clear();
+        self.memory.clear();
+        self.storage.clear().await
+    }
+
+    /// Close the disk cache.
+    pub async fn close(&self) -> Result<()> {
+        self.storage.close().await
+    }
+
+    pub fn memory(&self) -> &Cache<K, HybridValue<V>, HybridCacheEventListener<K, V>, S> {
+        &self.memory
+    }
+
+    pub fn storage(&self) -> &Store<K, V> {
+        &self.storage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, time::Duration};
+
+    use foyer_memory::FifoConfig;
+    use foyer_storage::{Compression, FsDeviceConfig, FsStoreConfig};
+
+    use super::*;
+
+    const KB: usize = 1024;
+    const MB: usize = 1024 * 1024;
+
+    const CAPACITY: usize = 4;
+
+    fn storage_config(dir: impl AsRef<Path>) -> StoreConfig<u64, Vec<u8>> {
+        StoreConfig::Fs(FsStoreConfig {
+            name: "".to_string(),
+            eviction_config: EvictionConfig::Fifo(FifoConfig {}),
+            device_config: FsDeviceConfig {
+                dir: dir.as_ref().into(),
+                capacity: 4 * MB,
+                file_size: MB,
+                align: 4 * KB,
+                io_size: 4 * KB,
+            },
+            catalog_bits: 1,
+            admissions: vec![],
+            reinsertions: vec![],
+            flushers: 1,
+            reclaimers: 1,
+            clean_region_threshold: 1,
+            recover_concurrency: 2,
+            compression: Compression::None,
+            tombstone_log_config: None,
+        })
+    }
+
+    async fn hybrid(dir: impl AsRef<Path>) -> HybridCache<u64, Vec<u8>> {
+        HybridCacheBuilder::new(CAPACITY)
+            .with_eviction_config(FifoConfig {})
+            .with_storage_config(storage_config(dir))
+            .build()
+            .await
+            .unwrap()
+    }
+
+    async fn exists_with_retry(cache: &HybridCache<u64, Vec<u8>>, key: &u64) -> bool {
+        for _ in 0..10 {
+            if cache.storage().exists(key).unwrap() {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        false
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_cache() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let cache = hybrid(tempdir.path()).await;
+
+        for i in 0..CAPACITY as u64 * 2 {
+            cache.insert(i, vec![i as u8; KB]).unwrap();
+        }
+
+        for i in 0..CAPACITY as u64 {
+            assert!(!cache.memory().contains(&i));
+            assert!(exists_with_retry(&cache, &i).await);
+        }
+
+        // Refill from disk.
+        let entry = cache.get(&0).await.unwrap().unwrap();
+        assert_eq!(**entry.value(), vec![0; KB]);
+        drop(entry);
+        assert!(cache.memory().contains(&0));
+
+        // Insert removes the outdated disk entry.
+        cache.insert(1, vec![42; KB]).unwrap();
+        assert!(!cache.storage().exists(&1).unwrap());
+        assert_eq!(**cache.get(&1).await.unwrap().unwrap().value(), vec![42; KB]);
+
+        assert!(cache.remove(&2).unwrap());
+        assert!(!cache.contains(&2).unwrap());
+        assert!(cache.get(&2).await.unwrap().is_none());
+
+        // Removed entries are not written to the disk cache.
+        let key = CAPACITY as u64 * 2 - 1;
+        assert!(cache.memory().contains(&key));
+        assert!(cache.remove(&key).unwrap());
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!cache.storage().exists(&key).unwrap());
+
+        cache.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_cache_reinsert_after_eviction() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let cache = hybrid(tempdir.path()).await;
+
+        let evict = |base: u64| {
+            for i in 0..CAPACITY as u64 {
+                cache.insert(base + i, vec![0; KB]).unwrap();
+            }
+        };
+
+        // The pending write of the evicted value is dropped after the key is inserted again.
+        cache.insert(0, vec![1; KB]).unwrap();
+        evict(100);
+        cache.insert(0, vec![2; KB]).unwrap();
+        evict(200);
+        assert!(exists_with_retry(&cache, &0).await);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!cache.memory().contains(&0));
+        assert_eq!(**cache.get(&0).await.unwrap().unwrap().value(), vec![2; KB]);
+
+        // The evicted value released after the key is inserted again is not written.
+        let entry = cache.insert(0, vec![3; KB]).unwrap();
+        evict(300);
+        cache.insert(0, vec![4; KB]).unwrap();
+        drop(entry);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!cache.storage().exists(&0).unwrap());
+        evict(400);
+        assert!(exists_with_retry(&cache, &0).await);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!cache.memory().contains(&0));
+        assert_eq!(**cache.get(&0).await.unwrap().unwrap().value(), vec![4; KB]);
+
+        // The pending write of the evicted value is dropped after the key is removed.
+        cache.insert(0, vec![5; KB]).unwrap();
+        evict(500);
+        cache.remove(&0).unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(cache.get(&0).await.unwrap().is_none());
+
+        cache.close().await.unwrap();
+    }
+
+    #[derive(Debug, thiserror::Error)]
+    enum TestError {
+        #[error("storage error: {0}")]
+        Storage(#[from] foyer_storage::Error),
+        #[error("recv error: {0}")]
+        Recv(#[from] tokio::sync::oneshot::error::RecvError),
+        #[error("unexpected fetch")]
+        Unexpected,
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_cache_entry() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let cache = hybrid(tempdir.path()).await;
+
+        for i in 0..CAPACITY as u64 * 2 {
+            cache.insert(i, vec![i as u8; KB]).unwrap();
+        }
+        assert!(exists_with_retry(&cache, &0).await);
+
+        // Disk hit, the fetch function must not be called.
+        let entry = cache
+            .entry(0, || async { Err::<(Vec<u8>, CacheContext), _>(TestError::Unexpected) })
+            .await
+            .unwrap();
+        assert_eq!(**entry.value(), vec![0; KB]);
+
+        // Miss on both tiers.
+        let entry = cache
+            .entry(100, || async {
+                Ok::<_, TestError>((vec![100; KB], CacheContext::default()))
+            })
+            .await
+            .unwrap();
+        assert_eq!(**entry.value(), vec![100; KB]);
+        assert!(cache.memory().contains(&100));
+
+        cache.close().await.unwrap();
+    }
+}
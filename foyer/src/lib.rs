@@ -16,3 +16,10 @@ pub use foyer_common as common;
 pub use foyer_intrusive as intrusive;
 pub use foyer_memory as memory;
 pub use foyer_storage as storage;
+
+mod hybrid;
+
+pub use hybrid::{
+    HybridCache, HybridCacheBuilder, HybridCacheEntry, HybridCacheEventListener, HybridEntry, HybridValue,
+};
+pub use memory::Weigher;